@@ -2,9 +2,38 @@
 
 use ziron_core::error::Result;
 use ziron_core::module::{ModuleContext, ModuleData};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::fs;
 
+/// Where the active AWS credentials come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialSource {
+    /// An IAM Identity Center (SSO) profile
+    Sso,
+    /// `AWS_ACCESS_KEY_ID` in the environment
+    Env,
+    /// A profile that assumes a role
+    Role,
+    /// Keys in the profile or obtained through `credential_process`
+    Static,
+    /// No credentials found
+    None,
+}
+
+impl CredentialSource {
+    /// Name reported in the module's `credential_source` key
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sso => "sso",
+            Self::Env => "env",
+            Self::Role => "role",
+            Self::Static => "static",
+            Self::None => "none",
+        }
+    }
+}
+
 /// AWS module implementation
 pub struct AwsModule;
 
@@ -31,6 +60,7 @@ impl AwsModule {
                     "profile": info.profile,
                     "region": info.region,
                     "account_id": info.account_id,
+                    "credential_source": info.credential_source.as_str(),
                 }),
                 cached: false,
             })
@@ -40,6 +70,7 @@ impl AwsModule {
                 data: serde_json::json!({
                     "text": "",
                     "profile": null,
+                    "credential_source": Self::credential_source("", "", "default", env_var).as_str(),
                 }),
                 cached: false,
             })
@@ -71,6 +102,11 @@ impl AwsModule {
             None
         };
 
+        let config = fs::read_to_string(&config_file).unwrap_or_default();
+        let credentials = fs::read_to_string(aws_dir.join("credentials")).unwrap_or_default();
+        let credential_source =
+            Self::credential_source(&config, &credentials, profile.as_deref().unwrap_or("default"), env_var);
+
         // Try to get account ID from credentials or environment
         let account_id = std::env::var("AWS_ACCOUNT_ID").ok();
 
@@ -79,6 +115,7 @@ impl AwsModule {
                 profile,
                 region,
                 account_id,
+                credential_source,
             }))
         } else {
             Ok(None)
@@ -88,37 +125,132 @@ impl AwsModule {
     fn read_region_from_config(config_path: &PathBuf, profile: Option<&str>) -> Result<Option<String>> {
         let content = fs::read_to_string(config_path)?;
         let profile_name = profile.unwrap_or("default");
-        let profile_section = if profile_name == "default" {
-            "[default]"
+        Ok(Self::profile_settings(&content, &Self::config_section(profile_name)).remove("region"))
+    }
+
+    /// Classify where the credentials for `profile` come from
+    ///
+    /// Environment keys win over the profile, as they do for the AWS CLI.
+    fn credential_source(
+        config: &str,
+        credentials: &str,
+        profile: &str,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> CredentialSource {
+        if env("AWS_ACCESS_KEY_ID").is_some_and(|key| !key.is_empty()) {
+            return CredentialSource::Env;
+        }
+
+        let settings = Self::profile_settings(config, &Self::config_section(profile));
+        if settings.contains_key("sso_session") || settings.contains_key("sso_start_url") {
+            CredentialSource::Sso
+        } else if settings.contains_key("role_arn") {
+            CredentialSource::Role
+        } else if settings.contains_key("credential_process")
+            || settings.contains_key("aws_access_key_id")
+            || Self::profile_settings(credentials, profile).contains_key("aws_access_key_id")
+        {
+            CredentialSource::Static
         } else {
-            &format!("[profile {}]", profile_name)
-        };
+            CredentialSource::None
+        }
+    }
 
+    /// Section name of a profile in `~/.aws/config`
+    fn config_section(profile: &str) -> String {
+        if profile == "default" {
+            profile.to_string()
+        } else {
+            format!("profile {}", profile)
+        }
+    }
+
+    /// The `key = value` settings of an INI section
+    fn profile_settings(content: &str, section: &str) -> HashMap<String, String> {
+        let mut settings = HashMap::new();
         let mut in_section = false;
         for line in content.lines() {
-            if line.trim() == profile_section {
-                in_section = true;
-                continue;
-            }
-            if line.starts_with('[') {
-                in_section = false;
+            let line = line.trim();
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                in_section = name.trim() == section;
                 continue;
             }
-            if in_section && line.trim().starts_with("region") {
-                if let Some(region) = line.split('=').nth(1) {
-                    return Ok(Some(region.trim().to_string()));
+            if in_section {
+                if let Some((key, value)) = line.split_once('=') {
+                    settings.insert(key.trim().to_string(), value.trim().to_string());
                 }
             }
         }
-
-        Ok(None)
+        settings
     }
 }
 
+/// Read an environment variable
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
 #[derive(Debug, Clone)]
 struct AwsInfo {
     profile: Option<String>,
     region: Option<String>,
     account_id: Option<String>,
+    credential_source: CredentialSource,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+[default]
+region = eu-central-1
+
+[profile dev]
+sso_session = corp
+sso_account_id = 123456789012
+
+[profile admin]
+role_arn = arn:aws:iam::123456789012:role/Admin
+source_profile = default
+
+[profile vault]
+credential_process = aws-vault export --format=json vault
+";
+
+    const CREDENTIALS: &str = "\
+[default]
+aws_access_key_id = AKIAEXAMPLE
+aws_secret_access_key = secret
+";
+
+    fn source(profile: &str, env: &[(&str, &str)]) -> CredentialSource {
+        let env: HashMap<String, String> = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        AwsModule::credential_source(CONFIG, CREDENTIALS, profile, |name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn test_credential_source_from_profile() {
+        assert_eq!(source("dev", &[]), CredentialSource::Sso);
+        assert_eq!(source("admin", &[]), CredentialSource::Role);
+        assert_eq!(source("vault", &[]), CredentialSource::Static);
+        assert_eq!(source("default", &[]), CredentialSource::Static);
+        assert_eq!(source("missing", &[]), CredentialSource::None);
+    }
+
+    #[test]
+    fn test_credential_source_from_env() {
+        let env = [("AWS_ACCESS_KEY_ID", "ASIAEXAMPLE"), ("AWS_SESSION_TOKEN", "token")];
+        assert_eq!(source("dev", &env), CredentialSource::Env);
+        assert_eq!(source("missing", &[("AWS_ACCESS_KEY_ID", "AKIAEXAMPLE")]), CredentialSource::Env);
+        assert_eq!(source("missing", &[("AWS_ACCESS_KEY_ID", "")]), CredentialSource::None);
+    }
+
+    #[test]
+    fn test_region_from_profile_section() {
+        let settings = AwsModule::profile_settings(CONFIG, &AwsModule::config_section("default"));
+        assert_eq!(settings.get("region").map(String::as_str), Some("eu-central-1"));
+        assert!(AwsModule::profile_settings(CONFIG, "dev").is_empty());
+    }
 }
 
@@ -1,16 +1,20 @@
 //! Azure module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
 use std::process::Command;
+use std::time::Duration;
 
 /// Azure module implementation
 pub struct AzureModule;
 
 impl AzureModule {
     /// Fetch Azure subscription information
-    pub fn fetch_data(_context: &ModuleContext) -> Result<ModuleData> {
-        let azure_info = Self::get_azure_info()?;
+    pub fn fetch_data(_context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let azure_info = match Self::get_azure_info(timeout) {
+            Ok(info) => info,
+            Err(e) => return Ok(ModuleData::error("azure", &e)),
+        };
 
         if let Some(info) = azure_info {
             let mut parts = vec![];
@@ -44,46 +48,14 @@ impl AzureModule {
         }
     }
 
-    fn get_azure_info() -> Result<Option<AzureInfo>> {
+    fn get_azure_info(timeout: Duration) -> Result<Option<AzureInfo>> {
         // Try to get from az CLI
-        let subscription_output = Command::new("az")
-            .args(&["account", "show", "--query", "name", "-o", "tsv"])
-            .output();
-
-        let subscription = subscription_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let sub = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !sub.is_empty() {
-                        Some(sub)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        let subscription = run_tool(Command::new("az").args(["account", "show", "--query", "name", "-o", "tsv"]), timeout)?
+            .filter(|sub| !sub.is_empty());
 
         // Get account
-        let account_output = Command::new("az")
-            .args(&["account", "show", "--query", "user.name", "-o", "tsv"])
-            .output();
-
-        let account = account_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let acc = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !acc.is_empty() {
-                        Some(acc)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        let account = run_tool(Command::new("az").args(["account", "show", "--query", "user.name", "-o", "tsv"]), timeout)?
+            .filter(|acc| !acc.is_empty());
 
         if subscription.is_some() || account.is_some() {
             Ok(Some(AzureInfo {
@@ -0,0 +1,121 @@
+//! Active cloud module for Ziron: shows whichever of AWS, GCP or Azure is in use
+
+use serde::Deserialize;
+use std::time::Duration;
+use ziron_core::error::Result;
+use ziron_core::module::{ModuleContext, ModuleData};
+
+/// Environment variables that mark each provider as active, checked in order
+const PROVIDER_ENV: &[(Provider, &[&str])] = &[
+    (Provider::Aws, &["AWS_PROFILE", "AWS_DEFAULT_PROFILE", "AWS_ACCESS_KEY_ID"]),
+    (Provider::Gcp, &["GOOGLE_CLOUD_PROJECT", "GCP_PROJECT", "CLOUDSDK_CORE_PROJECT"]),
+    (Provider::Azure, &["AZURE_SUBSCRIPTION_ID", "AZURE_CONFIG_DIR"]),
+];
+
+/// A cloud provider with its own module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Aws,
+    Gcp,
+    Azure,
+}
+
+impl Provider {
+    /// Name reported in the module's `provider` key
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Aws => "aws",
+            Self::Gcp => "gcp",
+            Self::Azure => "azure",
+        }
+    }
+}
+
+/// Cloud module settings (`[module_config.cloud]`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CloudConfig {
+    /// Always show this provider instead of detecting one from the environment
+    pub provider: Option<Provider>,
+}
+
+/// Cloud module implementation
+pub struct CloudModule;
+
+impl CloudModule {
+    /// Fetch the active provider's data; `Ok(None)` if no provider is active
+    pub fn fetch_data_with_config(
+        context: &ModuleContext,
+        config: &CloudConfig,
+        timeout: Duration,
+    ) -> Result<Option<ModuleData>> {
+        let Some(provider) = config.provider.or_else(|| Self::detect_provider(env_var)) else {
+            return Ok(None);
+        };
+
+        let mut data = match provider {
+            Provider::Aws => ziron_module_aws::AwsModule::fetch_data(context)?,
+            Provider::Gcp => ziron_module_gcp::GcpModule::fetch_data(context, timeout)?,
+            Provider::Azure => ziron_module_azure::AzureModule::fetch_data(context, timeout)?,
+        };
+        data.module = "cloud".to_string();
+        data.data["provider"] = provider.as_str().into();
+        Ok(Some(data))
+    }
+
+    /// Fetch the active provider's data, with empty text when no provider is active
+    pub fn fetch_data_or_empty(context: &ModuleContext, config: &CloudConfig, timeout: Duration) -> Result<ModuleData> {
+        Ok(Self::fetch_data_with_config(context, config, timeout)?.unwrap_or_else(|| ModuleData {
+            module: "cloud".to_string(),
+            data: serde_json::json!({
+                "text": "",
+                "provider": null,
+            }),
+            cached: false,
+        }))
+    }
+
+    /// The first provider whose environment variables are set
+    pub fn detect_provider(env: impl Fn(&str) -> Option<String>) -> Option<Provider> {
+        PROVIDER_ENV
+            .iter()
+            .find(|(_, vars)| vars.iter().any(|var| env(var).is_some_and(|value| !value.is_empty())))
+            .map(|(provider, _)| *provider)
+    }
+}
+
+/// Read an environment variable
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(env: &[(&str, &str)]) -> Option<Provider> {
+        CloudModule::detect_provider(|name| {
+            env.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_detect_provider_from_env() {
+        assert_eq!(detect(&[("AWS_PROFILE", "dev")]), Some(Provider::Aws));
+        assert_eq!(detect(&[("GOOGLE_CLOUD_PROJECT", "my-project")]), Some(Provider::Gcp));
+        assert_eq!(detect(&[("AZURE_SUBSCRIPTION_ID", "0000")]), Some(Provider::Azure));
+        assert_eq!(detect(&[("AWS_PROFILE", ""), ("CLOUDSDK_CORE_PROJECT", "p")]), Some(Provider::Gcp));
+        assert_eq!(detect(&[]), None);
+    }
+
+    #[test]
+    fn test_pinned_provider_is_tagged() {
+        let context = ModuleContext::from_env().unwrap();
+        let config = CloudConfig { provider: Some(Provider::Aws) };
+
+        let data = CloudModule::fetch_data_with_config(&context, &config, Duration::from_secs(2)).unwrap().unwrap();
+        assert_eq!(data.module, "cloud");
+        assert_eq!(data.data["provider"], "aws");
+    }
+}
@@ -1,17 +1,21 @@
 //! Docker module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
 /// Docker module implementation
 pub struct DockerModule;
 
 impl DockerModule {
     /// Fetch Docker context information
-    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let docker_info = Self::get_docker_info(&context.current_dir)?;
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let docker_info = match Self::get_docker_info(&context.current_dir, timeout) {
+            Ok(info) => info,
+            Err(e) => return Ok(ModuleData::error("docker", &e)),
+        };
 
         if let Some(info) = docker_info {
             let mut parts = vec![];
@@ -51,7 +55,7 @@ impl DockerModule {
         }
     }
 
-    fn get_docker_info(path: &PathBuf) -> Result<Option<DockerInfo>> {
+    fn get_docker_info(path: &PathBuf, timeout: Duration) -> Result<Option<DockerInfo>> {
         // Check for docker-compose.yml
         let compose_file = path.join("docker-compose.yml");
         let compose_project = if compose_file.exists() {
@@ -71,39 +75,12 @@ impl DockerModule {
         let has_dockerfile = path.join("Dockerfile").exists();
 
         // Get Docker context
-        let context_output = Command::new("docker")
-            .args(&["context", "show"])
-            .output();
-
-        let context = context_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let ctx = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if ctx != "default" {
-                        Some(ctx)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        let context = run_tool(Command::new("docker").args(["context", "show"]), timeout)?
+            .filter(|ctx| ctx != "default");
 
         // Get running container count
-        let container_output = Command::new("docker")
-            .args(&["ps", "-q"])
-            .output();
-
-        let container_count = container_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    Some(String::from_utf8_lossy(&o.stdout).lines().count())
-                } else {
-                    None
-                }
-            })
+        let container_count = run_tool(Command::new("docker").args(["ps", "-q"]), timeout)?
+            .map(|ids| ids.lines().count())
             .unwrap_or(0);
 
         // Only return info if we have something meaningful
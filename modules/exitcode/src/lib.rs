@@ -1,34 +1,115 @@
 //! Exit code module for Ziron
 
+use serde::Deserialize;
 use ziron_core::error::Result;
 use ziron_core::module::{ModuleContext, ModuleData};
 
+/// Linux signal names, indexed by signal number - 1
+const SIGNAL_NAMES: &[&str] = &[
+    "SIGHUP", "SIGINT", "SIGQUIT", "SIGILL", "SIGTRAP", "SIGABRT", "SIGBUS", "SIGFPE", "SIGKILL", "SIGUSR1",
+    "SIGSEGV", "SIGUSR2", "SIGPIPE", "SIGALRM", "SIGTERM", "SIGSTKFLT", "SIGCHLD", "SIGCONT", "SIGSTOP",
+    "SIGTSTP", "SIGTTIN", "SIGTTOU", "SIGURG", "SIGXCPU", "SIGXFSZ", "SIGVTALRM", "SIGPROF", "SIGWINCH",
+    "SIGIO", "SIGPWR", "SIGSYS",
+];
+
+/// Exit code module settings (`[module_config.exitcode]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExitCodeConfig {
+    /// Show `SIGSEGV` instead of `139` for commands killed by a signal
+    pub show_signal: bool,
+}
+
+impl Default for ExitCodeConfig {
+    fn default() -> Self {
+        Self { show_signal: true }
+    }
+}
+
 /// Exit code module implementation
 pub struct ExitCodeModule;
 
 impl ExitCodeModule {
     /// Fetch exit code information
-    pub fn fetch_data(_context: &ModuleContext) -> Result<ModuleData> {
-        // Get last exit code from environment (if set)
-        let exit_code = std::env::var("ZIRON_LAST_EXIT_CODE")
-            .ok()
-            .and_then(|s| s.parse::<i32>().ok())
-            .unwrap_or(0);
-
-        let text = if exit_code != 0 {
-            format!("{}", exit_code)
-        } else {
-            String::new()
+    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
+        Self::fetch_data_with_config(context, &ExitCodeConfig::default())
+    }
+
+    /// Fetch exit code information using module settings
+    pub fn fetch_data_with_config(context: &ModuleContext, config: &ExitCodeConfig) -> Result<ModuleData> {
+        Ok(Self::build_data(context.exit_code.unwrap_or(0), config))
+    }
+
+    fn build_data(exit_code: i32, config: &ExitCodeConfig) -> ModuleData {
+        let signal = Self::signal_name(exit_code);
+
+        let text = match signal {
+            Some(signal) if config.show_signal => signal.to_string(),
+            _ if exit_code != 0 => format!("{}", exit_code),
+            _ => String::new(),
         };
 
-        Ok(ModuleData {
+        ModuleData {
             module: "exitcode".to_string(),
             data: serde_json::json!({
                 "text": text,
                 "code": exit_code,
+                "signal": signal,
             }),
             cached: false,
-        })
+        }
+    }
+
+    /// Name of the signal behind a shell's `128 + N` exit code
+    fn signal_name(exit_code: i32) -> Option<&'static str> {
+        if !(129..=192).contains(&exit_code) {
+            return None;
+        }
+        SIGNAL_NAMES.get((exit_code - 129) as usize).copied()
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_names() {
+        let data = ExitCodeModule::build_data(130, &ExitCodeConfig::default()).data;
+        assert_eq!(data["text"], "SIGINT");
+        assert_eq!(data["code"], 130);
+        assert_eq!(data["signal"], "SIGINT");
+
+        let data = ExitCodeModule::build_data(139, &ExitCodeConfig::default()).data;
+        assert_eq!(data["text"], "SIGSEGV");
+
+        // Real-time signals have no name, so the code is shown
+        let data = ExitCodeModule::build_data(170, &ExitCodeConfig::default()).data;
+        assert_eq!(data["text"], "170");
+        assert!(data["signal"].is_null());
+    }
+
+    #[test]
+    fn test_exit_code_from_context() {
+        let mut context = ModuleContext::for_test("/tmp");
+        assert_eq!(ExitCodeModule::fetch_data(&context).unwrap().data["text"], "");
+
+        context.exit_code = Some(2);
+        let data = ExitCodeModule::fetch_data(&context).unwrap().data;
+        assert_eq!(data["text"], "2");
+        assert_eq!(data["code"], 2);
+    }
+
+    #[test]
+    fn test_show_signal_disabled() {
+        let config = ExitCodeConfig { show_signal: false };
+        let data = ExitCodeModule::build_data(139, &config).data;
+        assert_eq!(data["text"], "139");
+        assert_eq!(data["signal"], "SIGSEGV");
+
+        let data = ExitCodeModule::build_data(1, &config).data;
+        assert_eq!(data["text"], "1");
+        assert!(data["signal"].is_null());
+        assert_eq!(ExitCodeModule::build_data(0, &config).data["text"], "");
+    }
+}
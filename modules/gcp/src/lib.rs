@@ -1,16 +1,20 @@
 //! Google Cloud Platform module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
 use std::process::Command;
+use std::time::Duration;
 
 /// GCP module implementation
 pub struct GcpModule;
 
 impl GcpModule {
     /// Fetch GCP configuration information
-    pub fn fetch_data(_context: &ModuleContext) -> Result<ModuleData> {
-        let gcp_info = Self::get_gcp_info()?;
+    pub fn fetch_data(_context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let gcp_info = match Self::get_gcp_info(timeout) {
+            Ok(info) => info,
+            Err(e) => return Ok(ModuleData::error("gcp", &e)),
+        };
 
         if let Some(info) = gcp_info {
             let mut parts = vec![];
@@ -45,49 +49,21 @@ impl GcpModule {
         }
     }
 
-    fn get_gcp_info() -> Result<Option<GcpInfo>> {
+    fn get_gcp_info(timeout: Duration) -> Result<Option<GcpInfo>> {
         // Get GCP project from environment
         let project = std::env::var("GCP_PROJECT")
             .or_else(|_| std::env::var("GOOGLE_CLOUD_PROJECT"))
             .ok();
 
         // Try to get from gcloud CLI
-        let gcloud_project = Command::new("gcloud")
-            .args(&["config", "get-value", "project"])
-            .output()
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let proj = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !proj.is_empty() {
-                        Some(proj)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        let gcloud_project = run_tool(Command::new("gcloud").args(["config", "get-value", "project"]), timeout)?
+            .filter(|proj| !proj.is_empty());
 
         let project = project.or(gcloud_project);
 
         // Get account
-        let account = Command::new("gcloud")
-            .args(&["config", "get-value", "account"])
-            .output()
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let acc = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !acc.is_empty() {
-                        Some(acc)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        let account = run_tool(Command::new("gcloud").args(["config", "get-value", "account"]), timeout)?
+            .filter(|acc| !acc.is_empty());
 
         // Get region
         let region = std::env::var("GCP_REGION")
@@ -0,0 +1,181 @@
+//! In-process repository reading with gix (the `libgit2` feature)
+//!
+//! Produces the same `GitInfo` as the `git` subprocess path, from a single repository open.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use gix::bstr::{BString, ByteSlice};
+use gix::status::index_worktree::iter::Summary;
+
+use super::{GitInfo, GitModule, GitOperation, GitStatus};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Read the repository containing `path`; errors mean the caller should fall back to `git`
+pub(crate) fn read(path: &Path) -> Result<Option<GitInfo>, Error> {
+    let repo = gix::discover(path)?;
+    let Some(workdir) = repo.workdir() else {
+        return Ok(None);
+    };
+    let repo_root = workdir.canonicalize()?;
+    let operation = GitOperation::detect(repo.git_dir());
+
+    let head_id = repo.head_id().ok();
+    let commit_hash = head_id.map(|id| id.shorten_or_id().to_string());
+    let head_id = head_id.map(|id| id.detach());
+    let tag = head_id.map(|id| head_tag(&repo, id)).transpose()?.flatten();
+    let stash_count = stash_count(&repo)?;
+
+    let head_name = repo.head_name()?;
+    let Some(head_name) = head_name else {
+        if let Some(tag) = tag {
+            return Ok(Some(GitInfo {
+                branch: format!("HEAD@{}", tag),
+                status: GitStatus::Clean,
+                ahead: None,
+                behind: None,
+                commit_hash,
+                tag: Some(tag),
+                stash_count,
+                modified_count: 0,
+                staged_count: 0,
+                untracked_count: 0,
+                conflicts_count: 0,
+                remote_name: None,
+                branch_description: None,
+                operation,
+                detached: true,
+                repo_root,
+            }));
+        }
+
+        // A plain `git checkout <sha>`, or a rebase or bisect in progress
+        let (status, modified, staged, untracked, conflicts) = status(&repo)?;
+        return Ok(Some(GitInfo {
+            branch: GitModule::detached_branch(commit_hash.as_deref()),
+            status,
+            ahead: None,
+            behind: None,
+            commit_hash,
+            tag: None,
+            stash_count,
+            modified_count: modified,
+            staged_count: staged,
+            untracked_count: untracked,
+            conflicts_count: conflicts,
+            remote_name: None,
+            branch_description: None,
+            operation,
+            detached: true,
+            repo_root,
+        }));
+    };
+
+    let branch = head_name.shorten().to_string();
+    let (status, modified, staged, untracked, conflicts) = status(&repo)?;
+    let (ahead, behind) = match head_id {
+        Some(head_id) => ahead_behind(&repo, head_id, head_name.as_ref())?,
+        None => (None, None),
+    };
+    let config = repo.config_snapshot();
+    let config_value = |key: String| {
+        config
+            .string(key.as_str())
+            .map(|value| value.to_str_lossy().trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    Ok(Some(GitInfo {
+        remote_name: config_value(format!("branch.{}.remote", branch)),
+        branch_description: config_value(format!("branch.{}.description", branch)),
+        branch,
+        status,
+        ahead,
+        behind,
+        commit_hash,
+        tag,
+        stash_count,
+        modified_count: modified,
+        staged_count: staged,
+        untracked_count: untracked,
+        conflicts_count: conflicts,
+        operation,
+        detached: false,
+        repo_root,
+    }))
+}
+
+/// A tag pointing at `head`, like `git describe --tags --exact-match HEAD`
+fn head_tag(repo: &gix::Repository, head: gix::ObjectId) -> Result<Option<String>, Error> {
+    let references = repo.references()?;
+    for reference in references.tags()? {
+        let mut reference = reference?;
+        if reference.peel_to_id()?.detach() == head {
+            return Ok(Some(reference.name().shorten().to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Number of entries in the stash reflog
+fn stash_count(repo: &gix::Repository) -> Result<usize, Error> {
+    let Some(stash) = repo.try_find_reference("refs/stash")? else {
+        return Ok(0);
+    };
+    let mut log = stash.log_iter();
+    Ok(log.all()?.map(|entries| entries.count()).unwrap_or(0))
+}
+
+/// Commits ahead of and behind the branch's configured upstream
+fn ahead_behind(
+    repo: &gix::Repository,
+    head: gix::ObjectId,
+    branch: &gix::refs::FullNameRef,
+) -> Result<(Option<usize>, Option<usize>), Error> {
+    let Some(upstream) = repo.branch_remote_tracking_ref_name(branch, gix::remote::Direction::Fetch).transpose()? else {
+        return Ok((None, None));
+    };
+    let Some(mut upstream) = repo.try_find_reference(upstream.as_ref())? else {
+        return Ok((None, None));
+    };
+    let upstream = upstream.peel_to_id()?.detach();
+
+    let ahead = repo.rev_walk([head]).with_hidden([upstream]).all()?.count();
+    let behind = repo.rev_walk([upstream]).with_hidden([head]).all()?.count();
+    Ok((Some(ahead), Some(behind)))
+}
+
+/// Counts matching `parse_status` over `git status --porcelain`
+fn status(repo: &gix::Repository) -> Result<(GitStatus, usize, usize, usize, usize), Error> {
+    let mut modified = 0;
+    let mut untracked = 0;
+    let mut conflicted = HashSet::new();
+    let mut staged = HashSet::new();
+
+    let items = repo.status(gix::progress::Discard)?.into_iter(Vec::<BString>::new())?;
+    for item in items {
+        match item? {
+            gix::status::Item::IndexWorktree(item) => match item.summary() {
+                Some(Summary::Added) => untracked += 1,
+                Some(Summary::Conflict) => {
+                    conflicted.insert(item.rela_path().to_owned());
+                }
+                Some(_) => modified += 1,
+                None => {}
+            },
+            gix::status::Item::TreeIndex(change) => {
+                staged.insert(change.location().to_owned());
+            }
+        }
+    }
+    let staged = staged.difference(&conflicted).count();
+    let conflicts = conflicted.len();
+
+    let status = if modified == 0 && staged == 0 && untracked == 0 && conflicts == 0 {
+        GitStatus::Clean
+    } else {
+        GitStatus::Dirty
+    };
+    Ok((status, modified, staged, untracked, conflicts))
+}
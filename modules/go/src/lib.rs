@@ -1,18 +1,19 @@
 //! Go module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
-use std::path::PathBuf;
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::process::Command;
+use std::time::Duration;
 
 /// Go module implementation
 pub struct GoModule;
 
 impl GoModule {
     /// Fetch Go module information
-    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let go_info = Self::get_go_info(&context.current_dir)?;
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let go_info = Self::get_go_info(&context.current_dir, timeout)?;
 
         if let Some(info) = go_info {
             let mut parts = vec![];
@@ -25,12 +26,17 @@ impl GoModule {
                 parts.push(module.clone());
             }
 
+            if info.workspace {
+                parts.push("workspace".to_string());
+            }
+
             Ok(ModuleData {
                 module: "go".to_string(),
                 data: serde_json::json!({
                     "text": parts.join(" "),
                     "version": info.version,
                     "module": info.module_name,
+                    "workspace": info.workspace,
                 }),
                 cached: false,
             })
@@ -40,67 +46,113 @@ impl GoModule {
                 data: serde_json::json!({
                     "text": "",
                     "version": null,
+                    "workspace": false,
                 }),
                 cached: false,
             })
         }
     }
 
-    fn get_go_info(path: &PathBuf) -> Result<Option<GoInfo>> {
-        // Check for go.mod
+    fn get_go_info(path: &PathBuf, timeout: Duration) -> Result<Option<GoInfo>> {
+        // Check for go.mod, and a go.work workspace here or in a parent directory
         let go_mod = path.join("go.mod");
-        if !go_mod.exists() {
+        let go_work = Self::find_go_work(path);
+        if !go_mod.exists() && go_work.is_none() {
             return Ok(None);
         }
 
         // Read go.mod to get module name and Go version
-        let content = fs::read_to_string(&go_mod)?;
-        let mut module_name = None;
-        let mut go_version = None;
+        let (module_name, mut go_version) = if go_mod.exists() {
+            Self::parse_directives(&fs::read_to_string(&go_mod)?)
+        } else {
+            (None, None)
+        };
 
-        for line in content.lines() {
-            if line.starts_with("module ") {
-                module_name = line.split_whitespace().nth(1).map(|s| s.to_string());
-            } else if line.starts_with("go ") {
-                go_version = line.split_whitespace().nth(1).map(|s| s.to_string());
+        // Fall back to the workspace's Go version
+        if let Some(go_work) = &go_work {
+            if go_version.is_none() {
+                go_version = Self::parse_directives(&fs::read_to_string(go_work)?).1;
             }
         }
 
         // Get Go version from system if not in go.mod
-        let version = go_version.or_else(|| {
-            Command::new("go")
-                .args(&["version"])
-                .output()
-                .ok()
-                .and_then(|o| {
-                    if o.status.success() {
-                        let output = String::from_utf8_lossy(&o.stdout);
-                        // Extract version like "go1.21.0" -> "1.21.0"
-                        output
-                            .split_whitespace()
-                            .nth(2)
-                            .and_then(|v| v.strip_prefix("go"))
-                            .map(|s| s.to_string())
-                    } else {
-                        None
-                    }
-                })
-        });
+        let version = match go_version {
+            Some(version) => Some(version),
+            // Extract version like "go version go1.21.0 linux/amd64" -> "1.21.0"
+            None => run_tool(Command::new("go").arg("version"), timeout)?.and_then(|output| {
+                output.split_whitespace().nth(2).and_then(|v| v.strip_prefix("go")).map(|s| s.to_string())
+            }),
+        };
 
         if module_name.is_some() || version.is_some() {
             Ok(Some(GoInfo {
                 version,
                 module_name,
+                workspace: go_work.is_some(),
             }))
         } else {
             Ok(None)
         }
     }
+
+    /// Find the nearest `go.work` in `path` or its ancestors
+    fn find_go_work(path: &Path) -> Option<PathBuf> {
+        path.ancestors()
+            .map(|ancestor| ancestor.join("go.work"))
+            .find(|go_work| go_work.is_file())
+    }
+
+    /// The `module` and `go` directives of a go.mod or go.work file
+    fn parse_directives(content: &str) -> (Option<String>, Option<String>) {
+        let mut module_name = None;
+        let mut go_version = None;
+
+        for line in content.lines() {
+            if line.starts_with("module ") {
+                module_name = line.split_whitespace().nth(1).map(|s| s.to_string());
+            } else if line.starts_with("go ") {
+                go_version = line.split_whitespace().nth(1).map(|s| s.to_string());
+            }
+        }
+        (module_name, go_version)
+    }
 }
 
 #[derive(Debug, Clone)]
 struct GoInfo {
     version: Option<String>,
     module_name: Option<String>,
+    workspace: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_go_work_workspace() {
+        let root = tempfile::TempDir::new().unwrap();
+        fs::write(root.path().join("go.work"), "go 1.22\n\nuse (\n\t./api\n)\n").unwrap();
+        let api = root.path().join("api");
+        let docs = root.path().join("docs");
+        fs::create_dir_all(&api).unwrap();
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(api.join("go.mod"), "module example.com/api\n\ngo 1.21\n").unwrap();
+
+        let info = GoModule::get_go_info(&root.path().to_path_buf(), Duration::from_secs(2)).unwrap().unwrap();
+        assert!(info.workspace);
+        assert_eq!(info.version.as_deref(), Some("1.22"));
+        assert_eq!(info.module_name, None);
+
+        // A module inside the workspace reports both
+        let info = GoModule::get_go_info(&api, Duration::from_secs(2)).unwrap().unwrap();
+        assert!(info.workspace);
+        assert_eq!(info.version.as_deref(), Some("1.21"));
+        assert_eq!(info.module_name.as_deref(), Some("example.com/api"));
+
+        let info = GoModule::get_go_info(&docs, Duration::from_secs(2)).unwrap().unwrap();
+        assert!(info.workspace);
+        assert_eq!(info.module_name, None);
+    }
 }
 
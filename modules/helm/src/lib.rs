@@ -0,0 +1,120 @@
+//! Helm module for Ziron
+
+use ziron_core::error::Result;
+use ziron_core::module::{ModuleContext, ModuleData};
+use std::fs;
+use std::path::Path;
+
+/// Helm module implementation
+pub struct HelmModule;
+
+impl HelmModule {
+    /// Fetch Helm chart information
+    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
+        let helm_info = Self::get_helm_info(&context.current_dir)?;
+
+        if let Some(info) = helm_info {
+            let mut parts = vec![];
+
+            match (&info.chart, &info.version) {
+                (Some(chart), Some(version)) => parts.push(format!("{} v{}", chart, version)),
+                (Some(chart), None) => parts.push(chart.clone()),
+                (None, Some(version)) => parts.push(format!("v{}", version)),
+                (None, None) => {}
+            }
+
+            if let Some(ref namespace) = info.namespace {
+                parts.push(format!("ns:{}", namespace));
+            }
+
+            Ok(ModuleData {
+                module: "helm".to_string(),
+                data: serde_json::json!({
+                    "text": parts.join(" "),
+                    "chart": info.chart,
+                    "version": info.version,
+                    "namespace": info.namespace,
+                }),
+                cached: false,
+            })
+        } else {
+            Ok(ModuleData {
+                module: "helm".to_string(),
+                data: serde_json::json!({
+                    "text": "",
+                    "chart": null,
+                }),
+                cached: false,
+            })
+        }
+    }
+
+    fn get_helm_info(path: &Path) -> Result<Option<HelmInfo>> {
+        // Check for Chart.yaml
+        let chart_file = path.join("Chart.yaml");
+        if !chart_file.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&chart_file)?;
+        let namespace = std::env::var("HELM_NAMESPACE")
+            .ok()
+            .filter(|ns| !ns.is_empty());
+
+        Ok(Some(HelmInfo {
+            chart: Self::top_level_value(&content, "name"),
+            version: Self::top_level_value(&content, "version"),
+            namespace,
+        }))
+    }
+
+    /// Read a top-level `key: value` scalar from Chart.yaml
+    fn top_level_value(content: &str, key: &str) -> Option<String> {
+        content
+            .lines()
+            // Nested keys (e.g. under `dependencies:`) are indented
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim() == key)
+            .map(|(_, value)| {
+                let value = value.split(" #").next().unwrap_or("").trim();
+                value.trim_matches(|c| c == '"' || c == '\'').to_string()
+            })
+            .filter(|value| !value.is_empty())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HelmInfo {
+    chart: Option<String>,
+    version: Option<String>,
+    namespace: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHART_YAML: &str = "\
+apiVersion: v2
+name: my-app # the chart name
+description: A Helm chart for Kubernetes
+type: application
+version: \"1.2.3\"
+appVersion: \"4.5.6\"
+dependencies:
+  - name: redis
+    version: 17.0.0
+";
+
+    #[test]
+    fn test_chart_yaml() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(HelmModule::get_helm_info(dir.path()).unwrap().is_none());
+
+        fs::write(dir.path().join("Chart.yaml"), CHART_YAML).unwrap();
+        let info = HelmModule::get_helm_info(dir.path()).unwrap().unwrap();
+        assert_eq!(info.chart.as_deref(), Some("my-app"));
+        assert_eq!(info.version.as_deref(), Some("1.2.3"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Jujutsu (jj) module for Ziron
+
+use ziron_core::error::Result;
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// `jj log` template printing one field per line; the description goes last as it may be empty
+const LOG_TEMPLATE: &str = r#"change_id.short() ++ "\n" ++ bookmarks ++ "\n" ++ if(empty, "true", "false") ++ "\n" ++ description.first_line()"#;
+
+/// Jujutsu module implementation
+pub struct JjModule;
+
+impl JjModule {
+    /// Fetch the working-copy change of the enclosing jj repository
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let jj_info = Self::get_jj_info(&context.current_dir, timeout)?;
+
+        if let Some(info) = jj_info {
+            let mut parts = vec![info.change_id.clone()];
+
+            if let Some(ref bookmark) = info.bookmark {
+                parts.push(bookmark.clone());
+            }
+            if info.empty {
+                parts.push("(empty)".to_string());
+            }
+
+            Ok(ModuleData {
+                module: "jj".to_string(),
+                data: serde_json::json!({
+                    "text": parts.join(" "),
+                    "change_id": info.change_id,
+                    "bookmark": info.bookmark,
+                    "empty": info.empty,
+                    "description": info.description,
+                }),
+                cached: false,
+            })
+        } else {
+            Ok(ModuleData {
+                module: "jj".to_string(),
+                data: serde_json::json!({
+                    "text": "",
+                    "change_id": null,
+                    "bookmark": null,
+                    "empty": null,
+                    "description": null,
+                }),
+                cached: false,
+            })
+        }
+    }
+
+    fn get_jj_info(path: &Path, timeout: Duration) -> Result<Option<JjInfo>> {
+        // Check if directory is inside a jj repository
+        if !path.ancestors().any(|ancestor| ancestor.join(".jj").is_dir()) {
+            return Ok(None);
+        }
+
+        // `None` if jj isn't installed or the repository can't be read; `--ignore-working-copy`
+        // keeps a prompt from snapshotting the working copy (and racing the user's own jj commands)
+        let output = run_tool(
+            Command::new("jj")
+                .args(["log", "--ignore-working-copy", "-r", "@", "--no-graph", "--color", "never", "-T", LOG_TEMPLATE])
+                .current_dir(path),
+            timeout,
+        )?;
+
+        Ok(output.and_then(|output| Self::parse_log(&output)))
+    }
+
+    /// Parse the output of `jj log` with `LOG_TEMPLATE`
+    fn parse_log(output: &str) -> Option<JjInfo> {
+        let mut lines = output.lines();
+        let change_id = lines.next()?.trim().to_string();
+        if change_id.is_empty() {
+            return None;
+        }
+
+        // Bookmarks are space separated; a trailing `*` marks one that differs from its remote
+        let bookmark = lines
+            .next()?
+            .split_whitespace()
+            .next()
+            .map(|bookmark| bookmark.trim_end_matches('*').to_string());
+        let empty = lines.next()?.trim() == "true";
+        let description = lines.next().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string);
+
+        Some(JjInfo {
+            change_id,
+            bookmark,
+            empty,
+            description,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct JjInfo {
+    change_id: String,
+    bookmark: Option<String>,
+    empty: bool,
+    description: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log() {
+        let info = JjModule::parse_log("kxryzmor\nmain* feature\nfalse\nAdd the jj module").unwrap();
+        assert_eq!(info.change_id, "kxryzmor");
+        assert_eq!(info.bookmark.as_deref(), Some("main"));
+        assert!(!info.empty);
+        assert_eq!(info.description.as_deref(), Some("Add the jj module"));
+
+        let info = JjModule::parse_log("qpvuntsm\n\ntrue\n").unwrap();
+        assert_eq!(info.bookmark, None);
+        assert!(info.empty);
+        assert_eq!(info.description, None);
+
+        assert!(JjModule::parse_log("").is_none());
+    }
+
+    #[test]
+    fn test_outside_repository() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut context = ModuleContext::from_env().unwrap();
+        context.current_dir = dir.path().to_path_buf();
+
+        let data = JjModule::fetch_data(&context, Duration::from_secs(2)).unwrap();
+        assert_eq!(data.module, "jj");
+        assert_eq!(data.data["text"], "");
+        assert!(data.data.get("empty").is_some_and(|empty| empty.is_null()));
+    }
+}
@@ -1,17 +1,51 @@
 //! Kubernetes module for Ziron
 
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use ziron_core::config::PerformanceConfig;
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Compiled danger patterns by source; `None` for patterns that aren't valid regexes
+static PATTERN_CACHE: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+
+/// Kubernetes module settings (`[module_config.kubernetes]`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct KubernetesConfig {
+    /// Context/cluster patterns (regex or plain substring) that mark a dangerous cluster
+    pub danger_patterns: Vec<String>,
+}
 
 /// Kubernetes module implementation
 pub struct KubernetesModule;
 
 impl KubernetesModule {
     /// Fetch Kubernetes context information
-    pub fn fetch_data(_context: &ModuleContext) -> Result<ModuleData> {
-        let k8s_info = Self::get_k8s_info()?;
+    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
+        let timeout = PerformanceConfig::default().command_timeout();
+        Self::fetch_data_with_config(context, &KubernetesConfig::default(), timeout)
+    }
 
+    /// Fetch Kubernetes context information using module settings, killing kubectl after `timeout`
+    pub fn fetch_data_with_config(
+        _context: &ModuleContext,
+        config: &KubernetesConfig,
+        timeout: Duration,
+    ) -> Result<ModuleData> {
+        match Self::get_k8s_info(timeout) {
+            Ok(k8s_info) => Ok(Self::build_data(k8s_info, config)),
+            Err(e) => Ok(ModuleData::error("kubernetes", &e)),
+        }
+    }
+
+    fn build_data(k8s_info: Option<K8sInfo>, config: &KubernetesConfig) -> ModuleData {
         if let Some(info) = k8s_info {
             let mut parts = vec![];
             
@@ -29,105 +63,91 @@ impl KubernetesModule {
                 parts.push(format!("cluster:{}", cluster));
             }
 
-            Ok(ModuleData {
+            let danger = Self::is_dangerous(&info, &config.danger_patterns);
+            if danger {
+                parts.push("warning".to_string());
+            }
+
+            ModuleData {
                 module: "kubernetes".to_string(),
                 data: serde_json::json!({
                     "text": parts.join(" "),
                     "context": info.context,
                     "namespace": info.namespace,
                     "cluster": info.cluster,
+                    "danger": danger,
                 }),
                 cached: false,
-            })
+            }
         } else {
-            Ok(ModuleData {
+            ModuleData {
                 module: "kubernetes".to_string(),
                 data: serde_json::json!({
                     "text": "",
                     "context": null,
+                    "danger": false,
                 }),
                 cached: false,
-            })
+            }
         }
     }
 
-    fn get_k8s_info() -> Result<Option<K8sInfo>> {
-        // Get kubeconfig path
-        let kubeconfig = std::env::var("KUBECONFIG")
-            .ok()
-            .or_else(|| {
-                std::env::var("HOME")
-                    .ok()
-                    .map(|home| format!("{}/.kube/config", home))
-            });
-
-        if kubeconfig.is_none() {
+    /// Check the context and cluster names against the configured danger patterns
+    fn is_dangerous(info: &K8sInfo, patterns: &[String]) -> bool {
+        let names: Vec<&str> = [info.context.as_deref(), info.cluster.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut cache = PATTERN_CACHE
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        patterns.iter().any(|pattern| {
+            let regex = cache
+                .entry(pattern.clone())
+                .or_insert_with(|| Regex::new(pattern).ok());
+            // Patterns that aren't valid regexes are matched as plain substrings
+            match regex {
+                Some(re) => names.iter().any(|name| re.is_match(name)),
+                None => names.iter().any(|name| name.contains(pattern.as_str())),
+            }
+        })
+    }
+
+    fn get_k8s_info(timeout: Duration) -> Result<Option<K8sInfo>> {
+        let paths = Self::kubeconfig_paths(std::env::var_os("KUBECONFIG"), std::env::var_os("HOME"));
+
+        if paths.is_empty() {
             return Ok(None);
         }
 
-        // Get current context
-        let context_output = Command::new("kubectl")
-            .args(&["config", "current-context"])
-            .output();
-
-        let context = context_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let ctx = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !ctx.is_empty() {
-                        Some(ctx)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        // Reading the kubeconfig ourselves avoids kubectl's startup cost; kubectl handles what we can't parse
+        if let Ok(info) = Self::read_kubeconfig(&paths) {
+            return Ok(info);
+        }
+
+        // Get current context (kubectl not being installed just hides the module)
+        let context = run_tool(Command::new("kubectl").args(["config", "current-context"]), timeout)?
+            .filter(|ctx| !ctx.is_empty());
 
         if context.is_none() {
             return Ok(None);
         }
 
         // Get namespace
-        let namespace_output = Command::new("kubectl")
-            .args(&["config", "view", "--minify", "-o", "jsonpath={..namespace}"])
-            .output();
-
-        let namespace = namespace_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let ns = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !ns.is_empty() {
-                        Some(ns)
-                    } else {
-                        Some("default".to_string())
-                    }
-                } else {
-                    None
-                }
-            });
+        let namespace = run_tool(
+            Command::new("kubectl").args(["config", "view", "--minify", "-o", "jsonpath={..namespace}"]),
+            timeout,
+        )?
+        .map(|ns| if ns.is_empty() { "default".to_string() } else { ns });
 
         // Get cluster name
-        let cluster_output = Command::new("kubectl")
-            .args(&["config", "view", "--minify", "-o", "jsonpath={.clusters[0].name}"])
-            .output();
-
-        let cluster = cluster_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let cl = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !cl.is_empty() {
-                        Some(cl)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        let cluster = run_tool(
+            Command::new("kubectl").args(["config", "view", "--minify", "-o", "jsonpath={.clusters[0].name}"]),
+            timeout,
+        )?
+        .filter(|cl| !cl.is_empty());
 
         Ok(Some(K8sInfo {
             context,
@@ -135,6 +155,72 @@ impl KubernetesModule {
             cluster,
         }))
     }
+
+    /// Kubeconfig files from `$KUBECONFIG` (a path list), or `~/.kube/config`
+    fn kubeconfig_paths(kubeconfig: Option<OsString>, home: Option<OsString>) -> Vec<PathBuf> {
+        match kubeconfig.filter(|value| !value.is_empty()) {
+            Some(value) => std::env::split_paths(&value).filter(|path| !path.as_os_str().is_empty()).collect(),
+            None => home.map(|home| Path::new(&home).join(".kube").join("config")).into_iter().collect(),
+        }
+    }
+
+    /// Resolve the current context from the kubeconfig files, merged like kubectl does
+    ///
+    /// The first file setting `current-context` wins, as does the first context of each name.
+    /// Missing files are skipped; a file that can't be parsed is an error.
+    fn read_kubeconfig(paths: &[PathBuf]) -> std::result::Result<Option<K8sInfo>, serde_yaml::Error> {
+        let mut current_context = None;
+        let mut contexts: Vec<NamedContext> = Vec::new();
+
+        for path in paths {
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let kubeconfig: KubeConfig = serde_yaml::from_str(&content)?;
+
+            if current_context.is_none() {
+                current_context = kubeconfig.current_context.filter(|ctx| !ctx.is_empty());
+            }
+            for context in kubeconfig.contexts {
+                if !contexts.iter().any(|existing| existing.name == context.name) {
+                    contexts.push(context);
+                }
+            }
+        }
+
+        let Some(name) = current_context else {
+            return Ok(None);
+        };
+        let entry = contexts.into_iter().find(|context| context.name == name).map(|context| context.context);
+
+        Ok(Some(K8sInfo {
+            namespace: entry.as_ref().map(|entry| entry.namespace.clone().unwrap_or_else(|| "default".to_string())),
+            cluster: entry.and_then(|entry| entry.cluster).filter(|cluster| !cluster.is_empty()),
+            context: Some(name),
+        }))
+    }
+}
+
+/// The parts of a kubeconfig file the module reads
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct KubeConfig {
+    current_context: Option<String>,
+    contexts: Vec<NamedContext>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedContext {
+    name: String,
+    #[serde(default)]
+    context: ContextEntry,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ContextEntry {
+    cluster: Option<String>,
+    namespace: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -144,3 +230,111 @@ struct K8sInfo {
     cluster: Option<String>,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(context: &str, cluster: &str) -> Option<K8sInfo> {
+        Some(K8sInfo {
+            context: Some(context.to_string()),
+            namespace: Some("default".to_string()),
+            cluster: Some(cluster.to_string()),
+        })
+    }
+
+    fn config(patterns: &[&str]) -> KubernetesConfig {
+        KubernetesConfig {
+            danger_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_danger_pattern_matches() {
+        let data = KubernetesModule::build_data(info("prod-eu", "eks-main"), &config(&["^prod-"]));
+        assert_eq!(data.data["danger"], true);
+        assert!(data.data["text"].as_str().unwrap().ends_with("warning"));
+
+        // Plain substrings also match, including against the cluster name
+        let data = KubernetesModule::build_data(info("admin", "eks-production"), &config(&["production"]));
+        assert_eq!(data.data["danger"], true);
+
+        // Invalid regexes fall back to substring matching
+        let data = KubernetesModule::build_data(info("live(1)", "c1"), &config(&["live("]));
+        assert_eq!(data.data["danger"], true);
+    }
+
+    const KUBECONFIG: &str = "\
+apiVersion: v1
+kind: Config
+current-context: dev
+contexts:
+- name: dev
+  context:
+    cluster: dev-cluster
+    namespace: web
+- name: prod
+  context:
+    cluster: prod-cluster
+";
+
+    #[test]
+    fn test_read_kubeconfig() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config");
+        std::fs::write(&path, KUBECONFIG).unwrap();
+
+        let info = KubernetesModule::read_kubeconfig(&[path]).unwrap().unwrap();
+        assert_eq!(info.context.as_deref(), Some("dev"));
+        assert_eq!(info.namespace.as_deref(), Some("web"));
+        assert_eq!(info.cluster.as_deref(), Some("dev-cluster"));
+
+        let text = KubernetesModule::build_data(Some(info), &KubernetesConfig::default()).data["text"].clone();
+        assert_eq!(text, "dev ns:web cluster:dev-cluster");
+    }
+
+    #[test]
+    fn test_read_merged_kubeconfig() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        std::fs::write(&first, "current-context: prod
+contexts:
+- name: prod
+  context:
+    cluster: override
+").unwrap();
+        std::fs::write(&second, KUBECONFIG).unwrap();
+
+        let kubeconfig = std::env::join_paths([&first, &dir.path().join("missing"), &second]).unwrap();
+        let paths = KubernetesModule::kubeconfig_paths(Some(kubeconfig), None);
+        assert_eq!(paths.len(), 3);
+
+        // The first file's current context and context entry win
+        let info = KubernetesModule::read_kubeconfig(&paths).unwrap().unwrap();
+        assert_eq!(info.context.as_deref(), Some("prod"));
+        assert_eq!(info.namespace.as_deref(), Some("default"));
+        assert_eq!(info.cluster.as_deref(), Some("override"));
+
+        // Unparseable files are left to kubectl
+        std::fs::write(&first, "contexts: [").unwrap();
+        assert!(KubernetesModule::read_kubeconfig(&paths).is_err());
+    }
+
+    #[test]
+    fn test_kubeconfig_paths_default_to_home() {
+        let paths = KubernetesModule::kubeconfig_paths(None, Some("/home/user".into()));
+        assert_eq!(paths, vec![PathBuf::from("/home/user/.kube/config")]);
+        assert!(KubernetesModule::kubeconfig_paths(None, None).is_empty());
+    }
+
+    #[test]
+    fn test_danger_pattern_does_not_match() {
+        let data = KubernetesModule::build_data(info("staging", "eks-dev"), &config(&["^prod", "production"]));
+        assert_eq!(data.data["danger"], false);
+        assert!(!data.data["text"].as_str().unwrap().contains("warning"));
+
+        let data = KubernetesModule::build_data(info("prod-eu", "eks-main"), &KubernetesConfig::default());
+        assert_eq!(data.data["danger"], false);
+    }
+}
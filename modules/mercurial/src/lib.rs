@@ -1,17 +1,18 @@
 //! Mercurial module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
 /// Mercurial module implementation
 pub struct MercurialModule;
 
 impl MercurialModule {
     /// Fetch Mercurial status information
-    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let hg_info = Self::get_hg_info(&context.current_dir)?;
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let hg_info = Self::get_hg_info(&context.current_dir, timeout)?;
 
         if let Some(info) = hg_info {
             let mut parts = vec![info.branch.clone()];
@@ -49,7 +50,7 @@ impl MercurialModule {
         }
     }
 
-    fn get_hg_info(path: &PathBuf) -> Result<Option<HgInfo>> {
+    fn get_hg_info(path: &PathBuf, timeout: Duration) -> Result<Option<HgInfo>> {
         // Check if directory is a Mercurial repository
         let hg_dir = path.join(".hg");
         if !hg_dir.exists() {
@@ -57,57 +58,22 @@ impl MercurialModule {
         }
 
         // Get branch
-        let branch_output = Command::new("hg")
-            .args(&["branch"])
-            .current_dir(path)
-            .output()?;
-
-        if !branch_output.status.success() {
+        let Some(branch) = run_tool(Command::new("hg").arg("branch").current_dir(path), timeout)? else {
             return Ok(None);
-        }
-
-        let branch = String::from_utf8_lossy(&branch_output.stdout)
-            .trim()
-            .to_string();
+        };
 
         // Get revision
-        let rev_output = Command::new("hg")
-            .args(&["id", "-n"])
-            .current_dir(path)
-            .output()?;
-
-        let revision = if rev_output.status.success() {
-            String::from_utf8_lossy(&rev_output.stdout)
-                .trim()
-                .to_string()
-        } else {
-            String::new()
-        };
+        let revision = run_tool(Command::new("hg").args(["id", "-n"]).current_dir(path), timeout)?.unwrap_or_default();
 
         // Get bookmark if any
-        let bookmark_output = Command::new("hg")
-            .args(&["bookmark", "--active"])
-            .current_dir(path)
-            .output();
-
-        let bookmark = bookmark_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-                } else {
-                    None
-                }
-            })
+        let bookmark = run_tool(Command::new("hg").args(["bookmark", "--active"]).current_dir(path), timeout)?
             .filter(|s| !s.is_empty());
 
         // Get status
-        let status_output = Command::new("hg")
-            .args(&["status", "--quiet"])
-            .current_dir(path)
-            .output()?;
+        let status_output =
+            run_tool(Command::new("hg").args(["status", "--quiet"]).current_dir(path), timeout)?.unwrap_or_default();
 
-        let (status, modified) = Self::parse_status(&status_output.stdout);
+        let (status, modified) = Self::parse_status(status_output.as_bytes());
 
         Ok(Some(HgInfo {
             branch,
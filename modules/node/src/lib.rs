@@ -1,18 +1,19 @@
 //! Node.js module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
 use std::path::PathBuf;
 use std::fs;
 use std::process::Command;
+use std::time::Duration;
 
 /// Node.js module implementation
 pub struct NodeModule;
 
 impl NodeModule {
     /// Fetch Node.js version information
-    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let node_info = Self::get_node_info(&context.current_dir)?;
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let node_info = Self::get_node_info(&context.current_dir, timeout)?;
 
         let mut parts = vec![];
         
@@ -36,24 +37,15 @@ impl NodeModule {
         })
     }
 
-    fn get_node_info(path: &PathBuf) -> Result<NodeInfo> {
+    fn get_node_info(path: &PathBuf, timeout: Duration) -> Result<NodeInfo> {
         // Check for .nvmrc or .node-version
         let version_from_file = Self::read_version_file(path)?;
         
         // Get version from system if not in file
-        let version = version_from_file.or_else(|| {
-            Command::new("node")
-                .arg("--version")
-                .output()
-                .ok()
-                .and_then(|o| {
-                    if o.status.success() {
-                        Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-                    } else {
-                        None
-                    }
-                })
-        });
+        let version = match version_from_file {
+            Some(version) => Some(version),
+            None => run_tool(Command::new("node").arg("--version"), timeout)?,
+        };
 
         // Detect package manager
         let package_manager = Self::detect_package_manager(path)?;
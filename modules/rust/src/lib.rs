@@ -1,18 +1,19 @@
 //! Rust toolchain module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
 use std::path::PathBuf;
 use std::fs;
 use std::process::Command;
+use std::time::Duration;
 
 /// Rust toolchain module implementation
 pub struct RustModule;
 
 impl RustModule {
     /// Fetch Rust toolchain version information
-    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let rust_info = Self::get_rust_info(&context.current_dir)?;
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let rust_info = Self::get_rust_info(&context.current_dir, timeout)?;
 
         let mut parts = vec![];
         
@@ -36,28 +37,17 @@ impl RustModule {
         })
     }
 
-    fn get_rust_info(path: &PathBuf) -> Result<RustInfo> {
+    fn get_rust_info(path: &PathBuf, timeout: Duration) -> Result<RustInfo> {
         // Check for rust-toolchain.toml
         let version_from_file = Self::read_rust_toolchain(path)?;
         
         // Get version from system if not in file
-        let version = version_from_file.or_else(|| {
-            Command::new("rustc")
-                .arg("--version")
-                .output()
-                .ok()
-                .and_then(|o| {
-                    if o.status.success() {
-                        let version = String::from_utf8_lossy(&o.stdout)
-                            .trim()
-                            .to_string();
-                        // Extract just the version number (e.g., "rustc 1.70.0" -> "1.70.0")
-                        version.split_whitespace().nth(1).map(|s| s.to_string())
-                    } else {
-                        None
-                    }
-                })
-        });
+        let version = match version_from_file {
+            Some(version) => Some(version),
+            // Extract just the version number (e.g., "rustc 1.70.0" -> "1.70.0")
+            None => run_tool(Command::new("rustc").arg("--version"), timeout)?
+                .and_then(|output| output.split_whitespace().nth(1).map(|s| s.to_string())),
+        };
 
         // Check for Cargo.toml
         let cargo_toml = path.join("Cargo.toml");
@@ -0,0 +1,60 @@
+//! Session timer module for Ziron
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use ziron_core::error::Result;
+use ziron_core::module::{ModuleContext, ModuleData};
+
+/// Session module implementation
+///
+/// ziron-shell sends its start time as `ModuleContext::session_start_ms`; other
+/// shells export it as `$ZIRON_SESSION_START`.
+pub struct SessionModule;
+
+impl SessionModule {
+    /// Fetch how long the shell session has been open
+    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        let session_ms = context.session_start_ms.map(|start| now_ms.saturating_sub(start));
+
+        Ok(ModuleData {
+            module: "session".to_string(),
+            data: serde_json::json!({
+                "text": session_ms.map(Self::format_duration).unwrap_or_default(),
+                "session_ms": session_ms,
+            }),
+            cached: false,
+        })
+    }
+
+    /// Human-readable duration using its two largest units (`1h23m`, `5m12s`, `42s`)
+    pub fn format_duration(ms: u64) -> String {
+        let secs = ms / 1000;
+        let (days, hours, minutes, seconds) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+        if days > 0 {
+            format!("{}d{}h", days, hours)
+        } else if hours > 0 {
+            format!("{}h{}m", hours, minutes)
+        } else if minutes > 0 {
+            format!("{}m{}s", minutes, seconds)
+        } else {
+            format!("{}s", seconds)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(SessionModule::format_duration(0), "0s");
+        assert_eq!(SessionModule::format_duration(42_900), "42s");
+        assert_eq!(SessionModule::format_duration(312_000), "5m12s");
+        assert_eq!(SessionModule::format_duration((3600 + 23 * 60 + 5) * 1000), "1h23m");
+        assert_eq!(SessionModule::format_duration((2 * 86_400 + 3 * 3600) * 1000), "2d3h");
+    }
+}
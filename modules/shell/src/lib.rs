@@ -0,0 +1,136 @@
+//! Shell module for Ziron
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use ziron_core::error::Result;
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
+
+/// Shells known to print their version for `--version` and exit; others (`dash`, `ziron-shell`, ...)
+/// might start an interactive session instead
+const VERSION_SHELLS: &[&str] = &["bash", "zsh", "fish", "nu", "tcsh", "ksh"];
+
+/// Shell versions by executable path; a shell's version doesn't change while we run
+static VERSION_CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+/// Shell module implementation
+pub struct ShellModule;
+
+impl ShellModule {
+    /// Fetch the host shell and its version
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let shell = Self::shell_name(&context.shell);
+        let version = if VERSION_SHELLS.contains(&shell.as_str()) {
+            Self::cached_version(&context.shell, timeout)?
+        } else {
+            None
+        };
+
+        let text = match &version {
+            Some(version) => format!("{} {}", shell, version),
+            None => shell.clone(),
+        };
+
+        Ok(ModuleData {
+            module: "shell".to_string(),
+            data: serde_json::json!({
+                "text": text,
+                "shell": shell,
+                "version": version,
+            }),
+            cached: false,
+        })
+    }
+
+    /// Shell name from its path (`/usr/bin/zsh` -> `zsh`, `-bash` -> `bash`)
+    fn shell_name(shell: &str) -> String {
+        let name = Path::new(shell)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(shell);
+        // Login shells are started with a leading dash
+        name.trim_start_matches('-').to_string()
+    }
+
+    fn cached_version(shell: &str, timeout: Duration) -> Result<Option<String>> {
+        let cache = VERSION_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        if let Some(version) = cache.lock().ok().and_then(|cache| cache.get(shell).cloned()) {
+            return Ok(version);
+        }
+
+        let version = run_tool(Command::new(shell).arg("--version"), timeout)?
+            .and_then(|output| Self::parse_version(&output));
+        if let Ok(mut cache) = cache.lock() {
+            cache.insert(shell.to_string(), version.clone());
+        }
+        Ok(version)
+    }
+
+    /// Extract the version from `--version` output
+    ///
+    /// Handles `GNU bash, version 5.2.15(1)-release`, `zsh 5.9 (x86_64)` and
+    /// `fish, version 3.6.0`.
+    fn parse_version(output: &str) -> Option<String> {
+        output
+            .lines()
+            .next()?
+            .split_whitespace()
+            .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+            .map(|word| {
+                word.chars()
+                    .take_while(|c| c.is_ascii_digit() || *c == '.')
+                    .collect::<String>()
+                    .trim_end_matches('.')
+                    .to_string()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_shell_from_context() {
+        let context = ModuleContext {
+            shell: "/nonexistent/ziron-test/zsh".to_string(),
+            ..ModuleContext::for_test("/")
+        };
+
+        let data = ShellModule::fetch_data(&context, Duration::from_secs(2)).unwrap();
+        assert_eq!(data.data["shell"], "zsh");
+        assert_eq!(data.data["version"], serde_json::Value::Null);
+        assert_eq!(data.data["text"], "zsh");
+    }
+
+    #[test]
+    fn test_unknown_shell_not_run() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let shell = dir.path().join("myshell");
+        let marker = dir.path().join("ran");
+        std::fs::write(&shell, format!("#!/bin/sh\ntouch '{}'\necho 'myshell 1.0'\n", marker.display())).unwrap();
+        std::fs::set_permissions(&shell, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+
+        let context = ModuleContext {
+            shell: shell.display().to_string(),
+            ..ModuleContext::for_test("/")
+        };
+        let data = ShellModule::fetch_data(&context, Duration::from_secs(2)).unwrap();
+        assert_eq!(data.data["text"], "myshell");
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            ShellModule::parse_version("GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)\nCopyright"),
+            Some("5.2.15".to_string())
+        );
+        assert_eq!(ShellModule::parse_version("zsh 5.9 (x86_64-debian-linux-gnu)"), Some("5.9".to_string()));
+        assert_eq!(ShellModule::parse_version("fish, version 3.6.0"), Some("3.6.0".to_string()));
+        assert_eq!(ShellModule::parse_version("no version here"), None);
+        assert_eq!(ShellModule::shell_name("-bash"), "bash");
+    }
+}
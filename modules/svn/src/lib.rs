@@ -1,17 +1,18 @@
 //! SVN (Subversion) module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_command_with_timeout, run_tool, ModuleContext, ModuleData};
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
 /// SVN module implementation
 pub struct SvnModule;
 
 impl SvnModule {
     /// Fetch SVN status information
-    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let svn_info = Self::get_svn_info(&context.current_dir)?;
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let svn_info = Self::get_svn_info(&context.current_dir, timeout)?;
 
         if let Some(info) = svn_info {
             let mut parts = vec![info.branch_or_path.clone()];
@@ -53,7 +54,7 @@ impl SvnModule {
         }
     }
 
-    fn get_svn_info(path: &PathBuf) -> Result<Option<SvnInfo>> {
+    fn get_svn_info(path: &PathBuf, timeout: Duration) -> Result<Option<SvnInfo>> {
         // Check if directory is an SVN repository
         let svn_dir = path.join(".svn");
         if !svn_dir.exists() {
@@ -61,16 +62,15 @@ impl SvnModule {
         }
 
         // Get SVN info
-        let info_output = Command::new("svn")
-            .args(&["info", "--show-item", "revision", "url", "relative-url"])
-            .current_dir(path)
-            .output()?;
-
-        if !info_output.status.success() {
+        let Some(info_text) = run_tool(
+            Command::new("svn")
+                .args(["info", "--show-item", "revision", "url", "relative-url"])
+                .current_dir(path),
+            timeout,
+        )?
+        else {
             return Ok(None);
-        }
-
-        let info_text = String::from_utf8_lossy(&info_output.stdout);
+        };
         let lines: Vec<&str> = info_text.lines().collect();
         
         let revision = lines.get(0).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
@@ -80,10 +80,8 @@ impl SvnModule {
         let branch_or_path = Self::extract_branch_from_url(&url);
 
         // Get status
-        let status_output = Command::new("svn")
-            .args(&["status", "--quiet"])
-            .current_dir(path)
-            .output()?;
+        // Untrimmed, since the status columns start with spaces
+        let status_output = run_command_with_timeout("svn", &["status", "--quiet"], Some(path), timeout)?;
 
         let (status, modified, conflicts) = Self::parse_status(&status_output.stdout);
 
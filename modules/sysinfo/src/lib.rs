@@ -1,25 +1,207 @@
 //! System info module for Ziron
 
+use serde::Deserialize;
 use ziron_core::error::Result;
 use ziron_core::module::{ModuleContext, ModuleData};
 
+/// System info module settings (`[module_config.sysinfo]`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SysInfoConfig {
+    /// Show only the host part of the hostname (`host.example.com` -> `host`)
+    pub strip_domain: bool,
+    /// Color the segment with the hostname's `host_color`
+    pub color_by_host: bool,
+    /// When the username is shown
+    pub show_user: ShowUser,
+    /// Hide the username when it is this (usually your own login)
+    pub hide_user_if: Option<String>,
+    /// Only show the segment in SSH sessions
+    pub only_ssh: bool,
+}
+
+/// When the sysinfo segment includes the username
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowUser {
+    #[default]
+    Always,
+    RootOnly,
+}
+
+impl SysInfoConfig {
+    /// Whether the username should be shown for `user`
+    pub fn shows_user(&self, user: &str) -> bool {
+        if self.hide_user_if.as_deref() == Some(user) {
+            return false;
+        }
+        match self.show_user {
+            ShowUser::Always => true,
+            ShowUser::RootOnly => user == "root",
+        }
+    }
+}
+
 /// System info module implementation
 pub struct SysInfoModule;
 
 impl SysInfoModule {
     /// Fetch system information
     pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let text = format!("{}@{}", context.user, context.hostname);
+        Self::fetch_data_with_config(context, &SysInfoConfig::default())
+    }
+
+    /// Fetch system information using module settings
+    pub fn fetch_data_with_config(context: &ModuleContext, config: &SysInfoConfig) -> Result<ModuleData> {
+        Ok(Self::build_data(context, config, is_ssh_session(|name| std::env::var(name).ok())))
+    }
+
+    fn build_data(context: &ModuleContext, config: &SysInfoConfig, ssh: bool) -> ModuleData {
+        let hostname = if config.strip_domain {
+            short_hostname(&context.hostname)
+        } else {
+            context.hostname.as_str()
+        };
+        let show_user = config.shows_user(&context.user);
+        let text = if config.only_ssh && !ssh {
+            String::new()
+        } else if show_user {
+            format!("{}@{}", context.user, hostname)
+        } else {
+            hostname.to_string()
+        };
+        let host_color = ziron_core::theme::hash_color(&context.hostname);
+
+        let mut data = serde_json::json!({
+            "text": text,
+            "user": context.user.clone(),
+            "hostname": hostname,
+            "show_user": show_user,
+            "ssh": ssh,
+            "host_color": host_color,
+        });
+        if config.color_by_host {
+            data["color"] = host_color.into();
+        }
 
-        Ok(ModuleData {
+        ModuleData {
             module: "sysinfo".to_string(),
-            data: serde_json::json!({
-                "text": text,
-                "user": context.user.clone(),
-                "hostname": context.hostname.clone(),
-            }),
+            data,
             cached: false,
-        })
+        }
     }
 }
 
+/// Whether the shell runs in an SSH session, judged by the variables sshd sets
+pub fn is_ssh_session(env: impl Fn(&str) -> Option<String>) -> bool {
+    ["SSH_CONNECTION", "SSH_TTY", "SSH_CLIENT"]
+        .iter()
+        .any(|var| env(var).is_some_and(|value| !value.is_empty()))
+}
+
+/// Strip the domain from a fully qualified hostname
+fn short_hostname(hostname: &str) -> &str {
+    hostname.split('.').next().unwrap_or(hostname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(hostname: &str) -> ModuleContext {
+        context_for("alice", hostname)
+    }
+
+    fn context_for(user: &str, hostname: &str) -> ModuleContext {
+        ModuleContext {
+            user: user.to_string(),
+            hostname: hostname.to_string(),
+            ..ModuleContext::for_test("/")
+        }
+    }
+
+    #[test]
+    fn test_strip_domain() {
+        let context = context("host.example.com");
+
+        let full = SysInfoModule::fetch_data(&context).unwrap();
+        assert_eq!(full.data["text"], "alice@host.example.com");
+
+        let config = SysInfoConfig { strip_domain: true, ..Default::default() };
+        let short = SysInfoModule::fetch_data_with_config(&context, &config).unwrap();
+        assert_eq!(short.data["text"], "alice@host");
+        assert_eq!(short.data["hostname"], "host");
+
+        assert_eq!(short_hostname("localhost"), "localhost");
+    }
+
+    #[test]
+    fn test_host_color_is_stable_per_host() {
+        let web = SysInfoModule::fetch_data(&context("web-01")).unwrap();
+        assert_eq!(web.data["host_color"], SysInfoModule::fetch_data(&context("web-01")).unwrap().data["host_color"]);
+        assert!(web.data.get("color").is_none());
+
+        assert_eq!(ziron_core::theme::hash_color("web-01"), ziron_core::theme::hash_color("web-01"));
+        assert_ne!(ziron_core::theme::hash_color("web-01"), ziron_core::theme::hash_color("db-01"));
+
+        let config = SysInfoConfig { color_by_host: true, ..Default::default() };
+        let colored = SysInfoModule::fetch_data_with_config(&context("web-01"), &config).unwrap();
+        assert_eq!(colored.data["color"], web.data["host_color"]);
+    }
+
+    #[test]
+    fn test_show_user_root_only() {
+        let config = SysInfoConfig { show_user: ShowUser::RootOnly, ..Default::default() };
+
+        let alice = SysInfoModule::fetch_data_with_config(&context("web"), &config).unwrap();
+        assert_eq!(alice.data["text"], "web");
+        assert_eq!(alice.data["show_user"], false);
+
+        let root = SysInfoModule::fetch_data_with_config(&context_for("root", "web"), &config).unwrap();
+        assert_eq!(root.data["text"], "root@web");
+        assert_eq!(root.data["show_user"], true);
+    }
+
+    #[test]
+    fn test_hide_default_user() {
+        let config: SysInfoConfig = toml::from_str("hide_user_if = \"alice\"").unwrap();
+
+        let alice = SysInfoModule::fetch_data_with_config(&context("web"), &config).unwrap();
+        assert_eq!(alice.data["text"], "web");
+        assert_eq!(alice.data["show_user"], false);
+        assert_eq!(alice.data["user"], "alice");
+
+        let bob = SysInfoModule::fetch_data_with_config(&context_for("bob", "web"), &config).unwrap();
+        assert_eq!(bob.data["text"], "bob@web");
+        assert_eq!(bob.data["show_user"], true);
+    }
+
+    #[test]
+    fn test_only_ssh() {
+        let config: SysInfoConfig = toml::from_str("only_ssh = true").unwrap();
+
+        let local = SysInfoModule::build_data(&context("web"), &config, false);
+        assert_eq!(local.data["text"], "");
+        assert_eq!(local.data["ssh"], false);
+
+        let remote = SysInfoModule::build_data(&context("web"), &config, true);
+        assert_eq!(remote.data["text"], "alice@web");
+        assert_eq!(remote.data["ssh"], true);
+
+        // Without the option the segment always shows
+        let local = SysInfoModule::build_data(&context("web"), &SysInfoConfig::default(), false);
+        assert_eq!(local.data["text"], "alice@web");
+    }
+
+    #[test]
+    fn test_ssh_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        };
+        assert!(is_ssh_session(env(&[("SSH_CONNECTION", "10.0.0.2 51000 10.0.0.1 22")])));
+        assert!(is_ssh_session(env(&[("SSH_TTY", "/dev/pts/1")])));
+        assert!(is_ssh_session(env(&[("SSH_CLIENT", "10.0.0.2 51000 22")])));
+        assert!(!is_ssh_session(env(&[("SSH_TTY", "")])));
+        assert!(!is_ssh_session(env(&[])));
+    }
+}
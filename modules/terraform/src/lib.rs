@@ -1,17 +1,21 @@
 //! Terraform module for Ziron
 
 use ziron_core::error::Result;
-use ziron_core::module::{ModuleContext, ModuleData};
+use ziron_core::module::{run_tool, ModuleContext, ModuleData};
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
 /// Terraform module implementation
 pub struct TerraformModule;
 
 impl TerraformModule {
     /// Fetch Terraform workspace information
-    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let tf_info = Self::get_terraform_info(&context.current_dir)?;
+    pub fn fetch_data(context: &ModuleContext, timeout: Duration) -> Result<ModuleData> {
+        let tf_info = match Self::get_terraform_info(&context.current_dir, timeout) {
+            Ok(info) => info,
+            Err(e) => return Ok(ModuleData::error("terraform", &e)),
+        };
 
         if let Some(info) = tf_info {
             let mut parts = vec![];
@@ -41,7 +45,7 @@ impl TerraformModule {
         }
     }
 
-    fn get_terraform_info(path: &PathBuf) -> Result<Option<TerraformInfo>> {
+    fn get_terraform_info(path: &PathBuf, timeout: Duration) -> Result<Option<TerraformInfo>> {
         // Check for .terraform directory
         let tf_dir = path.join(".terraform");
         if !tf_dir.exists() {
@@ -49,47 +53,16 @@ impl TerraformModule {
         }
 
         // Get workspace
-        let workspace_output = Command::new("terraform")
-            .args(&["workspace", "show"])
-            .current_dir(path)
-            .output();
-
-        let workspace = workspace_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    let ws = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    if !ws.is_empty() && ws != "default" {
-                        Some(ws)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
+        let workspace = run_tool(Command::new("terraform").args(["workspace", "show"]).current_dir(path), timeout)?
+            .filter(|ws| !ws.is_empty() && ws != "default");
 
         // Get version
-        let version_output = Command::new("terraform")
-            .args(&["version", "-json"])
-            .current_dir(path)
-            .output();
-
-        let version = version_output
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    // Parse JSON to get version
-                    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&o.stdout) {
-                        json.get("terraform_version")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+        let version = run_tool(Command::new("terraform").args(["version", "-json"]).current_dir(path), timeout)?
+            .and_then(|out| serde_json::from_str::<serde_json::Value>(&out).ok())
+            .and_then(|json| {
+                json.get("terraform_version")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
             });
 
         if workspace.is_some() {
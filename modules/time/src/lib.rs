@@ -1,36 +1,79 @@
 //! Time module for Ziron
 
-use ziron_core::error::Result;
+use std::fmt::{Display, Write};
+
+use chrono::{DateTime, Local, TimeZone};
+use serde::Deserialize;
+use ziron_core::error::{Error, Result};
 use ziron_core::module::{ModuleContext, ModuleData};
 
+/// Time module settings (`[module_config.time]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TimeConfig {
+    /// strftime-style format, e.g. `%H:%M`
+    pub format: String,
+}
+
+impl Default for TimeConfig {
+    fn default() -> Self {
+        Self {
+            format: "%H:%M:%S".to_string(),
+        }
+    }
+}
+
 /// Time module implementation
 pub struct TimeModule;
 
 impl TimeModule {
     /// Fetch time information
-    pub fn fetch_data(_context: &ModuleContext) -> Result<ModuleData> {
-        use std::time::SystemTime;
-        
-        let now = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or_default();
-        
-        let secs = now.as_secs();
-        // Simple time formatting without chrono for now
-        let text = format!("{:02}:{:02}:{:02}", 
-            (secs / 3600) % 24,
-            (secs / 60) % 60,
-            secs % 60
-        );
+    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
+        Self::fetch_data_with_config(context, &TimeConfig::default())
+    }
+
+    /// Fetch the local time using module settings
+    pub fn fetch_data_with_config(_context: &ModuleContext, config: &TimeConfig) -> Result<ModuleData> {
+        let now = Local::now();
+        let formatted = Self::format_time(&now, &config.format)?;
 
         Ok(ModuleData {
             module: "time".to_string(),
             data: serde_json::json!({
-                "text": text,
-                "timestamp": secs,
+                "text": formatted,
+                "formatted": formatted,
+                "timestamp": now.timestamp(),
             }),
             cached: false,
         })
     }
+
+    /// Format `time` with a strftime-style format, rejecting invalid specifiers
+    fn format_time<Tz: TimeZone>(time: &DateTime<Tz>, format: &str) -> Result<String>
+    where
+        Tz::Offset: Display,
+    {
+        let mut formatted = String::new();
+        write!(formatted, "{}", time.format(format))
+            .map_err(|_| Error::Config(format!("Invalid time format: {}", format)))?;
+        Ok(formatted)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_format_in_fixed_timezone() {
+        // 2024-03-01 12:34:56 UTC
+        let utc = DateTime::from_timestamp(1_709_296_496, 0).unwrap();
+        let berlin = utc.with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        assert_eq!(TimeModule::format_time(&berlin, "%H:%M").unwrap(), "13:34");
+        assert_eq!(TimeModule::format_time(&berlin, &TimeConfig::default().format).unwrap(), "13:34:56");
+        assert_eq!(TimeModule::format_time(&berlin, "%Y-%m-%d %z").unwrap(), "2024-03-01 +0100");
+        assert!(TimeModule::format_time(&berlin, "%Q").is_err());
+    }
+}
@@ -1,38 +1,99 @@
 //! Timer module for Ziron
 
+use serde::Deserialize;
 use ziron_core::error::Result;
 use ziron_core::module::{ModuleContext, ModuleData};
 
+/// Timer module settings (`[module_config.timer]`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    /// Commands that ran for less than this many milliseconds show no timer
+    pub min_ms: u64,
+}
+
 /// Timer module implementation
 pub struct TimerModule;
 
 impl TimerModule {
     /// Fetch timer information
-    pub fn fetch_data(_context: &ModuleContext) -> Result<ModuleData> {
-        // Get command execution time from environment (if set)
-        let duration_ms = std::env::var("ZIRON_CMD_DURATION_MS")
-            .ok()
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(0);
-
-        let text = if duration_ms > 0 {
-            if duration_ms < 1000 {
-                format!("{}ms", duration_ms)
-            } else {
-                format!("{:.2}s", duration_ms as f64 / 1000.0)
-            }
-        } else {
-            String::new()
-        };
+    pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
+        Self::fetch_data_with_config(context, &TimerConfig::default())
+    }
+
+    /// Fetch timer information using module settings
+    pub fn fetch_data_with_config(context: &ModuleContext, config: &TimerConfig) -> Result<ModuleData> {
+        let duration_ms = context.cmd_duration_ms.unwrap_or(0);
 
         Ok(ModuleData {
             module: "timer".to_string(),
             data: serde_json::json!({
-                "text": text,
+                "text": Self::format_duration(duration_ms, config.min_ms),
                 "duration_ms": duration_ms,
             }),
             cached: false,
         })
     }
+
+    /// Render a duration like `250ms`, `1.50s`, `1m30s` or `1h2m`; empty below `min_ms`
+    fn format_duration(duration_ms: u64, min_ms: u64) -> String {
+        if duration_ms == 0 || duration_ms < min_ms {
+            return String::new();
+        }
+
+        let secs = duration_ms / 1000;
+        if duration_ms < 1000 {
+            format!("{}ms", duration_ms)
+        } else if secs < 60 {
+            format!("{:.2}s", duration_ms as f64 / 1000.0)
+        } else if secs < 3600 {
+            format!("{}m{}s", secs / 60, secs % 60)
+        } else {
+            format!("{}h{}m", secs / 3600, (secs % 3600) / 60)
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_below_threshold() {
+        assert_eq!(TimerModule::format_duration(0, 0), "");
+        assert_eq!(TimerModule::format_duration(1999, 2000), "");
+        assert_eq!(TimerModule::format_duration(2000, 2000), "2.00s");
+    }
+
+    #[test]
+    fn test_seconds() {
+        assert_eq!(TimerModule::format_duration(250, 0), "250ms");
+        assert_eq!(TimerModule::format_duration(1500, 0), "1.50s");
+        assert_eq!(TimerModule::format_duration(59_990, 0), "59.99s");
+    }
+
+    #[test]
+    fn test_minutes() {
+        assert_eq!(TimerModule::format_duration(60_000, 0), "1m0s");
+        assert_eq!(TimerModule::format_duration(90_500, 0), "1m30s");
+        assert_eq!(TimerModule::format_duration(3_599_999, 0), "59m59s");
+    }
+
+    #[test]
+    fn test_duration_from_context() {
+        let mut context = ModuleContext::for_test("/tmp");
+        assert_eq!(TimerModule::fetch_data(&context).unwrap().data["text"], "");
+
+        context.cmd_duration_ms = Some(1500);
+        let data = TimerModule::fetch_data(&context).unwrap().data;
+        assert_eq!(data["text"], "1.50s");
+        assert_eq!(data["duration_ms"], 1500);
+    }
+
+    #[test]
+    fn test_hours() {
+        assert_eq!(TimerModule::format_duration(3_600_000, 0), "1h0m");
+        assert_eq!(TimerModule::format_duration(3_720_000, 0), "1h2m");
+        assert_eq!(TimerModule::format_duration(90_000_000, 0), "25h0m");
+    }
+}
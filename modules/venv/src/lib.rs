@@ -1,17 +1,39 @@
 //! Python virtual environment module for Ziron
 
+use serde::Deserialize;
 use ziron_core::error::Result;
 use ziron_core::module::{ModuleContext, ModuleData};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 
+/// Venv module settings (`[module_config.venv]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct VenvConfig {
+    /// Tools in the order they are preferred when several match; unlisted tools are never shown
+    pub tool_priority: Vec<String>,
+}
+
+impl Default for VenvConfig {
+    fn default() -> Self {
+        Self {
+            tool_priority: ["uv", "poetry", "pipenv", "virtualenvwrapper"].map(String::from).to_vec(),
+        }
+    }
+}
+
 /// Python virtual environment module implementation
 pub struct VenvModule;
 
 impl VenvModule {
     /// Fetch virtual environment information
     pub fn fetch_data(context: &ModuleContext) -> Result<ModuleData> {
-        let venv_info = Self::detect_venv(&context.current_dir)?;
+        Self::fetch_data_with_config(context, &VenvConfig::default())
+    }
+
+    /// Fetch virtual environment information using module settings
+    pub fn fetch_data_with_config(context: &ModuleContext, config: &VenvConfig) -> Result<ModuleData> {
+        let venv_info = Self::detect_venv(&context.current_dir, config)?;
 
         let mut parts = vec![];
         
@@ -40,34 +62,54 @@ impl VenvModule {
         })
     }
 
-    fn detect_venv(path: &PathBuf) -> Result<VenvInfo> {
+    fn detect_venv(path: &PathBuf, config: &VenvConfig) -> Result<VenvInfo> {
+        Self::detect_venv_with_env(path, config, |name| std::env::var(name).ok())
+    }
+
+    fn detect_venv_with_env(path: &PathBuf, config: &VenvConfig, env: impl Fn(&str) -> Option<String>) -> Result<VenvInfo> {
+        let mut venv_dir = None;
         let mut venv_name = None;
-        let mut tool = None;
+        let mut tools = Vec::new();
 
         // Check VIRTUAL_ENV environment variable
-        if let Ok(venv_path) = std::env::var("VIRTUAL_ENV") {
+        if let Some(venv_path) = env("VIRTUAL_ENV").filter(|venv| !venv.is_empty()) {
             let venv_path = PathBuf::from(venv_path);
             if let Some(name) = venv_path.file_name().and_then(|n| n.to_str()) {
                 venv_name = Some(name.to_string());
             }
+            venv_dir = Some(venv_path);
         }
 
-        // Check for .python-version file
-        let python_version = Self::read_python_version(path)?;
-
         // Check for pipenv
         if path.join("Pipfile").exists() {
-            tool = Some("pipenv".to_string());
+            tools.push("pipenv");
         }
         
-        // Check for poetry
+        // Check for poetry and uv
         if path.join("pyproject.toml").exists() {
             if let Ok(content) = fs::read_to_string(path.join("pyproject.toml")) {
                 if content.contains("[tool.poetry]") {
-                    tool = Some("poetry".to_string());
+                    tools.push("poetry");
+                }
+                if content.lines().any(|line| line.trim() == "[tool.uv]" || line.trim().starts_with("[tool.uv.")) {
+                    tools.push("uv");
                 }
             }
         }
+        if path.join("uv.lock").exists() {
+            tools.push("uv");
+        }
+
+        // virtualenvwrapper keeps its environments under $WORKON_HOME (compared by path component,
+        // so `~/.virtualenvs2/app` isn't under `~/.virtualenvs`)
+        if let (Some(venv_dir), Some(workon_home)) = (&venv_dir, env("WORKON_HOME").filter(|home| !home.is_empty())) {
+            if venv_dir.starts_with(Path::new(&workon_home)) {
+                tools.push("virtualenvwrapper");
+            }
+        }
+
+        // Several tools can match, e.g. a Pipfile kept alongside pyproject.toml during a migration
+        let tool = config.tool_priority.iter().find(|tool| tools.contains(&tool.as_str())).cloned();
 
         // Check for common virtual environment directories if not found yet
         if venv_name.is_none() {
@@ -81,6 +123,7 @@ impl VenvModule {
                     if venv_path.exists() && venv_path.is_dir() {
                         if let Some(name) = venv_path.file_name().and_then(|n| n.to_str()) {
                             venv_name = Some(name.to_string());
+                            venv_dir = Some(venv_path);
                             break;
                         }
                     }
@@ -98,6 +141,12 @@ impl VenvModule {
             }
         }
 
+        // Prefer the version the environment was created with over .python-version
+        let python_version = match venv_dir.as_deref().and_then(Self::read_pyvenv_version) {
+            Some(version) => Some(version),
+            None => Self::read_python_version(path)?,
+        };
+
         Ok(VenvInfo {
             venv_name,
             python_version,
@@ -105,6 +154,24 @@ impl VenvModule {
         })
     }
 
+    /// Python version from the environment's `pyvenv.cfg`
+    ///
+    /// The standard library writes `version`; virtualenv and uv write `version_info`.
+    fn read_pyvenv_version(venv_dir: &Path) -> Option<String> {
+        let content = fs::read_to_string(venv_dir.join("pyvenv.cfg")).ok()?;
+        let value = |key: &str| {
+            content.lines().find_map(|line| {
+                let (name, value) = line.split_once('=')?;
+                (name.trim() == key).then(|| value.trim().to_string()).filter(|value| !value.is_empty())
+            })
+        };
+
+        // `version_info` may carry a release level, e.g. `3.12.1.final.0`
+        value("version").or_else(|| value("version_info").map(|version| {
+            version.split('.').take(3).collect::<Vec<_>>().join(".")
+        }))
+    }
+
     fn read_python_version(path: &PathBuf) -> Result<Option<String>> {
         // Check .python-version
         let python_version_file = path.join(".python-version");
@@ -128,3 +195,102 @@ struct VenvInfo {
     tool: Option<String>,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(path: &Path, env: &[(&str, String)]) -> VenvInfo {
+        detect_with_config(path, env, &VenvConfig::default())
+    }
+
+    fn detect_with_config(path: &Path, env: &[(&str, String)], config: &VenvConfig) -> VenvInfo {
+        VenvModule::detect_venv_with_env(&path.to_path_buf(), config, |name| {
+            env.iter().find(|(key, _)| *key == name).map(|(_, value)| value.clone())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_uv_lock() {
+        let project = tempfile::TempDir::new().unwrap();
+        fs::create_dir(project.path().join(".venv")).unwrap();
+        fs::write(project.path().join("uv.lock"), "version = 1\n").unwrap();
+
+        let info = detect(project.path(), &[]);
+        assert_eq!(info.venv_name.as_deref(), Some(".venv"));
+        assert_eq!(info.tool.as_deref(), Some("uv"));
+    }
+
+    #[test]
+    fn test_pyproject_tool_uv() {
+        let project = tempfile::TempDir::new().unwrap();
+        fs::write(project.path().join("pyproject.toml"), "[project]\nname = \"app\"\n\n[tool.uv]\ndev-dependencies = []\n").unwrap();
+        assert_eq!(detect(project.path(), &[]).tool.as_deref(), Some("uv"));
+
+        fs::write(project.path().join("pyproject.toml"), "[tool.poetry]\nname = \"app\"\n").unwrap();
+        assert_eq!(detect(project.path(), &[]).tool.as_deref(), Some("poetry"));
+    }
+
+    #[test]
+    fn test_workon_home() {
+        let project = tempfile::TempDir::new().unwrap();
+        let workon_home = tempfile::TempDir::new().unwrap();
+        let venv = workon_home.path().join("myproject");
+        fs::create_dir(&venv).unwrap();
+
+        let env = [
+            ("VIRTUAL_ENV", venv.display().to_string()),
+            ("WORKON_HOME", workon_home.path().display().to_string()),
+        ];
+        let info = detect(project.path(), &env);
+        assert_eq!(info.venv_name.as_deref(), Some("myproject"));
+        assert_eq!(info.tool.as_deref(), Some("virtualenvwrapper"));
+
+        // Environments elsewhere aren't attributed to virtualenvwrapper
+        let env = [
+            ("VIRTUAL_ENV", project.path().join(".venv").display().to_string()),
+            ("WORKON_HOME", workon_home.path().display().to_string()),
+        ];
+        assert_eq!(detect(project.path(), &env).tool, None);
+
+        // A sibling directory sharing WORKON_HOME as a string prefix isn't inside it
+        let env = [
+            ("VIRTUAL_ENV", format!("{}2/myproject", workon_home.path().display())),
+            ("WORKON_HOME", workon_home.path().display().to_string()),
+        ];
+        assert_eq!(detect(project.path(), &env).tool, None);
+    }
+
+    #[test]
+    fn test_pyvenv_cfg_version() {
+        let project = tempfile::TempDir::new().unwrap();
+        fs::write(project.path().join(".python-version"), "3.11\n").unwrap();
+        assert_eq!(detect(project.path(), &[]).python_version.as_deref(), Some("3.11"));
+
+        let venv = project.path().join(".venv");
+        fs::create_dir(&venv).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "home = /usr/bin\ninclude-system-site-packages = false\nversion = 3.12.1\n").unwrap();
+        assert_eq!(detect(project.path(), &[]).python_version.as_deref(), Some("3.12.1"));
+
+        // virtualenv and uv write `version_info` instead
+        fs::write(venv.join("pyvenv.cfg"), "home = /usr/bin\nversion_info = 3.13.0.final.0\n").unwrap();
+        let env = [("VIRTUAL_ENV", venv.display().to_string())];
+        assert_eq!(detect(project.path(), &env).python_version.as_deref(), Some("3.13.0"));
+    }
+
+    #[test]
+    fn test_tool_priority() {
+        let project = tempfile::TempDir::new().unwrap();
+        fs::write(project.path().join("Pipfile"), "").unwrap();
+        fs::write(project.path().join("pyproject.toml"), "[tool.poetry]\nname = \"app\"\n").unwrap();
+        assert_eq!(detect(project.path(), &[]).tool.as_deref(), Some("poetry"));
+
+        let config: VenvConfig = toml::from_str("tool_priority = [\"pipenv\", \"poetry\"]").unwrap();
+        assert_eq!(detect_with_config(project.path(), &[], &config).tool.as_deref(), Some("pipenv"));
+
+        // Tools left out of the list aren't shown
+        let config: VenvConfig = toml::from_str("tool_priority = [\"uv\"]").unwrap();
+        assert_eq!(detect_with_config(project.path(), &[], &config).tool, None);
+    }
+}
@@ -1,8 +1,11 @@
 //! Ziron CLI - Command-line interface for managing Ziron
 
 use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
 use ziron_core::config::Config;
-use ziron_core::error::Result;
+use ziron_core::error::{Error, Result};
+use ziron_core::module::{normalize_module_name, KNOWN_MODULES};
+use ziron_core::theme::Theme;
 
 #[derive(Parser)]
 #[command(name = "ziron")]
@@ -35,6 +38,26 @@ enum Commands {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// Check the environment for common problems
+    Doctor,
+    /// Print the prompt rendered by the daemon
+    Prompt {
+        /// Print styled segments as JSON instead of an ANSI string
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print daemon events as they fire
+    Events,
+    /// Inspect or clear the daemon's module cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Print a JSON Schema for editor completion and validation
+    Schema {
+        #[command(subcommand)]
+        action: SchemaAction,
+    },
 }
 
 #[derive(Subcommand)]
@@ -64,6 +87,25 @@ enum ThemeAction {
     List,
 }
 
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show cache hits, misses and size
+    Stats,
+    /// Clear cached data
+    Clear {
+        /// Only clear this module's entries
+        module: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaAction {
+    /// Schema of config.toml
+    Config,
+    /// Schema of theme.toml files
+    Theme,
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Validate configuration file
@@ -89,6 +131,11 @@ fn main() -> Result<()> {
         Commands::Config { action } => match action {
             ConfigAction::Validate => cmd_config_validate(),
         },
+        Commands::Doctor => cmd_doctor(),
+        Commands::Prompt { json } => cmd_prompt(json),
+        Commands::Events => cmd_events(),
+        Commands::Cache { action } => cmd_cache(&action),
+        Commands::Schema { action } => cmd_schema(&action),
     }
 }
 
@@ -112,14 +159,37 @@ fn cmd_init(force: bool) -> Result<()> {
     if let Some(theme) = &config.theme {
         println!("Default theme set to: {}", theme);
     }
+
+    let profile_path = Config::default_profile_path()?;
+    if !profile_path.exists() || force {
+        std::fs::write(&profile_path, STARTER_PROFILE)?;
+        println!("Created starter profile at {:?}", profile_path);
+    }
     Ok(())
 }
 
+/// Starter profile written by `ziron init`
+const STARTER_PROFILE: &str = "\
+# Ziron shell profile - sourced by ziron-shell at startup
+#
+# export EDITOR=vim
+# alias ll='ls -la'
+";
+
 fn cmd_plugin_add(name: &str) -> Result<()> {
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_user().unwrap_or_default();
+
+    let name = normalize_module_name(name);
+    if !KNOWN_MODULES.contains(&name.as_str()) {
+        return Err(Error::Config(format!(
+            "Unknown module: {} (available: {})",
+            name,
+            KNOWN_MODULES.join(", ")
+        )));
+    }
 
-    if !config.modules.contains(&name.to_string()) {
-        config.modules.push(name.to_string());
+    if !config.modules.iter().any(|m| normalize_module_name(m) == name) {
+        config.modules.push(name.clone());
         config.save()?;
         println!("Added plugin: {}", name);
     } else {
@@ -130,9 +200,9 @@ fn cmd_plugin_add(name: &str) -> Result<()> {
 }
 
 fn cmd_plugin_remove(name: &str) -> Result<()> {
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_user().unwrap_or_default();
 
-    if let Some(pos) = config.modules.iter().position(|m| m == name) {
+    if let Some(pos) = config.modules.iter().position(|m| normalize_module_name(m) == normalize_module_name(name)) {
         config.modules.remove(pos);
         config.save()?;
         println!("Removed plugin: {}", name);
@@ -159,7 +229,7 @@ fn cmd_plugin_list() -> Result<()> {
 }
 
 fn cmd_theme_set(name: &str) -> Result<()> {
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_user().unwrap_or_default();
     config.theme = Some(name.to_string());
     config.save()?;
     println!("Set theme to: {}", name);
@@ -185,3 +255,315 @@ fn cmd_config_validate() -> Result<()> {
     Ok(())
 }
 
+/// External tools used by the modules, as (module, program)
+const MODULE_TOOLS: &[(&str, &str)] = &[
+    ("git", "git"),
+    ("svn", "svn"),
+    ("mercurial", "hg"),
+    ("jj", "jj"),
+    ("docker", "docker"),
+    ("kubernetes", "kubectl"),
+    ("aws", "aws"),
+    ("gcp", "gcloud"),
+    ("azure", "az"),
+    ("terraform", "terraform"),
+    ("go", "go"),
+    ("node", "node"),
+    ("rust", "rustc"),
+    ("conda", "conda"),
+];
+
+/// Result of a single `ziron doctor` check
+struct Check {
+    name: String,
+    ok: bool,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, ok: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ok,
+            detail: detail.into(),
+        }
+    }
+}
+
+fn cmd_prompt(json: bool) -> Result<()> {
+    use ziron_core::ipc::{Request, Response};
+    use ziron_core::module::ModuleContext;
+
+    let context = ModuleContext::from_env()?;
+    let request = if json {
+        Request::GetPromptJson { context }
+    } else {
+        Request::GetPrompt { context }
+    };
+
+    match daemon_request(&Config::socket_path()?, request)? {
+        Response::Prompt(prompt) => print!("{}", prompt),
+        Response::PromptSegments(segments) => {
+            let output = serde_json::to_string(&segments)
+                .map_err(|e| Error::Config(format!("Failed to serialize prompt: {}", e)))?;
+            println!("{}", output);
+        }
+        Response::Error(e) => return Err(Error::Config(e)),
+        _ => return Err(Error::Config("Unexpected response from daemon".to_string())),
+    }
+    Ok(())
+}
+
+fn cmd_events() -> Result<()> {
+    use std::os::unix::net::UnixStream;
+    use ziron_core::ipc::{self, Request, Response};
+
+    let socket_path = Config::socket_path()?;
+    let mut stream = UnixStream::connect(&socket_path)?;
+    ipc::write_request(&mut stream, Request::SubscribeEvents)?;
+
+    loop {
+        match ipc::read_response(&mut stream)? {
+            Response::Ok => eprintln!("Listening for events on {}", socket_path.display()),
+            Response::Event { event_type, data } => println!("{:?} {}", event_type, data),
+            Response::Error(e) => return Err(Error::Config(e)),
+            _ => return Err(Error::Config("Unexpected response from daemon".to_string())),
+        }
+    }
+}
+
+fn cmd_cache(action: &CacheAction) -> Result<()> {
+    use ziron_core::ipc::Response;
+
+    let socket_path = Config::socket_path()?;
+    if !socket_path.exists() {
+        return Err(Error::Config(format!(
+            "Daemon is not running (no socket at {}); start it with: ziron-daemon",
+            socket_path.display()
+        )));
+    }
+
+    match daemon_request(&socket_path, cache_request(action))? {
+        Response::CacheStats { hits, misses, size, modules } => {
+            println!("hits:   {}", hits);
+            println!("misses: {}", misses);
+            println!("size:   {}", size);
+            for (module, stats) in &modules {
+                println!("  {}: {} hits, {} misses", module, stats.hits, stats.misses);
+            }
+        }
+        Response::Ok => match action {
+            CacheAction::Clear { module: Some(module) } => println!("Cleared cache for {}", module),
+            _ => println!("Cleared cache"),
+        },
+        Response::Error(e) => return Err(Error::Config(e)),
+        _ => return Err(Error::Config("Unexpected response from daemon".to_string())),
+    }
+    Ok(())
+}
+
+/// The daemon request for a cache subcommand
+fn cache_request(action: &CacheAction) -> ziron_core::ipc::Request {
+    use ziron_core::ipc::Request;
+
+    match action {
+        CacheAction::Stats => Request::GetCacheStats,
+        CacheAction::Clear { module } => Request::InvalidateCache {
+            module: module.as_deref().map(normalize_module_name),
+        },
+    }
+}
+
+fn cmd_schema(action: &SchemaAction) -> Result<()> {
+    println!("{}", schema_json(action)?);
+    Ok(())
+}
+
+/// The pretty-printed JSON Schema for a schema subcommand
+fn schema_json(action: &SchemaAction) -> Result<String> {
+    let schema = match action {
+        SchemaAction::Config => Config::json_schema(),
+        SchemaAction::Theme => Theme::json_schema(),
+    };
+    serde_json::to_string_pretty(&schema)
+        .map_err(|e| Error::Config(format!("Failed to serialize schema: {}", e)))
+}
+
+fn cmd_doctor() -> Result<()> {
+    let checks = doctor_checks(&Config::default_path()?, &Config::socket_path()?);
+
+    for check in &checks {
+        let status = if check.ok { "ok" } else { "FAIL" };
+        println!("[{:>4}] {}: {}", status, check.name, check.detail);
+    }
+
+    let failures = checks.iter().filter(|check| !check.ok).count();
+    if failures == 0 {
+        println!("\nNo problems found");
+    } else {
+        println!("\n{} problem(s) found", failures);
+    }
+    Ok(())
+}
+
+/// Run all environment checks against the given config file and daemon socket
+fn doctor_checks(config_path: &Path, socket_path: &Path) -> Vec<Check> {
+    let mut checks = vec![check_daemon(socket_path), check_socket_dir(socket_path)];
+
+    match Config::load_from(config_path) {
+        Ok(config) => {
+            checks.push(Check::new("config", true, format!("{} parses", config_path.display())));
+            checks.push(check_theme(&config, config_path));
+        }
+        Err(e) => checks.push(Check::new("config", false, e.to_string())),
+    }
+
+    for (module, program) in MODULE_TOOLS {
+        let check = match find_in_path(program) {
+            Some(path) => Check::new(*module, true, format!("{} found at {}", program, path.display())),
+            None => Check::new(*module, false, format!("{} not found on PATH", program)),
+        };
+        checks.push(check);
+    }
+
+    checks
+}
+
+/// Check that the daemon answers a health check on its socket
+fn check_daemon(socket_path: &Path) -> Check {
+    if !socket_path.exists() {
+        return Check::new(
+            "daemon",
+            false,
+            format!("not running (no socket at {}); start it with: ziron-daemon", socket_path.display()),
+        );
+    }
+
+    match daemon_health(socket_path) {
+        Ok(status) => Check::new("daemon", true, format!("reachable ({})", status)),
+        Err(e) => Check::new("daemon", false, format!("not reachable at {}: {}", socket_path.display(), e)),
+    }
+}
+
+fn daemon_health(socket_path: &Path) -> Result<String> {
+    use ziron_core::ipc::{Request, Response};
+
+    match daemon_request(socket_path, Request::HealthCheck)? {
+        Response::Health { status, .. } => Ok(status),
+        _ => Err(Error::Config("Unexpected response from daemon".to_string())),
+    }
+}
+
+/// Send a single request to the daemon and wait for its response
+fn daemon_request(socket_path: &Path, request: ziron_core::ipc::Request) -> Result<ziron_core::ipc::Response> {
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket_path)?;
+    let timeout = Some(std::time::Duration::from_secs(1));
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)?;
+
+    ziron_core::ipc::request(&mut stream, request)
+}
+
+/// Check that the daemon could create its socket
+fn check_socket_dir(socket_path: &Path) -> Check {
+    let Some(dir) = socket_path.parent() else {
+        return Check::new("socket", false, format!("invalid socket path {}", socket_path.display()));
+    };
+
+    let probe = dir.join(".ziron-doctor-probe");
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::new("socket", true, format!("{} is writable", dir.display()))
+        }
+        Err(e) => Check::new("socket", false, format!("{} is not writable: {}", dir.display(), e)),
+    }
+}
+
+/// Check that the configured theme can be found
+fn check_theme(config: &Config, config_path: &Path) -> Check {
+    let name = config.theme.as_deref().unwrap_or("default");
+    let mut candidates = Vec::new();
+    if let Some(dir) = config_path.parent() {
+        candidates.push(dir.join("themes").join(name).join("theme.toml"));
+    }
+    candidates.push(PathBuf::from("themes").join(name).join("theme.toml"));
+
+    match candidates.iter().find(|path| path.exists()) {
+        Some(path) => match Theme::load_from(path) {
+            Ok(_) => Check::new("theme", true, format!("{} ({})", name, path.display())),
+            Err(e) => Check::new("theme", false, format!("{}: {}", name, e)),
+        },
+        None => Check::new("theme", false, format!("{} not found (looked for themes/{}/theme.toml)", name, name)),
+    }
+}
+
+/// Locate an executable on PATH
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| {
+            candidate
+                .metadata()
+                .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doctor_reports_daemon_down_without_socket() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let checks = doctor_checks(
+            &temp_dir.path().join("config.toml"),
+            &temp_dir.path().join("ziron.sock"),
+        );
+
+        let daemon = checks.iter().find(|check| check.name == "daemon").unwrap();
+        assert!(!daemon.ok);
+        assert!(daemon.detail.contains("not running"));
+
+        let socket = checks.iter().find(|check| check.name == "socket").unwrap();
+        assert!(socket.ok);
+        let config = checks.iter().find(|check| check.name == "config").unwrap();
+        assert!(config.ok);
+    }
+
+    #[test]
+    fn test_cache_clear_request() {
+        use ziron_core::ipc::Request;
+
+        let cli = Cli::try_parse_from(["ziron", "cache", "clear", "git"]).unwrap();
+        let Commands::Cache { action } = cli.command else {
+            panic!("expected cache command");
+        };
+        assert!(matches!(
+            cache_request(&action),
+            Request::InvalidateCache { module: Some(module) } if module == "git"
+        ));
+
+        let all = cache_request(&CacheAction::Clear { module: None });
+        assert!(matches!(all, Request::InvalidateCache { module: None }));
+        assert!(matches!(cache_request(&CacheAction::Stats), Request::GetCacheStats));
+    }
+
+    #[test]
+    fn test_schema_lists_known_fields() {
+        let config: serde_json::Value = serde_json::from_str(&schema_json(&SchemaAction::Config).unwrap()).unwrap();
+        let performance = &config["definitions"]["PerformanceConfig"]["properties"];
+        assert!(performance.get("cache_ttl_ms").is_some(), "{}", config);
+        assert!(config["properties"].get("module_config").is_some());
+
+        let theme: serde_json::Value = serde_json::from_str(&schema_json(&SchemaAction::Theme).unwrap()).unwrap();
+        assert!(theme["properties"].get("segments").is_some(), "{}", theme);
+        assert!(theme["definitions"]["Segment"]["properties"].get("fill_char").is_some());
+    }
+}
@@ -1,7 +1,8 @@
 //! Caching system for module data
 
 use crate::module::ModuleData;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::time::{Duration, Instant};
 use std::sync::{Arc, RwLock};
 
@@ -11,12 +12,27 @@ pub struct CacheStats {
     pub hits: u64,
     pub misses: u64,
     pub size: usize,
+    /// Hits and misses per module, keyed by the module part of `module:dir` cache keys
+    pub modules: BTreeMap<String, ModuleCacheStats>,
+}
+
+/// Cache hits and misses of a single module
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleCacheStats {
+    pub hits: u64,
+    pub misses: u64,
 }
 
 /// Cached item with timestamp
 struct CachedItem {
     data: ModuleData,
     timestamp: Instant,
+    /// Last `get` hit (or the store), for least-recently-used eviction
+    last_access: Instant,
+    /// How long the item stays valid
+    ttl: Duration,
+    /// Content hash of `data`, for `set_if_changed`
+    hash: u64,
 }
 
 /// Cache implementation
@@ -30,6 +46,8 @@ pub struct Cache {
 struct CacheInner {
     data: HashMap<String, CachedItem>,
     stats: CacheStats,
+    /// Last data stored per key without an `error`; kept past expiry and invalidation
+    last_good: HashMap<String, CachedItem>,
 }
 
 impl Cache {
@@ -39,6 +57,7 @@ impl Cache {
             inner: Arc::new(RwLock::new(CacheInner {
                 data: HashMap::new(),
                 stats: CacheStats::default(),
+                last_good: HashMap::new(),
             })),
             ttl,
             max_size,
@@ -51,78 +70,152 @@ impl Cache {
         
         // Check if key exists and is valid
         let item_valid = inner.data.get(key)
-            .map(|item| item.timestamp.elapsed() < self.ttl)
+            .map(|item| item.timestamp.elapsed() < item.ttl)
             .unwrap_or(false);
         
+        let module = key.split(':').next().unwrap_or(key).to_string();
         if item_valid {
             inner.stats.hits += 1;
-            let item = inner.data.get(key).unwrap();
+            inner.stats.modules.entry(module).or_default().hits += 1;
+            let item = inner.data.get_mut(key).unwrap();
+            item.last_access = Instant::now();
             let mut data = item.data.clone();
             data.cached = true;
             Some(data)
         } else {
-            // Remove expired item if it exists
-            if inner.data.contains_key(key) {
-                inner.data.remove(key);
-            }
+            // Expired and invalidated items stay until replaced or evicted, so their
+            // content hash is still there when the data is fetched again
             inner.stats.misses += 1;
+            inner.stats.modules.entry(module).or_default().misses += 1;
             None
         }
     }
 
+    /// Whether unexpired data is cached for a key, without counting a hit or miss
+    pub fn contains(&self, key: &str) -> bool {
+        let inner = self.inner.read().unwrap();
+        inner.data.get(key).is_some_and(|item| item.timestamp.elapsed() < item.ttl)
+    }
+
     /// Store data in cache
     pub fn set(&self, key: String, data: ModuleData) {
+        self.set_with_ttl(key, data, self.ttl);
+    }
+
+    /// Store data in cache, valid for `ttl` instead of the cache's default
+    pub fn set_with_ttl(&self, key: String, data: ModuleData, ttl: Duration) {
         let mut inner = self.inner.write().unwrap();
         
-        // Evict old items if cache is full (simple FIFO for now)
+        // Evict an expired item, else the least recently used one, if cache is full
         if inner.data.len() >= self.max_size && !inner.data.contains_key(&key) {
-            // Remove oldest item (simple: remove first entry)
-            if let Some(oldest_key) = inner.data.keys().next().cloned() {
-                inner.data.remove(&oldest_key);
+            let lru_key = inner.data.iter()
+                .min_by_key(|(_, item)| (item.timestamp.elapsed() < item.ttl, item.last_access))
+                .map(|(key, _)| key.clone());
+            if let Some(lru_key) = lru_key {
+                inner.data.remove(&lru_key);
             }
         }
         
+        let hash = content_hash(&data.data);
+        if data.data.get("error").is_none() {
+            if inner.last_good.len() >= self.max_size && !inner.last_good.contains_key(&key) {
+                let oldest_key = inner.last_good.iter().min_by_key(|(_, item)| item.timestamp).map(|(key, _)| key.clone());
+                if let Some(oldest_key) = oldest_key {
+                    inner.last_good.remove(&oldest_key);
+                }
+            }
+            inner.last_good.insert(key.clone(), CachedItem {
+                data: data.clone(),
+                timestamp: Instant::now(),
+                last_access: Instant::now(),
+                ttl,
+                hash,
+            });
+        }
+
         inner.data.insert(key, CachedItem {
             data,
             timestamp: Instant::now(),
+            last_access: Instant::now(),
+            ttl,
+            hash,
         });
         inner.stats.size = inner.data.len();
     }
 
+    /// The last data stored for a key without an `error`, if stored within `max_age`
+    ///
+    /// Unlike `get` this ignores the TTL and invalidation, for showing a module's previous
+    /// state while a fresh fetch fails.
+    pub fn last_good(&self, key: &str, max_age: Duration) -> Option<ModuleData> {
+        let inner = self.inner.read().unwrap();
+        let item = inner.last_good.get(key).filter(|item| item.timestamp.elapsed() < max_age)?;
+        let mut data = item.data.clone();
+        data.cached = true;
+        Some(data)
+    }
+
+    /// Store data in cache, returning whether its content differs from the last data stored for `key`
+    ///
+    /// Invalidation only marks cached data stale, so a re-fetch after a watcher
+    /// invalidation that yields the same data reports no change.
+    pub fn set_if_changed(&self, key: String, data: ModuleData) -> bool {
+        self.set_if_changed_with_ttl(key, data, self.ttl)
+    }
+
+    /// `set_if_changed`, with the data valid for `ttl` instead of the cache's default
+    pub fn set_if_changed_with_ttl(&self, key: String, data: ModuleData, ttl: Duration) -> bool {
+        let hash = content_hash(&data.data);
+        let changed = {
+            let inner = self.inner.read().unwrap();
+            inner.data.get(&key).map(|item| item.hash) != Some(hash)
+        };
+        self.set_with_ttl(key, data, ttl);
+        changed
+    }
+
     /// Invalidate cache for a specific module or all modules
+    ///
+    /// Invalidated items count as expired; they are replaced on the next store or evicted.
     pub fn invalidate(&self, module: Option<&str>) {
         let mut inner = self.inner.write().unwrap();
         
         if let Some(module) = module {
-            // Remove specific module
-            if inner.data.remove(module).is_some() {
-                inner.stats.size = inner.data.len();
+            // Expire specific module
+            if let Some(item) = inner.data.get_mut(module) {
+                item.ttl = Duration::ZERO;
             }
         } else {
-            // Clear all cache
-            inner.data.clear();
-            inner.stats.size = 0;
+            // Expire all cache
+            for item in inner.data.values_mut() {
+                item.ttl = Duration::ZERO;
+            }
         }
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
         let inner = self.inner.read().unwrap();
-        CacheStats {
-            hits: inner.stats.hits,
-            misses: inner.stats.misses,
-            size: inner.stats.size,
-        }
+        inner.stats.clone()
     }
 
     /// Clear all cache
     pub fn clear(&self) {
         let mut inner = self.inner.write().unwrap();
         inner.data.clear();
+        inner.last_good.clear();
         inner.stats.size = 0;
     }
 }
 
+/// Hash of module data's JSON (object keys are sorted, so equal data hashes equally)
+fn content_hash(data: &serde_json::Value) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    data.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
 impl Default for Cache {
     fn default() -> Self {
         Self::new(Duration::from_millis(50), 1000)
@@ -183,6 +276,26 @@ mod tests {
         assert!(cached.is_none());
     }
 
+    #[test]
+    fn test_last_good_survives_expiry_but_not_errors() {
+        let cache = Cache::new(Duration::ZERO, 100);
+        let good = ModuleData {
+            module: "git".to_string(),
+            data: json!({"text": "main"}),
+            cached: false,
+        };
+
+        cache.set("git:/repo".to_string(), good);
+        cache.set("git:/repo".to_string(), ModuleData::error("git", &crate::error::Error::Module("index.lock".to_string())));
+        cache.invalidate(Some("git:/repo"));
+
+        assert!(cache.get("git:/repo").is_none());
+        let last_good = cache.last_good("git:/repo", Duration::from_secs(10)).unwrap();
+        assert_eq!(last_good.data["text"], "main");
+        assert!(last_good.cached);
+        assert!(cache.last_good("git:/repo", Duration::ZERO).is_none());
+    }
+
     #[test]
     fn test_cache_stats() {
         let cache = Cache::new(Duration::from_secs(1), 100);
@@ -202,5 +315,103 @@ mod tests {
         assert_eq!(stats.misses, 1);
         assert_eq!(stats.size, 1);
     }
-}
 
+    #[test]
+    fn test_cache_stats_per_module() {
+        let cache = Cache::new(Duration::from_secs(1), 100);
+        let data = ModuleData {
+            module: "git".to_string(),
+            data: json!({"text": "main"}),
+            cached: false,
+        };
+
+        cache.set("git:/repo".to_string(), data);
+        let _ = cache.get("git:/repo"); // Hit
+        let _ = cache.get("git:/repo"); // Hit
+        let _ = cache.get("git:/other"); // Miss
+        let _ = cache.get("node:/repo"); // Miss
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.modules["git"], ModuleCacheStats { hits: 2, misses: 1 });
+        assert_eq!(stats.modules["node"], ModuleCacheStats { hits: 0, misses: 1 });
+    }
+
+    #[test]
+    fn test_set_if_changed_across_invalidation() {
+        let cache = Cache::new(Duration::from_secs(1), 100);
+        let data = |branch: &str| ModuleData {
+            module: "git".to_string(),
+            data: json!({"text": branch, "branch": branch}),
+            cached: false,
+        };
+
+        assert!(cache.set_if_changed("git:/repo".to_string(), data("main")));
+        cache.invalidate(None);
+        assert!(!cache.set_if_changed("git:/repo".to_string(), data("main")));
+        assert!(cache.get("git:/repo").is_some());
+        assert!(cache.set_if_changed("git:/repo".to_string(), data("feature")));
+    }
+
+    #[test]
+    fn test_hash_evicted_with_its_data() {
+        let cache = Cache::new(Duration::from_secs(10), 2);
+        let data = |module: &str| ModuleData {
+            module: module.to_string(),
+            data: json!({"text": module}),
+            cached: false,
+        };
+
+        assert!(cache.set_if_changed("a".to_string(), data("a")));
+        cache.invalidate(None);
+        // The invalidated entry is evicted first, and its hash with it
+        cache.set("b".to_string(), data("b"));
+        cache.set("c".to_string(), data("c"));
+        assert!(cache.contains("b"));
+        assert_eq!(cache.stats().size, 2);
+        assert!(cache.set_if_changed("a".to_string(), data("a")));
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = Cache::new(Duration::from_secs(10), 3);
+        let data = |module: &str| ModuleData {
+            module: module.to_string(),
+            data: json!({"text": module}),
+            cached: false,
+        };
+
+        for key in ["a", "b", "c"] {
+            cache.set(key.to_string(), data(key));
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert!(cache.get("a").is_some());
+        cache.set("d".to_string(), data("d"));
+
+        assert!(cache.contains("a"));
+        assert!(!cache.contains("b"));
+        assert!(cache.contains("c"));
+        assert!(cache.contains("d"));
+        assert_eq!(cache.stats().size, 3);
+    }
+
+    #[test]
+    fn test_set_with_ttl_expires_independently() {
+        let cache = Cache::new(Duration::from_secs(10), 100);
+        let data = |module: &str| ModuleData {
+            module: module.to_string(),
+            data: json!({"text": module}),
+            cached: false,
+        };
+
+        cache.set_with_ttl("time:/".to_string(), data("time"), Duration::from_millis(10));
+        cache.set_with_ttl("aws:/".to_string(), data("aws"), Duration::from_secs(60));
+        cache.set("git:/".to_string(), data("git"));
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert!(cache.get("time:/").is_none());
+        assert!(cache.get("aws:/").is_some());
+        assert!(cache.get("git:/").is_some());
+    }
+}
@@ -1,12 +1,15 @@
 //! Configuration loading and management
 
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
 
 /// Main configuration structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     #[serde(default)]
     pub shell: ShellConfig,
@@ -18,6 +21,10 @@ pub struct Config {
     pub modules: Vec<String>,
     #[serde(default)]
     pub theme: Option<String>,
+    /// Per-module settings, keyed by module name (`[module_config.<name>]`)
+    #[serde(default)]
+    #[schemars(with = "HashMap<String, serde_json::Map<String, serde_json::Value>>")]
+    pub module_config: HashMap<String, toml::Table>,
 }
 
 impl Default for Config {
@@ -28,20 +35,88 @@ impl Default for Config {
             completion: CompletionConfig::default(),
             modules: vec![],
             theme: Some("default".to_string()), // Standard-Theme: ziron-default
+            module_config: HashMap::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ShellConfig {
     #[serde(default = "default_shell")]
     pub default: String,
+    /// Profile script sourced at shell startup (defaults to `profile` in the config directory or ~/.zironrc)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<PathBuf>,
+    /// Render prompts through the daemon (falls back to local rendering if it is unreachable)
+    #[serde(default = "default_use_daemon")]
+    pub use_daemon: bool,
+    /// Start `ziron-daemon` when the prompt binary can't reach it
+    #[serde(default)]
+    pub auto_start_daemon: bool,
+    /// Commands run before each command line (like zsh's `preexec`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preexec: Vec<String>,
+    /// Commands run after each command line (like zsh's `precmd`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub precmd: Vec<String>,
+    /// Milliseconds a command substitution may run before it is killed (default 10000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub substitution_timeout_ms: Option<u64>,
+    /// Maximum bytes of output captured from a command substitution (default 1 MiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub substitution_max_output: Option<usize>,
+    /// How Ctrl-R searches the history
+    #[serde(default)]
+    pub history_search: HistorySearch,
+    /// History file (defaults to `history` in the state directory; `$HISTFILE` overrides)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_file: Option<PathBuf>,
+    /// Maximum number of history entries kept (`$HISTSIZE` overrides)
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+    /// Don't record a command identical to the previous one
+    #[serde(default = "default_history_ignore_dups")]
+    pub history_ignore_dups: bool,
+    /// Regexes of commands never recorded in the history (e.g. `"PASSWORD="`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history_ignore_patterns: Vec<String>,
+    /// Suggest similarly named commands when a command isn't found
+    #[serde(default = "default_suggest_commands")]
+    pub suggest_commands: bool,
+    /// Builtins that defer to the external command of the same name (`cd` and `exit` always stay builtin)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_builtins: Vec<String>,
+}
+
+/// History search behavior bound to Ctrl-R
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySearch {
+    /// Incremental reverse search for a substring (readline's reverse-i-search)
+    #[default]
+    Substring,
+    /// Replace the line with the latest entry containing the typed characters in order
+    Fuzzy,
 }
 
 impl Default for ShellConfig {
     fn default() -> Self {
         Self {
             default: default_shell(),
+            profile: None,
+            use_daemon: default_use_daemon(),
+            auto_start_daemon: false,
+            preexec: Vec::new(),
+            precmd: Vec::new(),
+            substitution_timeout_ms: None,
+            substitution_max_output: None,
+            history_search: HistorySearch::default(),
+            history_file: None,
+            history_size: default_history_size(),
+            history_ignore_dups: default_history_ignore_dups(),
+            history_ignore_patterns: Vec::new(),
+            suggest_commands: default_suggest_commands(),
+            disabled_builtins: Vec::new(),
         }
     }
 }
@@ -50,49 +125,182 @@ fn default_shell() -> String {
     "zsh".to_string()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_use_daemon() -> bool {
+    true
+}
+
+fn default_history_size() -> usize {
+    10000
+}
+
+fn default_history_ignore_dups() -> bool {
+    true
+}
+
+fn default_suggest_commands() -> bool {
+    true
+}
+
+/// Module settings shared by all modules (`[module_config.<name>]`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct SharedModuleSettings {
+    /// Glob patterns of directories the module is limited to
+    #[serde(alias = "directories")]
+    when_path: Vec<String>,
+    /// Re-fetch on every prompt instead of caching; defaults per `VOLATILE_MODULES`
+    volatile: Option<bool>,
+    /// How long the module's data stays cached, overriding `performance.cache_ttl_ms`
+    cache_ttl_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PerformanceConfig {
     #[serde(default = "default_cache_ttl")]
     pub cache_ttl_ms: u64,
+    /// Fetch a directory's modules into the daemon cache when a client reports a `cd` into it
+    #[serde(default)]
+    pub prewarm: bool,
+    /// How many modules are fetched at once while pre-warming
+    #[serde(default = "default_prewarm_concurrency")]
+    pub prewarm_concurrency: usize,
+    /// Time budget for pre-warming a directory; modules still running afterwards stay uncached
+    #[serde(default = "default_prewarm_budget")]
+    pub prewarm_budget_ms: u64,
+    /// How long a module's last good data is still shown when fetching it fails
+    #[serde(default = "default_error_grace")]
+    pub error_grace_ms: u64,
+    /// How long a module's external command (git, kubectl, ...) may run before it is killed
+    #[serde(default = "default_command_timeout")]
+    pub command_timeout_ms: u64,
 }
 
 impl Default for PerformanceConfig {
     fn default() -> Self {
         Self {
             cache_ttl_ms: default_cache_ttl(),
+            prewarm: false,
+            prewarm_concurrency: default_prewarm_concurrency(),
+            prewarm_budget_ms: default_prewarm_budget(),
+            error_grace_ms: default_error_grace(),
+            command_timeout_ms: default_command_timeout(),
         }
     }
 }
 
+impl PerformanceConfig {
+    /// How long a module's external command may run (`command_timeout_ms`)
+    pub fn command_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.command_timeout_ms)
+    }
+}
+
 fn default_cache_ttl() -> u64 {
     50
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_prewarm_concurrency() -> usize {
+    4
+}
+
+fn default_prewarm_budget() -> u64 {
+    500
+}
+
+fn default_error_grace() -> u64 {
+    30_000
+}
+
+fn default_command_timeout() -> u64 {
+    2000
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CompletionConfig {
     #[serde(default = "default_partial_completion")]
     pub partial_completion: bool,
+    /// Argument completions for commands, keyed by command name (`[completion.commands.<name>]`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, CommandCompletion>,
 }
 
 impl Default for CompletionConfig {
     fn default() -> Self {
         Self {
             partial_completion: default_partial_completion(),
+            commands: HashMap::new(),
         }
     }
 }
 
+/// Completion candidates for a command's arguments
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandCompletion {
+    /// Fixed candidates
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+    /// Shell command whose output lines are offered as candidates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
 fn default_partial_completion() -> bool {
     true // Enable by default
 }
 
+/// Merge `overlay` into `base`, recursing into tables present in both
+pub(crate) fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
 impl Config {
-    /// Load configuration from the default location (~/.config/ziron/config.toml)
+    /// Load configuration from the default location (`$XDG_CONFIG_HOME/ziron/config.toml`),
+    /// layered over the system-wide config in `/etc/ziron/config.toml`
     pub fn load() -> Result<Self> {
+        let config_path = Self::default_path()?;
+        Self::load_layered(&crate::paths::system_config_file(), &config_path)
+    }
+
+    /// Load only the user's configuration, e.g. to modify and save it
+    pub fn load_user() -> Result<Self> {
         let config_path = Self::default_path()?;
         Self::load_from(&config_path)
     }
 
+    /// Load a user config over a system config, merging tables key by key
+    ///
+    /// Either file may be missing; settings in neither fall back to the defaults.
+    pub fn load_layered(system_path: &Path, user_path: &Path) -> Result<Self> {
+        let mut table = Self::read_table(system_path)?;
+        merge_tables(&mut table, Self::read_table(user_path)?);
+
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| Error::Config(format!("Failed to parse config: {}", e)))
+    }
+
+    /// Read a config file as a TOML table, empty if the file doesn't exist
+    fn read_table(path: &Path) -> Result<toml::Table> {
+        if !path.exists() {
+            return Ok(toml::Table::new());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Failed to read config file {}: {}", path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| Error::Config(format!("Failed to parse config {}: {}", path.display(), e)))
+    }
+
     /// Load configuration from a specific path
     pub fn load_from(path: &Path) -> Result<Self> {
         if !path.exists() {
@@ -108,11 +316,116 @@ impl Config {
         Ok(config)
     }
 
+    /// Deserialize the settings table of a module, falling back to defaults
+    /// when the table is missing or malformed
+    pub fn module_settings<T: DeserializeOwned + Default>(&self, module: &str) -> T {
+        let Some(table) = self.module_config.get(module) else {
+            return T::default();
+        };
+
+        match toml::Value::Table(table.clone()).try_into() {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::warn!("Invalid settings for module {}: {}", module, e);
+                T::default()
+            }
+        }
+    }
+
+    /// Whether a module should run in `dir`, according to its `when_path` globs
+    ///
+    /// A module without `when_path` runs everywhere. Otherwise `dir` or one of its
+    /// ancestors must match a pattern (`~` expands to the home directory).
+    pub fn module_enabled_in(&self, module: &str, dir: &Path) -> bool {
+        let settings: SharedModuleSettings = self.module_settings(module);
+        if settings.when_path.is_empty() {
+            return true;
+        }
+
+        let home = std::env::var("HOME").ok();
+        settings.when_path.iter().any(|pattern| {
+            let pattern = match (pattern.strip_prefix('~'), &home) {
+                (Some(rest), Some(home)) => format!("{}{}", home, rest),
+                _ => pattern.clone(),
+            };
+            match glob::Pattern::new(pattern.trim_end_matches('/')) {
+                Ok(pattern) => dir.ancestors().any(|path| pattern.matches_path(path)),
+                Err(e) => {
+                    tracing::warn!("Invalid when_path pattern for module {}: {}", module, e);
+                    false
+                }
+            }
+        })
+    }
+
+    /// Whether a module changes on every prompt and so must bypass the cache
+    pub fn module_volatile(&self, module: &str) -> bool {
+        let settings: SharedModuleSettings = self.module_settings(module);
+        settings.volatile.unwrap_or_else(|| crate::module::VOLATILE_MODULES.contains(&module))
+    }
+
+    /// A module's `cache_ttl_ms`, when it overrides the cache's `performance.cache_ttl_ms`
+    pub fn module_cache_ttl(&self, module: &str) -> Option<std::time::Duration> {
+        let settings: SharedModuleSettings = self.module_settings(module);
+        settings.cache_ttl_ms.map(std::time::Duration::from_millis)
+    }
+
+    /// JSON Schema of the config file format, for editor completion and validation
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(Config)).unwrap_or_default()
+    }
+
     /// Get the default configuration path
     pub fn default_path() -> Result<PathBuf> {
-        let home = std::env::var("HOME")
-            .map_err(|_| Error::Config("HOME environment variable not set".to_string()))?;
-        Ok(PathBuf::from(home).join(".config").join("ziron").join("config.toml"))
+        Ok(crate::paths::config_dir()?.join("config.toml"))
+    }
+
+    /// Get the default profile path (`profile` in the config directory)
+    pub fn default_profile_path() -> Result<PathBuf> {
+        Ok(crate::paths::config_dir()?.join("profile"))
+    }
+
+    /// Get the daemon socket path (`ziron.sock` in the runtime directory)
+    pub fn socket_path() -> Result<PathBuf> {
+        crate::paths::socket_file()
+    }
+
+    /// Resolve the profile script to source at startup, if one exists
+    pub fn profile_path(&self) -> Option<PathBuf> {
+        if let Some(profile) = &self.shell.profile {
+            return Some(profile.clone());
+        }
+
+        let mut candidates = Vec::new();
+        if let Ok(path) = Self::default_profile_path() {
+            candidates.push(path);
+        }
+        if let Ok(home) = std::env::var("HOME") {
+            candidates.push(PathBuf::from(home).join(".zironrc"));
+        }
+        candidates.into_iter().find(|path| path.exists())
+    }
+
+    /// Resolve the shell history file (`shell.history_file`, then the default)
+    pub fn history_path(&self) -> Option<PathBuf> {
+        self.shell
+            .history_file
+            .clone()
+            .or_else(|| crate::paths::history_file().ok())
+    }
+
+    /// Let `$HISTFILE` and `$HISTSIZE` override the configured history file and size
+    pub fn apply_env_overrides(&mut self) {
+        self.apply_env_overrides_from(|var| std::env::var_os(var));
+    }
+
+    fn apply_env_overrides_from(&mut self, env: impl Fn(&str) -> Option<std::ffi::OsString>) {
+        if let Some(histfile) = env("HISTFILE").filter(|s| !s.is_empty()) {
+            self.shell.history_file = Some(PathBuf::from(histfile));
+        }
+        if let Some(size) = env("HISTSIZE").and_then(|size| size.to_str()?.trim().parse().ok()) {
+            self.shell.history_size = size;
+        }
     }
 
     /// Save configuration to the default location
@@ -159,11 +472,30 @@ mod tests {
         let config = Config {
             shell: ShellConfig {
                 default: "bash".to_string(),
+                profile: None,
+                use_daemon: true,
+                auto_start_daemon: false,
+                preexec: Vec::new(),
+                precmd: vec!["echo done".to_string()],
+                substitution_timeout_ms: Some(5000),
+                substitution_max_output: None,
+                history_search: HistorySearch::Fuzzy,
+                history_file: None,
+                history_size: 500,
+                history_ignore_dups: true,
+                history_ignore_patterns: vec!["PASSWORD=".to_string()],
+                suggest_commands: false,
+                disabled_builtins: vec!["echo".to_string()],
+            },
+            performance: PerformanceConfig {
+                cache_ttl_ms: 100,
+                prewarm: true,
+                ..PerformanceConfig::default()
             },
-            performance: PerformanceConfig { cache_ttl_ms: 100 },
             completion: CompletionConfig::default(),
             modules: vec!["git".to_string(), "sysinfo".to_string()],
             theme: Some("default".to_string()),
+            module_config: HashMap::new(),
         };
 
         config.save_to(&config_path).unwrap();
@@ -173,6 +505,125 @@ mod tests {
         assert_eq!(loaded.performance.cache_ttl_ms, 100);
         assert_eq!(loaded.modules.len(), 2);
         assert_eq!(loaded.theme, Some("default".to_string()));
+        assert_eq!(loaded.shell.history_search, HistorySearch::Fuzzy);
     }
-}
 
+    #[test]
+    fn test_user_config_layered_over_system_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let system_path = temp_dir.path().join("system.toml");
+        let user_path = temp_dir.path().join("user.toml");
+        fs::write(
+            &system_path,
+            "theme = \"minimal\"\nmodules = [\"git\"]\n\n[performance]\ncache_ttl_ms = 200\nprewarm = true\n\n\
+             [module_config.git]\nwhen_path = [\"/srv\"]\nvolatile = true\n",
+        )
+        .unwrap();
+        fs::write(&user_path, "[performance]\ncache_ttl_ms = 20\n\n[module_config.git]\nvolatile = false\n").unwrap();
+
+        let config = Config::load_layered(&system_path, &user_path).unwrap();
+        assert_eq!(config.performance.cache_ttl_ms, 20);
+        assert!(config.performance.prewarm);
+        assert_eq!(config.performance.prewarm_concurrency, 4);
+        assert_eq!(config.theme.as_deref(), Some("minimal"));
+        assert_eq!(config.modules, vec!["git"]);
+        assert!(config.module_config["git"].contains_key("when_path"));
+        assert!(!config.module_volatile("git"));
+
+        // Without a system config the user file applies over the defaults
+        let config = Config::load_layered(&temp_dir.path().join("missing.toml"), &user_path).unwrap();
+        assert_eq!(config.performance.cache_ttl_ms, 20);
+        assert!(!config.performance.prewarm);
+        assert_eq!(config.modules, Config::default().modules);
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    struct SampleSettings {
+        #[serde(default)]
+        patterns: Vec<String>,
+    }
+
+    #[test]
+    fn test_history_env_overrides() {
+        let mut config = Config::default();
+        config.shell.history_file = Some(PathBuf::from("/configured/history"));
+        config.apply_env_overrides_from(|var| match var {
+            "HISTFILE" => Some("/env/history".into()),
+            "HISTSIZE" => Some(" 42 ".into()),
+            _ => None,
+        });
+        assert_eq!(config.history_path(), Some(PathBuf::from("/env/history")));
+        assert_eq!(config.shell.history_size, 42);
+
+        let mut config = Config::default();
+        config.apply_env_overrides_from(|var| match var {
+            "HISTFILE" => Some("".into()),
+            "HISTSIZE" => Some("lots".into()),
+            _ => None,
+        });
+        assert_eq!(config.shell.history_file, None);
+        assert_eq!(config.shell.history_size, default_history_size());
+    }
+
+    #[test]
+    fn test_module_settings() {
+        let config: Config = toml::from_str(
+            r#"
+            [module_config.kubernetes]
+            patterns = ["prod"]
+            "#,
+        )
+        .unwrap();
+
+        let settings: SampleSettings = config.module_settings("kubernetes");
+        assert_eq!(settings.patterns, vec!["prod".to_string()]);
+
+        let missing: SampleSettings = config.module_settings("git");
+        assert!(missing.patterns.is_empty());
+    }
+
+    #[test]
+    fn test_module_volatile_defaults_and_override() {
+        let mut config = Config::default();
+        assert!(config.module_volatile("time"));
+        assert!(!config.module_volatile("aws"));
+
+        config.module_config.insert("aws".to_string(), toml::from_str("volatile = true").unwrap());
+        config.module_config.insert("time".to_string(), toml::from_str("volatile = false").unwrap());
+        assert!(config.module_volatile("aws"));
+        assert!(!config.module_volatile("time"));
+    }
+
+    #[test]
+    fn test_module_cache_ttl_override() {
+        let mut config = Config::default();
+        config.module_config.insert("aws".to_string(), toml::from_str("cache_ttl_ms = 60000").unwrap());
+        assert_eq!(config.module_cache_ttl("aws"), Some(std::time::Duration::from_secs(60)));
+        assert_eq!(config.module_cache_ttl("git"), None);
+    }
+
+    #[test]
+    fn test_module_enabled_in() {
+        let config: Config = toml::from_str(
+            r#"
+            [module_config.aws]
+            when_path = ["/srv/infra", "/opt/*/deploy"]
+
+            [module_config.docker]
+            directories = ["/srv/apps/*"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.module_enabled_in("aws", Path::new("/srv/infra")));
+        assert!(config.module_enabled_in("aws", Path::new("/srv/infra/prod/vpc")));
+        assert!(config.module_enabled_in("aws", Path::new("/opt/site/deploy")));
+        assert!(!config.module_enabled_in("aws", Path::new("/srv/other")));
+        assert!(!config.module_enabled_in("aws", Path::new("/srv")));
+
+        assert!(config.module_enabled_in("docker", Path::new("/srv/apps/web")));
+        assert!(!config.module_enabled_in("docker", Path::new("/home/me")));
+
+        assert!(config.module_enabled_in("git", Path::new("/anywhere")));
+    }
+}
@@ -28,5 +28,11 @@ pub enum Error {
 
     #[error("IPC error: {0}")]
     Ipc(String),
+
+    #[error("Command not found: {0}")]
+    CommandNotFound(String),
+
+    #[error("Command timed out: {0}")]
+    Timeout(String),
 }
 
@@ -15,6 +15,8 @@ pub enum EventType {
     CommandExecuted,
     /// Shell initialized
     ShellInit,
+    /// A module's data changed after being re-fetched
+    ModuleChanged,
 }
 
 /// Event structure
@@ -47,5 +49,13 @@ impl Event {
             serde_json::json!({ "path": path }),
         )
     }
+
+    /// Create a module changed event
+    pub fn module_changed(module: String, path: String) -> Self {
+        Self::new(
+            EventType::ModuleChanged,
+            serde_json::json!({ "module": module, "path": path }),
+        )
+    }
 }
 
@@ -0,0 +1,85 @@
+//! Segment icons with ASCII fallback for terminals without Nerd Fonts
+
+use crate::theme::Segment;
+
+/// ASCII replacements for module icons, used when Nerd Font glyphs are disabled
+const ASCII_FALLBACKS: &[(&str, &str)] = &[
+    ("git", "git:"),
+    ("svn", "svn:"),
+    ("mercurial", "hg:"),
+    ("jj", "jj:"),
+    ("docker", "docker:"),
+    ("kubernetes", "k8s:"),
+    ("aws", "aws:"),
+    ("gcp", "gcp:"),
+    ("azure", "az:"),
+    ("cloud", "cloud:"),
+    ("terraform", "tf:"),
+    ("go", "go:"),
+    ("helm", "helm:"),
+    ("shell", "sh:"),
+    ("session", "up"),
+    ("node", "node:"),
+    ("rust", "rs:"),
+    ("venv", "py:"),
+    ("conda", "conda:"),
+    ("time", "at"),
+    ("timer", "took"),
+    ("exitcode", "x"),
+    ("symbol", ">"),
+];
+
+/// Whether icons should fall back to ASCII (`ZIRON_ASCII=1` or `NO_NERD_FONT` set)
+pub fn ascii_mode() -> bool {
+    std::env::var("ZIRON_ASCII").map(|v| v == "1").unwrap_or(false)
+        || std::env::var_os("NO_NERD_FONT").is_some()
+}
+
+/// ASCII fallback for a module's icon
+pub fn ascii_fallback(module: &str) -> Option<&'static str> {
+    ASCII_FALLBACKS
+        .iter()
+        .find(|(name, _)| *name == module)
+        .map(|(_, ascii)| *ascii)
+}
+
+/// Resolve the icon to display for a segment, if any
+pub fn resolve(segment: &Segment, ascii: bool) -> Option<String> {
+    let icon = segment.icon.as_ref().filter(|icon| !icon.is_empty())?;
+    if !ascii {
+        return Some(icon.clone());
+    }
+
+    segment
+        .ascii_icon
+        .clone()
+        .or_else(|| ascii_fallback(&segment.module).map(|s| s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(module: &str, icon: Option<&str>, ascii_icon: Option<&str>) -> Segment {
+        let mut segment: Segment = toml::from_str(&format!("module = \"{}\"", module)).unwrap();
+        segment.icon = icon.map(|s| s.to_string());
+        segment.ascii_icon = ascii_icon.map(|s| s.to_string());
+        segment
+    }
+
+    #[test]
+    fn test_resolve_icon() {
+        let git = segment("git", Some("\u{e0a0}"), None);
+        assert_eq!(resolve(&git, false), Some("\u{e0a0}".to_string()));
+        assert_eq!(resolve(&git, true), Some("git:".to_string()));
+
+        let custom = segment("rust", Some("🦀"), Some("rust"));
+        assert_eq!(resolve(&custom, true), Some("rust".to_string()));
+
+        let unknown = segment("custom", Some("\u{f120}"), None);
+        assert_eq!(resolve(&unknown, true), None);
+
+        let none = segment("git", None, Some("git:"));
+        assert_eq!(resolve(&none, true), None);
+    }
+}
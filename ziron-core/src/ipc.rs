@@ -1,11 +1,19 @@
 //! IPC protocol for daemon communication
 
 use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use crate::cache::ModuleCacheStats;
+use crate::error::{Error, Result};
+use crate::event::EventType;
 use crate::module::{ModuleContext, ModuleData};
+use crate::prompt::StyledSegment;
 
 /// Protocol version
 pub const PROTOCOL_VERSION: u32 = 1;
 
+/// Largest message accepted on the socket, so a corrupt length prefix can't exhaust memory
+pub const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
 /// Request message types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Request {
@@ -13,17 +21,27 @@ pub enum Request {
     GetPrompt {
         context: ModuleContext,
     },
+    /// Request the prompt as styled segments instead of an ANSI string
+    GetPromptJson {
+        context: ModuleContext,
+    },
     /// Request module data
     GetModuleData {
         module: String,
         context: ModuleContext,
     },
+    /// Report that the client changed directory, so its prompt modules can be pre-warmed
+    DirectoryChanged {
+        context: ModuleContext,
+    },
     /// Invalidate cache for a module or all modules
     InvalidateCache {
         module: Option<String>,
     },
     /// Get cache statistics
     GetCacheStats,
+    /// Stream daemon events until the client disconnects
+    SubscribeEvents,
     /// Shutdown daemon
     Shutdown,
     /// Health check
@@ -35,6 +53,8 @@ pub enum Request {
 pub enum Response {
     /// Prompt string
     Prompt(String),
+    /// Prompt as styled segments
+    PromptSegments(Vec<StyledSegment>),
     /// Module data
     ModuleData(ModuleData),
     /// Cache statistics
@@ -42,6 +62,14 @@ pub enum Response {
         hits: u64,
         misses: u64,
         size: usize,
+        /// Hits and misses per module
+        modules: std::collections::BTreeMap<String, ModuleCacheStats>,
+    },
+    /// An event from a subscribed stream
+    Event {
+        event_type: EventType,
+        /// Event data as JSON text, since bincode cannot encode arbitrary JSON values
+        data: String,
     },
     /// Success response
     Ok,
@@ -89,13 +117,85 @@ impl Message {
     }
 
     /// Serialize message to bytes
-    pub fn serialize(&self) -> Result<Vec<u8>, bincode::Error> {
+    pub fn serialize(&self) -> std::result::Result<Vec<u8>, bincode::Error> {
         bincode::serialize(self)
     }
 
     /// Deserialize message from bytes
-    pub fn deserialize(data: &[u8]) -> Result<Self, bincode::Error> {
+    pub fn deserialize(data: &[u8]) -> std::result::Result<Self, bincode::Error> {
         bincode::deserialize(data)
     }
+
+    /// The message as sent on the socket: its length as 4 little-endian bytes, then the bincode data
+    pub fn to_frame(&self) -> Result<Vec<u8>> {
+        let data = self.serialize().map_err(|e| Error::Ipc(format!("Failed to serialize message: {}", e)))?;
+        let mut frame = Vec::with_capacity(4 + data.len());
+        frame.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&data);
+        Ok(frame)
+    }
+}
+
+/// The length a message's 4-byte prefix announces, rejecting anything over `MAX_MESSAGE_LEN`
+pub fn message_len(prefix: [u8; 4]) -> Result<usize> {
+    let len = u32::from_le_bytes(prefix) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(Error::Ipc(format!("Message of {} bytes exceeds the {} byte limit", len, MAX_MESSAGE_LEN)));
+    }
+    Ok(len)
+}
+
+/// Write a length-prefixed request message
+pub fn write_request(stream: &mut impl Write, request: Request) -> Result<()> {
+    stream.write_all(&Message::new_request(0, request).to_frame()?)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Read a length-prefixed response message
+pub fn read_response(stream: &mut impl Read) -> Result<Response> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let mut buffer = vec![0u8; message_len(len_buf)?];
+    stream.read_exact(&mut buffer)?;
+
+    let message = Message::deserialize(&buffer)
+        .map_err(|e| Error::Ipc(format!("Failed to deserialize response: {}", e)))?;
+    match message.payload {
+        MessagePayload::Response(response) => Ok(response),
+        MessagePayload::Request(_) => Err(Error::Ipc("Unexpected request from daemon".to_string())),
+    }
 }
 
+/// Send one request to the daemon and wait for its response
+pub fn request<S: Read + Write>(stream: &mut S, request: Request) -> Result<Response> {
+    write_request(stream, request)?;
+    read_response(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        let mut sent = Vec::new();
+        write_request(&mut sent, Request::HealthCheck).unwrap();
+        assert_eq!(message_len(sent[..4].try_into().unwrap()).unwrap(), sent.len() - 4);
+        let message = Message::deserialize(&sent[4..]).unwrap();
+        assert!(matches!(message.payload, MessagePayload::Request(Request::HealthCheck)));
+
+        let reply = Message::new_response(0, Response::Prompt("~ > ".to_string())).to_frame().unwrap();
+        match read_response(&mut reply.as_slice()).unwrap() {
+            Response::Prompt(prompt) => assert_eq!(prompt, "~ > "),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_oversized_message_rejected() {
+        let mut reply = (u32::MAX).to_le_bytes().to_vec();
+        reply.extend_from_slice(b"garbage");
+        assert!(matches!(read_response(&mut reply.as_slice()), Err(Error::Ipc(_))));
+    }
+}
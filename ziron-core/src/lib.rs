@@ -3,15 +3,41 @@
 //! This library provides the core functionality for the Ziron shell framework,
 //! including configuration loading, module registry, event system, prompt pipeline,
 //! and IPC interface.
+//!
+//! # Rendering without the daemon
+//!
+//! Status bars and editors can render a prompt in-process with [`render_prompt`],
+//! which fetches the built-in modules and renders them with a theme:
+//!
+//! ```no_run
+//! use ziron_core::config::Config;
+//! use ziron_core::module::ModuleContext;
+//! use ziron_core::theme::Theme;
+//!
+//! # fn main() -> ziron_core::Result<()> {
+//! let config = Config::load()?;
+//! let theme = Theme::load_from(&Theme::default_path()?)?;
+//! let prompt = ziron_core::render_prompt(&config, &theme, &ModuleContext::from_env()?)?;
+//! print!("{}", prompt);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Other modules (git, kubernetes, ...) live in their own crates; register their
+//! fetchers on a [`module::ModuleRegistry`] and call [`render_prompt_with`].
 
 pub mod cache;
 pub mod config;
 pub mod error;
 pub mod event;
+pub mod icons;
 pub mod ipc;
 pub mod module;
+pub mod paths;
+pub mod project;
 pub mod prompt;
 pub mod theme;
 
 pub use error::{Error, Result};
+pub use prompt::{render_prompt, render_prompt_with};
 
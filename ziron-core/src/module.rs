@@ -2,10 +2,31 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
 
+use crate::config::Config;
 use crate::error::{Error, Result};
 
+/// Function producing a module's data for a context
+pub type ModuleFetcher = fn(&ModuleContext, &Config) -> Result<ModuleData>;
+
+/// Names of the modules shipped with Ziron
+pub const KNOWN_MODULES: &[&str] = &[
+    "symbol", "cwd", "git", "sysinfo", "exitcode", "timer", "time", "venv", "node", "rust", "conda",
+    "svn", "mercurial", "docker", "kubernetes", "aws", "gcp", "azure", "terraform", "go", "helm",
+    "shell", "session", "cloud", "jj",
+];
+
+/// Modules whose data changes on every prompt, so they bypass the cache by default
+pub const VOLATILE_MODULES: &[&str] = &["symbol", "time", "timer", "exitcode", "session"];
+
+/// Normalize a configured module name for dispatch (`" Git "` -> `"git"`)
+pub fn normalize_module_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
 /// Module manifest structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleManifest {
@@ -21,6 +42,7 @@ pub struct ModuleManifest {
 pub struct ModuleRegistry {
     modules: HashMap<String, ModuleManifest>,
     module_paths: HashMap<String, PathBuf>,
+    fetchers: HashMap<String, ModuleFetcher>,
 }
 
 impl ModuleRegistry {
@@ -29,6 +51,33 @@ impl ModuleRegistry {
         Self::default()
     }
 
+    /// Create a registry with the modules built into ziron-core (`symbol`, `cwd`)
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register_fetcher("symbol", builtin_symbol);
+        registry.register_fetcher("cwd", builtin_cwd);
+        registry
+    }
+
+    /// Register the data fetcher for a module
+    pub fn register_fetcher(&mut self, name: &str, fetcher: ModuleFetcher) {
+        self.fetchers.insert(normalize_module_name(name), fetcher);
+    }
+
+    /// Fetch data for a module; `Ok(None)` if no fetcher is registered under that name
+    /// or the module is limited (`when_path`) to other directories
+    pub fn fetch(&self, name: &str, context: &ModuleContext, config: &Config) -> Result<Option<ModuleData>> {
+        let name = normalize_module_name(name);
+        if !config.module_enabled_in(&name, &context.current_dir) {
+            return Ok(None);
+        }
+
+        match self.fetchers.get(&name) {
+            Some(fetcher) => fetcher(context, config).map(Some),
+            None => Ok(None),
+        }
+    }
+
     /// Register a module from a manifest file
     pub fn register(&mut self, manifest_path: &PathBuf) -> Result<()> {
         let content = std::fs::read_to_string(manifest_path)
@@ -76,9 +125,89 @@ pub struct ModuleContext {
     pub user: String,
     pub hostname: String,
     pub exit_code: Option<i32>,
+    /// When the shell session started, in milliseconds since the Unix epoch (`$ZIRON_SESSION_START`)
+    #[serde(default)]
+    pub session_start_ms: Option<u64>,
+    /// Width of the terminal the prompt is shown in, in columns
+    #[serde(default)]
+    pub terminal_width: Option<usize>,
+    /// Colors the terminal can display
+    #[serde(default)]
+    pub color_support: ColorSupport,
+    /// How long the last command ran, in milliseconds (`$ZIRON_CMD_DURATION_MS`)
+    #[serde(default)]
+    pub cmd_duration_ms: Option<u64>,
+    /// Whether icons fall back to ASCII (`ZIRON_ASCII=1` or `NO_NERD_FONT`)
+    #[serde(default)]
+    pub ascii_icons: bool,
+}
+
+/// Colors a terminal can display, from richest to none
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSupport {
+    /// 24-bit color; the default, matching what themes render without downgrading
+    #[default]
+    TrueColor,
+    /// The 256-color palette
+    Ansi256,
+    /// The 16 basic colors
+    Ansi16,
+    /// No colors at all (`NO_COLOR`, `TERM=dumb`, or no terminal)
+    None,
+}
+
+impl ColorSupport {
+    /// Detect color support from the environment and whether a terminal is attached
+    pub fn detect() -> Self {
+        let is_tty = [libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO]
+            .into_iter()
+            // SAFETY: isatty only inspects the descriptor
+            .any(|fd| unsafe { libc::isatty(fd) } == 1);
+        Self::detect_with(|name| std::env::var(name).ok(), is_tty)
+    }
+
+    /// Detect color support from `NO_COLOR`, `COLORTERM` and `TERM`
+    fn detect_with(env: impl Fn(&str) -> Option<String>, is_tty: bool) -> Self {
+        let var = |name: &str| env(name).filter(|value| !value.is_empty());
+        if var("NO_COLOR").is_some() {
+            return Self::None;
+        }
+
+        let term = var("TERM");
+        if term.as_deref() == Some("dumb") {
+            return Self::None;
+        }
+        if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit")) {
+            return Self::TrueColor;
+        }
+        match term {
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            Some(_) => Self::Ansi16,
+            None if is_tty => Self::Ansi16,
+            None => Self::None,
+        }
+    }
 }
 
 impl ModuleContext {
+    /// A context for `dir` with fixed values instead of the environment's, for tests
+    #[doc(hidden)]
+    pub fn for_test(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            current_dir: dir.into(),
+            shell: "zsh".to_string(),
+            user: "user".to_string(),
+            hostname: "host".to_string(),
+            exit_code: None,
+            session_start_ms: None,
+            terminal_width: None,
+            color_support: ColorSupport::default(),
+            cmd_duration_ms: None,
+            ascii_icons: false,
+        }
+    }
+
     /// Create a new module context from the current environment
     pub fn from_env() -> Result<Self> {
         Ok(Self {
@@ -86,17 +215,35 @@ impl ModuleContext {
                 .map_err(|e| Error::Io(e))?,
             shell: std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string()),
             user: std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
-            hostname: std::env::var("HOSTNAME").unwrap_or_else(|_| {
-                hostname::get()
-                    .ok()
-                    .and_then(|h| h.to_str().map(|s| s.to_string()))
-                    .unwrap_or_else(|| "unknown".to_string())
-            }),
-            exit_code: None,
+            // $HOSTNAME is often unset or stale in non-login shells, so only use it as a last resort
+            hostname: hostname::get()
+                .ok()
+                .and_then(|h| h.to_str().map(|s| s.to_string()))
+                .filter(|h| !h.is_empty())
+                .or_else(|| std::env::var("HOSTNAME").ok())
+                .unwrap_or_else(|| "unknown".to_string()),
+            exit_code: std::env::var("ZIRON_LAST_EXIT_CODE").ok().and_then(|s| s.trim().parse().ok()),
+            session_start_ms: std::env::var("ZIRON_SESSION_START").ok().and_then(|s| s.trim().parse().ok()),
+            terminal_width: terminal_width(),
+            color_support: ColorSupport::detect(),
+            cmd_duration_ms: std::env::var("ZIRON_CMD_DURATION_MS").ok().and_then(|s| s.trim().parse().ok()),
+            ascii_icons: crate::icons::ascii_mode(),
         })
     }
 }
 
+/// Width of the controlling terminal, falling back to `$COLUMNS`
+fn terminal_width() -> Option<usize> {
+    for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO] {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        // SAFETY: TIOCGWINSZ only writes a winsize into the struct we pass
+        if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+            return Some(size.ws_col as usize);
+        }
+    }
+    std::env::var("COLUMNS").ok()?.trim().parse().ok().filter(|&columns| columns > 0)
+}
+
 /// Module data returned by plugins
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleData {
@@ -105,3 +252,257 @@ pub struct ModuleData {
     pub cached: bool,
 }
 
+impl ModuleData {
+    /// Empty module data carrying an `error` key, for tools that failed to run
+    pub fn error(module: &str, error: &Error) -> Self {
+        Self {
+            module: module.to_string(),
+            data: serde_json::json!({
+                "text": "",
+                "error": error.to_string(),
+            }),
+            cached: false,
+        }
+    }
+}
+
+/// Run `cmd` with `args` (in `dir`, if given), killing it once `timeout` passes
+///
+/// A missing program is reported as `Error::CommandNotFound` and a killed one as `Error::Timeout`.
+pub fn run_command_with_timeout(cmd: &str, args: &[&str], dir: Option<&Path>, timeout: Duration) -> Result<Output> {
+    let mut command = Command::new(cmd);
+    command.args(args);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    command_output(&mut command, timeout)
+}
+
+/// Collect a command's output like `Command::output`, with a deadline
+fn command_output(command: &mut Command, timeout: Duration) -> Result<Output> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let child = match command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(Error::CommandNotFound(program)),
+        Err(e) => return Err(e.into()),
+    };
+
+    // One thread collects the output (draining both pipes) while this one waits out the deadline
+    let pid = child.id();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(child.wait_with_output());
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(output) => Ok(output?),
+        Err(_) => {
+            // The child isn't reaped until `wait_with_output` returns, so the pid is still its own;
+            // the collecting thread reaps it once the pipes close
+            unsafe {
+                libc::kill(pid as libc::pid_t, libc::SIGKILL);
+            }
+            Err(Error::Timeout(format!("{} after {} ms", program, timeout.as_millis())))
+        }
+    }
+}
+
+/// Run a module's external tool, returning its trimmed stdout if it exited successfully
+///
+/// A tool that isn't installed yields `Ok(None)` silently, like a non-zero exit;
+/// any other failure to run it (including a timeout) is logged as a warning and returned as an error.
+pub fn run_tool(command: &mut Command, timeout: Duration) -> Result<Option<String>> {
+    match command_output(command, timeout) {
+        Ok(output) if output.status.success() => {
+            Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()))
+        }
+        Ok(_) => Ok(None),
+        Err(Error::CommandNotFound(_)) => Ok(None),
+        Err(e) => {
+            let program = command.get_program().to_string_lossy().to_string();
+            tracing::warn!("Failed to run {}: {}", program, e);
+            Err(Error::Module(format!("Failed to run {}: {}", program, e)))
+        }
+    }
+}
+
+/// Symbol module settings (`[module_config.symbol]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SymbolConfig {
+    /// Color after a command that succeeded quickly
+    pub success_color: String,
+    /// Color after a command that failed
+    pub error_color: String,
+    /// Color after a command that succeeded but ran for at least `slow_ms`
+    pub slow_color: String,
+    /// Milliseconds after which a command counts as slow
+    pub slow_ms: u64,
+}
+
+impl Default for SymbolConfig {
+    fn default() -> Self {
+        Self {
+            success_color: "green".to_string(),
+            error_color: "red".to_string(),
+            slow_color: "yellow".to_string(),
+            slow_ms: 5000,
+        }
+    }
+}
+
+impl SymbolConfig {
+    /// The status of the last command (`success`, `slow` or `error`) and its color
+    ///
+    /// `None` when the context carries neither an exit code nor a duration.
+    fn status<'a>(&'a self, context: &ModuleContext) -> Option<(&'static str, &'a str)> {
+        if context.exit_code.is_none() && context.cmd_duration_ms.is_none() {
+            return None;
+        }
+        if context.exit_code.is_some_and(|code| code != 0) {
+            Some(("error", &self.error_color))
+        } else if context.cmd_duration_ms.is_some_and(|ms| ms >= self.slow_ms) {
+            Some(("slow", &self.slow_color))
+        } else {
+            Some(("success", &self.success_color))
+        }
+    }
+}
+
+fn builtin_symbol(context: &ModuleContext, config: &Config) -> Result<ModuleData> {
+    let settings: SymbolConfig = config.module_settings("symbol");
+    let mut data = serde_json::json!({"text": " ⚡"});
+    if let Some((status, color)) = settings.status(context) {
+        data["status"] = status.into();
+        data["color"] = color.into();
+    }
+    Ok(ModuleData {
+        module: "symbol".to_string(),
+        data,
+        cached: false,
+    })
+}
+
+fn builtin_cwd(context: &ModuleContext, _config: &Config) -> Result<ModuleData> {
+    let cwd = context.current_dir.display().to_string();
+    let home = std::env::var("HOME").unwrap_or_else(|_| format!("/home/{}", context.user));
+    let cwd_short = if cwd.starts_with(&home) {
+        cwd.replace(&home, "~")
+    } else {
+        cwd
+    };
+    Ok(ModuleData {
+        module: "cwd".to_string(),
+        data: serde_json::json!({"text": cwd_short}),
+        cached: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_git(_context: &ModuleContext, _config: &Config) -> Result<ModuleData> {
+        Ok(ModuleData {
+            module: "git".to_string(),
+            data: serde_json::json!({"text": "main"}),
+            cached: false,
+        })
+    }
+
+    #[test]
+    fn test_fetch_normalizes_module_name() {
+        let mut registry = ModuleRegistry::new();
+        registry.register_fetcher("git", fake_git);
+        let context = ModuleContext::for_test("/");
+        let config = Config::default();
+
+        for name in ["git", "Git", " git ", "GIT"] {
+            let data = registry.fetch(name, &context, &config).unwrap();
+            assert_eq!(data.map(|d| d.module), Some("git".to_string()), "name {:?}", name);
+        }
+        assert!(registry.fetch("svn", &context, &config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fetch_skips_module_outside_when_path() {
+        let mut registry = ModuleRegistry::new();
+        registry.register_fetcher("git", fake_git);
+        let config: Config = toml::from_str("[module_config.git]\nwhen_path = [\"/srv/repos\"]").unwrap();
+        assert!(registry.fetch("git", &ModuleContext::for_test("/srv/repos/ziron"), &config).unwrap().is_some());
+        assert!(registry.fetch("git", &ModuleContext::for_test("/tmp"), &config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_symbol_color_from_last_command() {
+        let config: Config = toml::from_str("[module_config.symbol]\nslow_ms = 2000\nslow_color = \"#ff8800\"").unwrap();
+        let symbol = |exit_code: Option<i32>, cmd_duration_ms: Option<u64>| {
+            let context = ModuleContext {
+                exit_code,
+                cmd_duration_ms,
+                ..ModuleContext::for_test("/")
+            };
+            builtin_symbol(&context, &config).unwrap().data
+        };
+
+        let fast = symbol(Some(0), Some(150));
+        assert_eq!((&fast["status"], &fast["color"]), (&"success".into(), &"green".into()));
+        let slow = symbol(Some(0), Some(2000));
+        assert_eq!((&slow["status"], &slow["color"]), (&"slow".into(), &"#ff8800".into()));
+        let failed = symbol(Some(1), Some(60_000));
+        assert_eq!((&failed["status"], &failed["color"]), (&"error".into(), &"red".into()));
+
+        // Without anything to report the theme's color applies
+        assert!(symbol(None, None).get("color").is_none());
+    }
+
+    #[test]
+    fn test_color_support_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        };
+
+        let truecolor = env(&[("COLORTERM", "truecolor"), ("TERM", "xterm-256color")]);
+        assert_eq!(ColorSupport::detect_with(truecolor, true), ColorSupport::TrueColor);
+        let dumb = env(&[("COLORTERM", "truecolor"), ("TERM", "dumb")]);
+        assert_eq!(ColorSupport::detect_with(dumb, true), ColorSupport::None);
+
+        assert_eq!(ColorSupport::detect_with(env(&[("TERM", "screen-256color")]), true), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::detect_with(env(&[("TERM", "xterm")]), false), ColorSupport::Ansi16);
+        assert_eq!(ColorSupport::detect_with(env(&[("NO_COLOR", "1"), ("TERM", "xterm")]), true), ColorSupport::None);
+        assert_eq!(ColorSupport::detect_with(env(&[]), false), ColorSupport::None);
+    }
+
+    #[test]
+    fn test_run_tool_missing_binary_is_silent() {
+        let output = run_tool(&mut std::process::Command::new("ziron-no-such-tool"), Duration::from_secs(5)).unwrap();
+        assert!(output.is_none());
+    }
+
+    #[test]
+    fn test_run_command_with_timeout() {
+        let timeout = Duration::from_secs(5);
+        let output = run_command_with_timeout("sh", &["-c", "echo hi; exit 3"], Some(Path::new("/")), timeout).unwrap();
+        assert_eq!(output.stdout, b"hi\n");
+        assert_eq!(output.status.code(), Some(3));
+
+        let start = std::time::Instant::now();
+        let err = run_command_with_timeout("sleep", &["5"], None, Duration::from_millis(50)).unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{}", err);
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        let err = run_command_with_timeout("ziron-no-such-tool", &[], None, timeout).unwrap_err();
+        assert!(matches!(err, Error::CommandNotFound(ref program) if program == "ziron-no-such-tool"), "{}", err);
+    }
+
+    #[test]
+    fn test_run_tool_reports_other_errors() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let err = run_tool(&mut std::process::Command::new(temp_dir.path()), Duration::from_secs(5)).unwrap_err();
+        assert!(err.to_string().contains("Failed to run"));
+
+        let data = ModuleData::error("kubernetes", &err);
+        assert_eq!(data.data["text"], "");
+        assert!(data.data["error"].as_str().unwrap().contains("Failed to run"));
+    }
+}
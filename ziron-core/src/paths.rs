@@ -0,0 +1,98 @@
+//! XDG base directory resolution for Ziron's files
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+
+/// Configuration directory (`$XDG_CONFIG_HOME/ziron`, default `~/.config/ziron`)
+pub fn config_dir() -> Result<PathBuf> {
+    xdg_dir(env, "XDG_CONFIG_HOME", ".config")
+}
+
+/// System-wide configuration file that the user's config is layered over
+pub fn system_config_file() -> PathBuf {
+    PathBuf::from("/etc/ziron/config.toml")
+}
+
+/// Data directory (`$XDG_DATA_HOME/ziron`, default `~/.local/share/ziron`)
+pub fn data_dir() -> Result<PathBuf> {
+    xdg_dir(env, "XDG_DATA_HOME", ".local/share")
+}
+
+/// State directory for history and logs (`$XDG_STATE_HOME/ziron`, default `~/.local/state/ziron`)
+pub fn state_dir() -> Result<PathBuf> {
+    xdg_dir(env, "XDG_STATE_HOME", ".local/state")
+}
+
+/// Runtime directory for the socket and PID file (`$XDG_RUNTIME_DIR/ziron`, default the state directory)
+pub fn runtime_dir() -> Result<PathBuf> {
+    match absolute(env("XDG_RUNTIME_DIR")) {
+        Some(dir) => Ok(dir.join("ziron")),
+        None => state_dir(),
+    }
+}
+
+/// Shell history file; an existing `~/.ziron_history` keeps being used
+pub fn history_file() -> Result<PathBuf> {
+    let path = state_dir()?.join("history");
+    if !path.exists() {
+        if let Some(legacy) = env("HOME").map(|home| PathBuf::from(home).join(".ziron_history")) {
+            if legacy.exists() {
+                return Ok(legacy);
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// Daemon socket
+pub fn socket_file() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join("ziron.sock"))
+}
+
+/// Daemon PID file
+pub fn pid_file() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join("ziron-daemon.pid"))
+}
+
+fn env(var: &str) -> Option<OsString> {
+    std::env::var_os(var)
+}
+
+/// `$var/ziron`, or `~/<fallback>/ziron` when the variable is unset or not absolute
+fn xdg_dir(env: impl Fn(&str) -> Option<OsString>, var: &str, fallback: &str) -> Result<PathBuf> {
+    if let Some(dir) = absolute(env(var)) {
+        return Ok(dir.join("ziron"));
+    }
+    let home = env("HOME").ok_or_else(|| Error::Config("HOME environment variable not set".to_string()))?;
+    Ok(PathBuf::from(home).join(fallback).join("ziron"))
+}
+
+/// The XDG spec says relative paths are invalid and must be ignored
+fn absolute(value: Option<OsString>) -> Option<PathBuf> {
+    value.map(PathBuf::from).filter(|path| path.is_absolute())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<OsString> {
+        move |var| vars.iter().find(|(name, _)| *name == var).map(|(_, value)| value.into())
+    }
+
+    #[test]
+    fn test_xdg_config_home_honored() {
+        let env = fake_env(&[("HOME", "/home/user"), ("XDG_CONFIG_HOME", "/xdg/config")]);
+        assert_eq!(xdg_dir(env, "XDG_CONFIG_HOME", ".config").unwrap(), PathBuf::from("/xdg/config/ziron"));
+    }
+
+    #[test]
+    fn test_xdg_fallbacks() {
+        let env = fake_env(&[("HOME", "/home/user"), ("XDG_CONFIG_HOME", "relative/config")]);
+        assert_eq!(xdg_dir(&env, "XDG_CONFIG_HOME", ".config").unwrap(), PathBuf::from("/home/user/.config/ziron"));
+        assert_eq!(xdg_dir(&env, "XDG_STATE_HOME", ".local/state").unwrap(), PathBuf::from("/home/user/.local/state/ziron"));
+        assert!(xdg_dir(fake_env(&[]), "XDG_CONFIG_HOME", ".config").is_err());
+    }
+}
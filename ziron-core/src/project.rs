@@ -0,0 +1,222 @@
+//! Per-directory prompt overrides from a project's `.ziron.toml`
+
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::theme::Theme;
+
+/// Name of the project override file
+pub const PROJECT_FILE: &str = ".ziron.toml";
+
+/// How long a directory's discovery result is reused before looking again
+const DISCOVERY_TTL: Duration = Duration::from_secs(2);
+
+/// Most directories whose discovery result is remembered
+const MAX_DISCOVERY_ENTRIES: usize = 256;
+
+/// Overrides a `.ziron.toml` applies to prompts rendered in its directory tree
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Theme name, or a path to a theme file ending in `.toml` (relative to the project file)
+    pub theme: Option<String>,
+    /// Replaces the configured module list
+    pub modules: Option<Vec<String>>,
+    /// Module settings, merged key by key over `[module_config.<name>]`
+    pub module_config: HashMap<String, toml::Table>,
+}
+
+/// A discovered project file with its theme already loaded
+#[derive(Debug, Clone)]
+pub struct ProjectOverrides {
+    /// The `.ziron.toml` the overrides came from
+    pub path: PathBuf,
+    pub config: ProjectConfig,
+    pub theme: Option<Theme>,
+}
+
+impl ProjectConfig {
+    /// Find the nearest `.ziron.toml` in `dir` or its ancestors
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|ancestor| ancestor.join(PROJECT_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Load a project file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| Error::Config(format!("Failed to parse {}: {}", path.display(), e)))
+    }
+
+    /// The base config with these overrides applied
+    pub fn apply(&self, base: &Config) -> Config {
+        let mut config = base.clone();
+        if let Some(theme) = &self.theme {
+            config.theme = Some(theme.clone());
+        }
+        if let Some(modules) = &self.modules {
+            config.modules = modules.clone();
+        }
+        for (module, settings) in &self.module_config {
+            let table = config.module_config.entry(module.clone()).or_default();
+            for (key, value) in settings {
+                table.insert(key.clone(), value.clone());
+            }
+        }
+        config
+    }
+
+    /// Load the overriding theme, if any
+    fn load_theme(&self, project_dir: &Path) -> Result<Option<Theme>> {
+        let Some(theme) = &self.theme else {
+            return Ok(None);
+        };
+        // A project file comes with the repository, so it may only pick a theme by name or from inside the project
+        let path = if theme.ends_with(".toml") {
+            let root = project_dir.canonicalize()?;
+            let path = project_dir.join(theme).canonicalize()?;
+            if !path.starts_with(&root) {
+                return Err(Error::Config(format!("Project theme {} is outside {}", theme, root.display())));
+            }
+            path
+        } else if is_theme_name(theme) {
+            Theme::path_for(theme)
+        } else {
+            return Err(Error::Config(format!("Invalid project theme name '{}'", theme)));
+        };
+        Theme::load_from(&path).map(Some)
+    }
+}
+
+impl ProjectOverrides {
+    /// Discover and load the overrides for `dir`, without caching
+    pub fn discover_uncached(dir: &Path) -> Result<Option<Self>> {
+        let Some(path) = ProjectConfig::find(dir) else {
+            return Ok(None);
+        };
+        let config = ProjectConfig::load_from(&path)?;
+        let project_dir = path.parent().unwrap_or(Path::new("."));
+        let theme = config.load_theme(project_dir)?;
+        Ok(Some(Self { path, config, theme }))
+    }
+
+    /// Discover the overrides for `dir`, reusing recent lookups
+    ///
+    /// An unreadable or invalid project file is logged and ignored.
+    pub fn discover(dir: &Path) -> Option<Arc<Self>> {
+        type DiscoveryCache = Mutex<HashMap<PathBuf, (Instant, Option<Arc<ProjectOverrides>>)>>;
+        static CACHE: OnceLock<DiscoveryCache> = OnceLock::new();
+
+        let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        if let Some((checked, overrides)) = cache.lock().ok()?.get(dir) {
+            if checked.elapsed() < DISCOVERY_TTL {
+                return overrides.clone();
+            }
+        }
+
+        let overrides = match Self::discover_uncached(dir) {
+            Ok(overrides) => overrides.map(Arc::new),
+            Err(e) => {
+                tracing::warn!("Ignoring project overrides for {}: {}", dir.display(), e);
+                None
+            }
+        };
+        if let Ok(mut cache) = cache.lock() {
+            if cache.len() >= MAX_DISCOVERY_ENTRIES {
+                cache.retain(|_, (checked, _)| checked.elapsed() < DISCOVERY_TTL);
+            }
+            if cache.len() >= MAX_DISCOVERY_ENTRIES {
+                let oldest = cache.iter().min_by_key(|(_, (checked, _))| *checked).map(|(dir, _)| dir.clone());
+                if let Some(oldest) = oldest {
+                    cache.remove(&oldest);
+                }
+            }
+            cache.insert(dir.to_path_buf(), (Instant::now(), overrides.clone()));
+        }
+        overrides
+    }
+
+    /// The config and theme to render with after applying these overrides
+    pub fn apply<'a>(&self, config: &'a Config, theme: &'a Theme) -> (Cow<'a, Config>, Cow<'a, Theme>) {
+        let theme = match &self.theme {
+            Some(theme) => Cow::Owned(theme.clone()),
+            None => Cow::Borrowed(theme),
+        };
+        (Cow::Owned(self.config.apply(config)), theme)
+    }
+}
+
+/// Whether `theme` is a bare theme name, with no path separators or `..`
+fn is_theme_name(theme: &str) -> bool {
+    let mut components = Path::new(theme).components();
+    matches!((components.next(), components.next()), (Some(Component::Normal(_)), None))
+}
+
+/// The config and theme for prompts in `dir`, with any project overrides applied
+pub fn resolve_for_dir<'a>(config: &'a Config, theme: &'a Theme, dir: &Path) -> (Cow<'a, Config>, Cow<'a, Theme>) {
+    match ProjectOverrides::discover(dir) {
+        Some(overrides) => overrides.apply(config, theme),
+        None => (Cow::Borrowed(config), Cow::Borrowed(theme)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_file_overrides_theme() {
+        let project = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            project.path().join("project-theme.toml"),
+            "[theme]\nname = \"project\"\n\n[[segments]]\nmodule = \"git\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            project.path().join(PROJECT_FILE),
+            "theme = \"project-theme.toml\"\nmodules = [\"git\"]\n\n[module_config.git]\nwhen_path = [\"/src\"]\n",
+        )
+        .unwrap();
+        let nested = project.path().join("src").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let base_theme: Theme = toml::from_str("[theme]\nname = \"base\"").unwrap();
+        let base_config = Config::default();
+
+        let (config, theme) = resolve_for_dir(&base_config, &base_theme, &nested);
+        assert_eq!(theme.config.name, "project");
+        assert_eq!(config.modules, vec!["git"]);
+        assert_eq!(config.theme.as_deref(), Some("project-theme.toml"));
+        assert!(config.module_config["git"].contains_key("when_path"));
+
+        // Directories outside the project keep the base theme
+        let outside = tempfile::TempDir::new().unwrap();
+        let (_, theme) = resolve_for_dir(&base_config, &base_theme, outside.path());
+        assert_eq!(theme.config.name, "base");
+    }
+
+    #[test]
+    fn test_project_theme_stays_inside_project() {
+        let parent = tempfile::TempDir::new().unwrap();
+        let project = parent.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(parent.path().join("outside.toml"), "[theme]\nname = \"outside\"\n").unwrap();
+
+        let load = |theme: &str| ProjectConfig { theme: Some(theme.to_string()), ..Default::default() }.load_theme(&project);
+        assert!(load("../outside.toml").unwrap_err().to_string().contains("outside"));
+        assert!(load("../../etc").unwrap_err().to_string().contains("Invalid project theme name"));
+        assert!(load("nested/name").is_err());
+
+        std::fs::write(project.join("inside.toml"), "[theme]\nname = \"inside\"\n").unwrap();
+        assert_eq!(load("inside.toml").unwrap().unwrap().config.name, "inside");
+    }
+}
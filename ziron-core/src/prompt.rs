@@ -1,8 +1,24 @@
 //! Prompt rendering pipeline
 
+use crate::config::Config;
 use crate::error::Result;
-use crate::module::{ModuleContext, ModuleData};
-use crate::theme::Theme;
+use crate::module::{normalize_module_name, ColorSupport, ModuleContext, ModuleData, ModuleRegistry};
+use crate::theme::{Align, RuleMatch, Theme};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthChar;
+
+/// A rendered prompt segment with its styling, for renderers that don't use ANSI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StyledSegment {
+    pub module: String,
+    pub text: String,
+    /// Foreground color, with palette names resolved
+    pub fg: Option<String>,
+    /// Background color, with palette names resolved
+    pub bg: Option<String>,
+    pub styles: Vec<String>,
+}
 
 /// Prompt renderer
 #[derive(Clone)]
@@ -22,22 +38,31 @@ impl PromptRenderer {
     }
 
     /// Render a prompt from module data
-    pub fn render(&self, _context: &ModuleContext, modules: &[ModuleData]) -> Result<String> {
+    pub fn render(&self, context: &ModuleContext, modules: &[ModuleData]) -> Result<String> {
         let mut output = String::new();
+        let colors = context.color_support;
+        let ascii = context.ascii_icons;
 
         // Set background color if specified
         if let Some(bg_color) = &self.theme.config.background {
-            output.push_str(&self.hex_to_bg_ansi(bg_color));
+            output.push_str(&self.hex_to_bg_ansi(bg_color, colors));
         }
 
         let mut segments = Vec::new();
+        let mut fills = Vec::new();
+        let left_segments = self.gradient_segments(&self.theme.segments, modules, ascii)?;
 
         // Render main prompt segments
-        for segment_config in &self.theme.segments {
+        for segment_config in left_segments.iter() {
+            if segment_config.is_fill() {
+                segments.push((self.render_fill(segment_config, colors), segment_config));
+                fills.push(segment_config.fill_char());
+                continue;
+            }
             // Find module data for this segment
-            if let Some(module_data) = modules.iter().find(|m| m.module == segment_config.module) {
-                let segment = self.render_segment(segment_config, module_data)?;
-                segments.push(segment);
+            if let Some(module_data) = modules.iter().find(|m| m.module == normalize_module_name(&segment_config.module)) {
+                let segment = self.render_segment(segment_config, module_data, colors, ascii)?;
+                segments.push((segment, segment_config));
             } else {
                 // If module not found, skip silently (for optional modules like git)
                 // This allows themes to include optional modules
@@ -47,28 +72,36 @@ impl PromptRenderer {
         // Handle multi-line prompts
         if self.theme.config.multiline.unwrap_or(false) {
             // For multi-line, join segments with newlines
-            output.push_str(&segments.join("\n"));
+            let lines: Vec<&str> = segments.iter().map(|(text, _)| text.as_str()).collect();
+            output.push_str(&lines.join("\n"));
         } else {
             // Single line
-            output.push_str(&segments.join(""));
+            output.push_str(&self.join_segments(&segments));
         }
         
         // Render right-side prompt if specified
-        if let Some(right_segments) = &self.theme.config.right_segments {
-            let right_prompt = self.render_right_prompt(right_segments, modules)?;
-            if !right_prompt.is_empty() {
-                // Calculate terminal width (default to 80 if not available)
-                let terminal_width: usize = 80; // TODO: Get actual terminal width
-                let left_prompt_len: usize = output.len(); // Approximate
-                let padding = terminal_width.saturating_sub(left_prompt_len + right_prompt.len());
-                output.push_str(&" ".repeat(padding));
-                output.push_str(&right_prompt);
-            }
+        let terminal_width = context.terminal_width.unwrap_or(80);
+        let right_prompt = match &self.theme.config.right_segments {
+            Some(right_segments) => self.render_right_prompt(right_segments, modules, colors, ascii)?,
+            None => String::new(),
+        };
+        let right_width = visible_width(&right_prompt);
+
+        // Stretch fill segments so the line (with the right prompt) reaches the terminal edge
+        if !fills.is_empty() {
+            output = expand_fills(&output, &fills, terminal_width, right_width);
+        }
+
+        if !right_prompt.is_empty() {
+            let left_width = visible_width(output.rsplit('\n').next().unwrap_or(""));
+            let padding = terminal_width.saturating_sub(left_width + right_width);
+            output.push_str(&" ".repeat(padding));
+            output.push_str(&right_prompt);
         }
         
         // Reset background right after the last "#" so it includes the "#" but not the trailing space
         // We need to find the actual "#" character in the visible text (ignoring ANSI codes)
-        if self.theme.config.background.is_some() {
+        if self.theme.config.background.is_some() && colors != ColorSupport::None {
             // Parse the string to find the last "#" while skipping ANSI escape sequences
             let mut last_hash_byte_pos: Option<usize> = None;
             let mut i = 0;
@@ -113,67 +146,199 @@ impl PromptRenderer {
                 output.push_str("\x1b[0m");
             }
         }
-        
+
+        // Terminal title and cwd reports go first; they take up no columns
+        let mut osc = String::new();
+        if let Some(template) = &self.theme.config.terminal_title {
+            let home = std::env::var_os("HOME").filter(|home| !home.is_empty()).map(std::path::PathBuf::from);
+            osc.push_str(&terminal_title(template, context, home.as_deref()));
+        }
+        if self.theme.config.report_cwd.unwrap_or(false) {
+            osc.push_str(&cwd_report(context));
+        }
+        output.insert_str(0, &osc);
+
         Ok(output)
     }
 
+    /// Render the prompt as styled segments for external renderers, instead of ANSI
+    ///
+    /// Left segments come first, followed by the theme's right segments.
+    pub fn render_json(&self, context: &ModuleContext, modules: &[ModuleData]) -> Result<Vec<StyledSegment>> {
+        let ascii = context.ascii_icons;
+        let right_segments = self.theme.config.right_segments.as_deref().unwrap_or_default();
+        let left_segments = self.gradient_segments(&self.theme.segments, modules, ascii)?;
+        let mut segments = Vec::new();
+
+        for segment_config in left_segments.iter().chain(right_segments) {
+            if segment_config.is_fill() {
+                segments.push(StyledSegment {
+                    module: "fill".to_string(),
+                    text: segment_config.fill_char().to_string(),
+                    fg: segment_config.color.as_ref().map(|color| self.get_color(color)),
+                    bg: self.theme.config.background.as_ref().map(|color| self.get_color(color)),
+                    styles: segment_config.styles.clone(),
+                });
+                continue;
+            }
+            let Some(module_data) = modules.iter().find(|m| m.module == normalize_module_name(&segment_config.module)) else {
+                continue;
+            };
+            let Some(text) = self.segment_text(segment_config, module_data, ascii)? else {
+                continue;
+            };
+
+            let bg = module_data.data.get("bg_color")
+                .and_then(|v| v.as_str())
+                .or(self.theme.config.background.as_deref())
+                .map(|color| self.get_color(color));
+            segments.push(StyledSegment {
+                module: module_data.module.clone(),
+                text,
+                fg: segment_color(segment_config, module_data).map(|color| self.get_color(color)),
+                bg,
+                styles: segment_config.styles.clone(),
+            });
+        }
+
+        Ok(segments)
+    }
+
+    /// Render a fill segment as a marker that `expand_fills` later stretches
+    fn render_fill(&self, segment_config: &crate::theme::Segment, colors: ColorSupport) -> String {
+        match &segment_config.color {
+            Some(color) if colors != ColorSupport::None => {
+                format!("{}{}\x1b[39m", self.color_to_ansi(color, colors), FILL_MARKER)
+            }
+            _ => FILL_MARKER.to_string(),
+        }
+    }
+
+    /// The segments with the theme's gradient assigned to visible segments lacking a color
+    fn gradient_segments<'a>(
+        &self,
+        segments: &'a [crate::theme::Segment],
+        modules: &[ModuleData],
+        ascii: bool,
+    ) -> Result<Cow<'a, [crate::theme::Segment]>> {
+        let stops: Vec<(u8, u8, u8)> = match &self.theme.config.gradient {
+            Some(gradient) => gradient.iter().filter_map(|color| parse_hex(&self.get_color(color))).collect(),
+            None => Vec::new(),
+        };
+        if stops.is_empty() {
+            return Ok(Cow::Borrowed(segments));
+        }
+
+        let mut visible = Vec::new();
+        for (index, segment_config) in segments.iter().enumerate() {
+            let module_data = modules.iter().find(|m| m.module == normalize_module_name(&segment_config.module));
+            if let Some(module_data) = module_data {
+                if self.segment_text(segment_config, module_data, ascii)?.is_some() {
+                    visible.push((index, module_color(module_data).is_some()));
+                }
+            }
+        }
+
+        let mut segments = segments.to_vec();
+        for (position, &(index, module_colored)) in visible.iter().enumerate() {
+            if segments[index].color.is_none() && !module_colored {
+                let t = if visible.len() > 1 { position as f64 / (visible.len() - 1) as f64 } else { 0.0 };
+                let (r, g, b) = interpolate_stops(&stops, t);
+                segments[index].color = Some(format!("#{:02x}{:02x}{:02x}", r, g, b));
+            }
+        }
+        Ok(Cow::Owned(segments))
+    }
+
     /// Render right-side prompt
     fn render_right_prompt(
         &self,
         right_segments: &[crate::theme::Segment],
         modules: &[ModuleData],
+        colors: ColorSupport,
+        ascii: bool,
     ) -> Result<String> {
-        let mut output = String::new();
+        let mut segments = Vec::new();
 
         for segment_config in right_segments {
             // Find module data for this segment
-            if let Some(module_data) = modules.iter().find(|m| m.module == segment_config.module) {
-                let segment = self.render_segment(segment_config, module_data)?;
-                output.push_str(&segment);
+            if let Some(module_data) = modules.iter().find(|m| m.module == normalize_module_name(&segment_config.module)) {
+                let segment = self.render_segment(segment_config, module_data, colors, ascii)?;
+                segments.push((segment, segment_config));
             }
         }
 
-        Ok(output)
+        Ok(self.join_segments(&segments))
+    }
+
+    /// Join rendered segments on one line, putting the theme's `segment_separator`
+    /// between visible segments that have no separator of their own
+    fn join_segments(&self, segments: &[(String, &crate::theme::Segment)]) -> String {
+        let separator = self.theme.config.segment_separator();
+        let mut output = String::new();
+        let mut pending = false;
+
+        for (text, segment) in segments {
+            if text.is_empty() {
+                continue;
+            }
+            let fill = segment.is_fill();
+            if pending && !fill {
+                output.push_str(separator.unwrap_or_default());
+            }
+            output.push_str(text);
+            pending = !fill && segment.separator.is_none();
+        }
+
+        output
     }
 
     fn render_segment(
         &self,
         segment_config: &crate::theme::Segment,
         module_data: &ModuleData,
+        colors: ColorSupport,
+        ascii: bool,
     ) -> Result<String> {
         let mut output = String::new();
 
-        // Check rules for conditional display
-        if !self.should_display_segment(segment_config, module_data)? {
+        let Some(text) = self.segment_text(segment_config, module_data, ascii)? else {
             return Ok(String::new());
+        };
+
+        // Apply a module-supplied background (e.g. red on failure) over the theme background
+        let segment_bg = module_data.data.get("bg_color")
+            .and_then(|v| v.as_str())
+            .map(|color| self.color_to_bg_ansi(color, colors))
+            .filter(|ansi| !ansi.is_empty());
+        if let Some(bg_ansi) = &segment_bg {
+            output.push_str(bg_ansi);
         }
 
-        // Apply color if specified
-        if let Some(color) = &segment_config.color {
-            output.push_str(&self.color_to_ansi(color));
+        // Apply the segment's color, or the one the module suggests
+        let color = segment_color(segment_config, module_data);
+        if let Some(color) = color {
+            output.push_str(&self.color_to_ansi(color, colors));
         }
 
-        // Render module data
-        let text = module_data.data.get("text").and_then(|v| v.as_str()).unwrap_or("");
-        
-        // Skip rendering if text is empty (module has no data to display)
-        if text.is_empty() {
-            return Ok(String::new());
+        output.push_str(&text);
+
+        // Without a theme background there is nothing to restore below, so clear the segment's own
+        if segment_bg.is_some() && self.theme.config.background.is_none() {
+            output.push_str("\x1b[49m");
         }
-        
-        output.push_str(text);
 
-        // Reset foreground color (but keep background)
-        if segment_config.color.is_some() {
+        // Reset foreground color (but keep background); nothing was set without color support
+        if color.is_some() && colors != ColorSupport::None {
             output.push_str("\x1b[39m"); // Reset foreground color only
             // Restore background if set
             if let Some(bg_color) = &self.theme.config.background {
-                output.push_str(&self.hex_to_bg_ansi(bg_color));
+                output.push_str(&self.hex_to_bg_ansi(bg_color, colors));
             }
         } else {
             // Even if no color, restore background if set
             if let Some(bg_color) = &self.theme.config.background {
-                output.push_str(&self.hex_to_bg_ansi(bg_color));
+                output.push_str(&self.hex_to_bg_ansi(bg_color, colors));
             }
         }
 
@@ -181,12 +346,12 @@ impl PromptRenderer {
         if let Some(separator) = &segment_config.separator {
             // Ensure background is still active for the separator
             if let Some(bg_color) = &self.theme.config.background {
-                output.push_str(&self.hex_to_bg_ansi(bg_color));
+                output.push_str(&self.hex_to_bg_ansi(bg_color, colors));
             }
             
             // If this is the last segment and separator contains "#", we need special handling
             // to reset background after "#" but before any trailing space
-            if separator.contains('#') && separator.ends_with(' ') {
+            if separator.contains('#') && separator.ends_with(' ') && colors != ColorSupport::None {
                 // Find the position of "#" in the separator
                 if let Some(hash_pos) = separator.rfind('#') {
                     // Add everything up to and including "#"
@@ -208,6 +373,60 @@ impl PromptRenderer {
         Ok(output)
     }
 
+    /// The text a segment displays, or `None` when its rules hide it or it has no data
+    fn segment_text(
+        &self,
+        segment_config: &crate::theme::Segment,
+        module_data: &ModuleData,
+        ascii: bool,
+    ) -> Result<Option<String>> {
+        // Check rules for conditional display
+        if !self.should_display_segment(segment_config, module_data)? {
+            return Ok(None);
+        }
+
+        // Render module data
+        let text = match &segment_config.format {
+            Some(template) => interpolate(template, &module_data.data),
+            None => module_data.data.get("text").and_then(display_value).unwrap_or_default().into_owned(),
+        };
+
+        // Skip rendering if text is empty (module has no data to display)
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let text = match crate::icons::resolve(segment_config, ascii) {
+            Some(icon) => format!("{} {}", icon, text),
+            None => text,
+        };
+        Ok(Some(Self::fit_width(segment_config, &text)))
+    }
+
+    /// Apply the segment's max/min width and alignment to its text
+    fn fit_width(segment_config: &crate::theme::Segment, text: &str) -> String {
+        let mut text = match segment_config.max_width {
+            Some(max_width) => truncate_visible(text, max_width),
+            None => text.to_string(),
+        };
+
+        if let Some(min_width) = segment_config.min_width {
+            let padding = min_width.saturating_sub(visible_width(&text));
+            if padding > 0 {
+                text = match segment_config.align {
+                    Align::Left => format!("{}{}", text, " ".repeat(padding)),
+                    Align::Right => format!("{}{}", " ".repeat(padding), text),
+                    Align::Center => {
+                        let left = padding / 2;
+                        format!("{}{}{}", " ".repeat(left), text, " ".repeat(padding - left))
+                    }
+                };
+            }
+        }
+
+        text
+    }
+
     /// Get color from palette or use directly
     fn get_color(&self, color: &str) -> String {
         // Check if color is in custom palette
@@ -219,33 +438,16 @@ impl PromptRenderer {
         color.to_string()
     }
 
-    fn color_to_ansi(&self, color: &str) -> String {
-        let color = self.get_color(color);
-        // Check if it's a hex color (true color)
-        if color.starts_with('#') && color.len() == 7 {
-            let hex = &color[1..];
-            if let (Ok(r), Ok(g), Ok(b)) = (
-                u8::from_str_radix(&hex[0..2], 16),
-                u8::from_str_radix(&hex[2..4], 16),
-                u8::from_str_radix(&hex[4..6], 16),
-            ) {
-                return format!("\x1b[38;2;{};{};{}m", r, g, b);
-            }
+    /// Convert a palette name, color name, hex code or `rgb()` to an ANSI foreground escape sequence
+    ///
+    /// True colors are downgraded to the nearest color the terminal supports.
+    fn color_to_ansi(&self, color: &str, colors: ColorSupport) -> String {
+        if colors == ColorSupport::None {
+            return String::new();
         }
-        
-        // Check if it's RGB format: rgb(r,g,b)
-        if color.starts_with("rgb(") && color.ends_with(')') {
-            let rgb = &color[4..color.len()-1];
-            let parts: Vec<&str> = rgb.split(',').map(|s| s.trim()).collect();
-            if parts.len() == 3 {
-                if let (Ok(r), Ok(g), Ok(b)) = (
-                    parts[0].parse::<u8>(),
-                    parts[1].parse::<u8>(),
-                    parts[2].parse::<u8>(),
-                ) {
-                    return format!("\x1b[38;2;{};{};{}m", r, g, b);
-                }
-            }
+        let color = self.get_color(color);
+        if let Some(rgb) = parse_color(&color) {
+            return rgb_to_ansi(rgb, colors, false);
         }
         
         // Standard color names
@@ -263,24 +465,41 @@ impl PromptRenderer {
         format!("\x1b[{}m", code)
     }
 
+    /// Convert a palette name, color name or hex code to an ANSI background escape sequence
+    fn color_to_bg_ansi(&self, color: &str, colors: ColorSupport) -> String {
+        let color = self.get_color(color);
+        if color.starts_with('#') || color.starts_with("rgb(") {
+            return match parse_color(&color) {
+                Some(rgb) => rgb_to_ansi(rgb, colors, true),
+                None => String::new(),
+            };
+        }
+        if colors == ColorSupport::None {
+            return String::new();
+        }
+
+        let code = match color.to_lowercase().as_str() {
+            "black" => "40",
+            "red" => "41",
+            "green" => "42",
+            "yellow" => "43",
+            "blue" => "44",
+            "magenta" => "45",
+            "cyan" => "46",
+            "white" => "47",
+            _ => return String::new(),
+        };
+        format!("\x1b[{}m", code)
+    }
+
     /// Convert hex color code to ANSI background color escape sequence
-    fn hex_to_bg_ansi(&self, hex: &str) -> String {
-        // Remove # if present
-        let hex = hex.trim_start_matches('#');
-        
-        if hex.len() == 6 {
-            // Parse RGB values
-            if let (Ok(r), Ok(g), Ok(b)) = (
-                u8::from_str_radix(&hex[0..2], 16),
-                u8::from_str_radix(&hex[2..4], 16),
-                u8::from_str_radix(&hex[4..6], 16),
-            ) {
-                return format!("\x1b[48;2;{};{};{}m", r, g, b);
-            }
+    fn hex_to_bg_ansi(&self, hex: &str, colors: ColorSupport) -> String {
+        // The leading # is optional here
+        match parse_hex(&format!("#{}", hex.trim_start_matches('#'))) {
+            Some(rgb) => rgb_to_ansi(rgb, colors, true),
+            // Fallback: no background color
+            None => String::new(),
         }
-        
-        // Fallback: no background color
-        String::new()
     }
 
     /// Check if a segment should be displayed based on rules
@@ -289,41 +508,1055 @@ impl PromptRenderer {
         segment_config: &crate::theme::Segment,
         module_data: &ModuleData,
     ) -> Result<bool> {
-        if segment_config.rules.is_empty() {
+        // Unknown or malformed rules are skipped, so they can't decide the outcome either way
+        let results: Vec<bool> = segment_config
+            .rules
+            .iter()
+            .filter_map(|rule| Self::rule_matches(rule, module_data))
+            .collect();
+        if results.is_empty() {
             return Ok(true);
         }
 
-        // Evaluate rules (simplified implementation)
-        for rule in &segment_config.rules {
-            // Simple rule evaluation - check if condition matches module data
-            match rule.condition.as_str() {
-                "if_exists" => {
-                    if let Some(value) = rule.value.as_str() {
-                        if !module_data.data.get(value).is_some() {
-                            return Ok(false);
-                        }
-                    }
-                }
-                "if_not_empty" => {
-                    if let Some(value) = rule.value.as_str() {
-                        if let Some(data_value) = module_data.data.get(value) {
-                            if let Some(str_val) = data_value.as_str() {
-                                if str_val.is_empty() {
-                                    return Ok(false);
-                                }
-                            }
-                        } else {
-                            return Ok(false);
-                        }
-                    }
-                }
-                _ => {
-                    // Unknown rule, default to showing
+        Ok(match segment_config.rule_match {
+            RuleMatch::All => results.iter().all(|&matched| matched),
+            RuleMatch::Any => results.iter().any(|&matched| matched),
+        })
+    }
+
+    /// Evaluate a single display rule against module data; `None` if the rule is unknown or malformed
+    fn rule_matches(rule: &crate::theme::Rule, module_data: &ModuleData) -> Option<bool> {
+        let value = rule.value.as_str();
+        let matched = match rule.condition.as_str() {
+            "if_exists" => lookup_field(&module_data.data, value?).is_some(),
+            "if_not_empty" => lookup_field(&module_data.data, value?).is_some_and(|value| !is_empty_value(value)),
+            "if_equals" | "if_not_equals" => {
+                let (field, expected) = value?.split_once('=')?;
+                let equals = lookup_field(&module_data.data, field.trim())
+                    .is_some_and(|data_value| value_equals(data_value, expected));
+                equals == (rule.condition == "if_equals")
+            }
+            condition => {
+                tracing::debug!("Ignoring unknown segment rule condition '{}'", condition);
+                return None;
+            }
+        };
+        Some(matched)
+    }
+}
+
+
+/// Placeholder for a fill segment until the line's width is known
+const FILL_MARKER: char = '\u{0}';
+
+/// Replace fill markers with fill characters so each line spans `width` columns
+///
+/// The last line also leaves room for a right prompt of `reserved` columns. Several
+/// fills on one line share the free space, the first taking any remainder.
+fn expand_fills(text: &str, fills: &[&str], width: usize, reserved: usize) -> String {
+    let mut fills = fills.iter();
+    let line_count = text.split('\n').count();
+    let lines: Vec<String> = text
+        .split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let markers = line.matches(FILL_MARKER).count();
+            if markers == 0 {
+                return line.to_string();
+            }
+            let reserved = if index + 1 == line_count { reserved } else { 0 };
+            let free = width.saturating_sub(visible_width(&line.replace(FILL_MARKER, "")) + reserved);
+            let (share, mut remainder) = (free / markers, free % markers);
+
+            let mut expanded = String::new();
+            for (position, part) in line.split(FILL_MARKER).enumerate() {
+                if position > 0 {
+                    let fill = fills.next().copied().unwrap_or(" ");
+                    let columns = share + std::mem::take(&mut remainder);
+                    expanded.push_str(&fill.repeat(columns / visible_width(fill).max(1)));
                 }
+                expanded.push_str(part);
             }
+            expanded
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// The color a module suggests through the `color` key of its data
+fn module_color(module_data: &ModuleData) -> Option<&str> {
+    module_data.data.get("color").and_then(|v| v.as_str()).filter(|color| !color.is_empty())
+}
+
+/// The segment's own color, falling back to the one its module suggests
+fn segment_color<'a>(segment_config: &'a crate::theme::Segment, module_data: &'a ModuleData) -> Option<&'a str> {
+    segment_config.color.as_deref().or_else(|| module_color(module_data))
+}
+
+/// Parse a `#rrggbb` color
+fn parse_hex(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    Some((
+        u8::from_str_radix(&hex[0..2], 16).ok()?,
+        u8::from_str_radix(&hex[2..4], 16).ok()?,
+        u8::from_str_radix(&hex[4..6], 16).ok()?,
+    ))
+}
+
+/// Parse a `#rrggbb` or `rgb(r, g, b)` color
+fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    if let Some(rgb) = color.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+        let parts: Vec<&str> = rgb.split(',').map(|s| s.trim()).collect();
+        if parts.len() != 3 {
+            return None;
         }
+        return Some((parts[0].parse().ok()?, parts[1].parse().ok()?, parts[2].parse().ok()?));
+    }
+    parse_hex(color)
+}
+
+/// Channel levels of the xterm 256-color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
 
-        Ok(true)
+/// RGB values of the 16 standard ANSI colors (xterm defaults)
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Squared distance between two RGB colors
+fn color_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Nearest xterm 256-color index, from the color cube or the grayscale ramp
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_value = 8 + gray_index * 10;
+    let gray = (gray_value, gray_value, gray_value);
+
+    if color_distance(rgb, gray) < color_distance(rgb, cube) {
+        232 + gray_index
+    } else {
+        16 + (36 * r + 6 * g + b) as u8
     }
 }
 
+/// Nearest of the 16 standard ANSI colors, as an index from 0 to 15
+fn nearest_16(rgb: (u8, u8, u8)) -> u8 {
+    (0..ANSI_16.len())
+        .min_by_key(|&i| color_distance(rgb, ANSI_16[i]))
+        .unwrap_or(0) as u8
+}
+
+/// Escape sequence for an RGB color at the terminal's color support level
+fn rgb_to_ansi((r, g, b): (u8, u8, u8), colors: ColorSupport, background: bool) -> String {
+    let layer = if background { 48 } else { 38 };
+    match colors {
+        ColorSupport::TrueColor => format!("\x1b[{};2;{};{};{}m", layer, r, g, b),
+        ColorSupport::Ansi256 => format!("\x1b[{};5;{}m", layer, nearest_256((r, g, b))),
+        ColorSupport::Ansi16 => {
+            let index = nearest_16((r, g, b));
+            let base = if index < 8 { 30 + index } else { 90 + index - 8 };
+            format!("\x1b[{}m", base + if background { 10 } else { 0 })
+        }
+        ColorSupport::None => String::new(),
+    }
+}
+
+/// Linearly interpolate RGB color stops at `t` (0.0 to 1.0)
+fn interpolate_stops(stops: &[(u8, u8, u8)], t: f64) -> (u8, u8, u8) {
+    if stops.len() == 1 {
+        return stops[0];
+    }
+    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+    let index = (scaled.floor() as usize).min(stops.len() - 2);
+    let local = scaled - index as f64;
+    let (from, to) = (stops[index], stops[index + 1]);
+    let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * local).round() as u8;
+    (channel(from.0, to.0), channel(from.1, to.1), channel(from.2, to.2))
+}
+
+/// Look up a field by dot path (`info.branch`), walking nested objects
+fn lookup_field<'a>(data: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(data, |value, key| value.get(key))
+}
+
+/// Display form of a module value: strings as-is, numbers in decimal and bools as `true`/`false`
+///
+/// Nulls have no display form; arrays and objects use their JSON form.
+fn display_value(value: &serde_json::Value) -> Option<Cow<'_, str>> {
+    match value {
+        serde_json::Value::String(s) => Some(Cow::Borrowed(s)),
+        serde_json::Value::Null => None,
+        value => Some(Cow::Owned(value.to_string())),
+    }
+}
+
+/// Whether a rule treats a value as empty (nulls, empty strings, arrays and objects)
+fn is_empty_value(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => true,
+        serde_json::Value::String(s) => s.is_empty(),
+        serde_json::Value::Array(items) => items.is_empty(),
+        serde_json::Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+/// Compare a module value with a rule's expected text, according to the value's type
+///
+/// Bools match `true`/`false`, numbers match numerically (`3` equals `3.0`) and
+/// nulls match `null` or nothing.
+fn value_equals(value: &serde_json::Value, expected: &str) -> bool {
+    let expected_trimmed = expected.trim();
+    match value {
+        serde_json::Value::String(s) => s == expected,
+        serde_json::Value::Bool(b) => expected_trimmed.parse::<bool>().ok() == Some(*b),
+        serde_json::Value::Number(n) => match (n.as_f64(), expected_trimmed.parse::<f64>()) {
+            (Some(actual), Ok(expected)) => actual == expected,
+            _ => false,
+        },
+        serde_json::Value::Null => expected_trimmed.is_empty() || expected_trimmed == "null",
+        value => serde_json::from_str::<serde_json::Value>(expected).ok().as_ref() == Some(value),
+    }
+}
+
+/// Replace `{field}` placeholders in a format template with module data
+///
+/// Missing fields and nulls render as empty; other values use their display form.
+fn interpolate(template: &str, data: &serde_json::Value) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            output.push_str(&rest[start..]);
+            return output;
+        };
+        let path = rest[start + 1..start + end].trim();
+        if let Some(value) = lookup_field(data, path).and_then(display_value) {
+            output.push_str(&value);
+        }
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Render a prompt in-process, without the daemon, using the built-in modules
+pub fn render_prompt(config: &Config, theme: &Theme, context: &ModuleContext) -> Result<String> {
+    render_prompt_with(&ModuleRegistry::with_builtins(), config, theme, context)
+}
+
+/// Render a prompt in-process, fetching module data through `registry`
+///
+/// A `.ziron.toml` in or above the context's directory overrides the config and theme.
+pub fn render_prompt_with(
+    registry: &ModuleRegistry,
+    config: &Config,
+    theme: &Theme,
+    context: &ModuleContext,
+) -> Result<String> {
+    let (config, theme) = crate::project::resolve_for_dir(config, theme, &context.current_dir);
+    let (config, theme) = (config.as_ref(), theme.as_ref());
+
+    let mut module_data = Vec::new();
+    for module_name in prompt_modules(config, theme) {
+        match registry.fetch(&module_name, context, config) {
+            Ok(Some(data)) => module_data.push(data),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Error fetching data for module {}: {}", module_name, e),
+        }
+    }
+
+    PromptRenderer::new(theme.clone()).render(context, &module_data)
+}
+
+/// Modules a prompt needs: those used by the theme's segments, or the configured
+/// modules if the theme has no segments
+pub fn prompt_modules(config: &Config, theme: &Theme) -> Vec<String> {
+    let names: Vec<&String> = if theme.segments.is_empty() {
+        config.modules.iter().collect()
+    } else {
+        let right_segments = theme.config.right_segments.iter().flatten();
+        theme.segments.iter().chain(right_segments).filter(|s| !s.is_fill()).map(|s| &s.module).collect()
+    };
+
+    let mut modules: Vec<String> = Vec::new();
+    for name in names {
+        let name = normalize_module_name(name);
+        if !name.is_empty() && !modules.contains(&name) {
+            modules.push(name);
+        }
+    }
+    modules
+}
+
+/// Split a rendered prompt into its leading terminal title/cwd reports and the visible prompt
+///
+/// Line editors that measure the prompt (like rustyline) should print the reports
+/// themselves, since they count escape bytes as columns.
+pub fn split_terminal_reports(prompt: &str) -> (&str, &str) {
+    let mut end = 0;
+    while prompt[end..].starts_with("\x1b]") {
+        match escape_len(&prompt[end..]) {
+            Some(len) => end += len,
+            None => break,
+        }
+    }
+    prompt.split_at(end)
+}
+
+/// OSC 0 sequence setting the terminal title from a `{user}`/`{host}`/`{cwd}`/`{shell}` template
+fn terminal_title(template: &str, context: &ModuleContext, home: Option<&std::path::Path>) -> String {
+    // Whole path components only, so /home/alice isn't inside a home of /home/al
+    let cwd = match home.and_then(|home| context.current_dir.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => context.current_dir.display().to_string(),
+    };
+    let fields = serde_json::json!({
+        "user": context.user,
+        "host": context.hostname,
+        "cwd": cwd,
+        "shell": context.shell,
+    });
+    // Control characters (e.g. from a directory name) would end the sequence early
+    let title: String = interpolate(template, &fields).chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]0;{}\x07", title)
+}
+
+/// OSC 7 sequence reporting the working directory as a `file://host/path` URL
+fn cwd_report(context: &ModuleContext) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut path = String::new();
+    for &byte in context.current_dir.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            path.push(byte as char);
+        } else {
+            path.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    format!("\x1b]7;file://{}{}\x07", context.hostname, path)
+}
+
+/// Length of an escape sequence at the start of `s`: ANSI CSI (`\x1b[...` up to its final byte)
+/// or OSC (`\x1b]...` up to BEL or `\x1b\\`)
+fn escape_len(s: &str) -> Option<usize> {
+    if let Some(rest) = s.strip_prefix("\x1b]") {
+        return rest
+            .char_indices()
+            .find_map(|(i, c)| match c {
+                '\x07' => Some(i + 3),
+                '\x1b' if rest[i..].starts_with("\x1b\\") => Some(i + 4),
+                _ => None,
+            });
+    }
+    let rest = s.strip_prefix("\x1b[")?;
+    rest.char_indices()
+        .find(|(_, c)| ('@'..='~').contains(c))
+        .map(|(i, _)| i + 3)
+}
+
+/// Width of text in terminal columns, ignoring ANSI escape sequences
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut i = 0;
+    while i < text.len() {
+        if let Some(len) = escape_len(&text[i..]) {
+            i += len;
+            continue;
+        }
+        let ch = text[i..].chars().next().unwrap_or_default();
+        width += ch.width().unwrap_or(0);
+        i += ch.len_utf8();
+    }
+    width
+}
+
+/// Truncate text to `max_width` visible columns, ending with an ellipsis.
+/// Escape sequences are kept so colors opened or reset inside the text still apply.
+fn truncate_visible(text: &str, max_width: usize) -> String {
+    if visible_width(text) <= max_width {
+        return text.to_string();
+    }
+
+    let budget = max_width.saturating_sub(1);
+    let mut output = String::new();
+    let mut width = 0;
+    let mut truncated = false;
+    let mut i = 0;
+    while i < text.len() {
+        if let Some(len) = escape_len(&text[i..]) {
+            output.push_str(&text[i..i + len]);
+            i += len;
+            continue;
+        }
+        let ch = text[i..].chars().next().unwrap_or_default();
+        let ch_width = ch.width().unwrap_or(0);
+        if !truncated {
+            if width + ch_width <= budget {
+                output.push(ch);
+                width += ch_width;
+            } else {
+                if max_width > 0 {
+                    output.push('…');
+                }
+                truncated = true;
+            }
+        }
+        i += ch.len_utf8();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn renderer(theme_toml: &str) -> PromptRenderer {
+        PromptRenderer::new(toml::from_str(theme_toml).unwrap())
+    }
+
+    fn context() -> ModuleContext {
+        ModuleContext::for_test("/")
+    }
+
+    fn module(name: &str, text: &str) -> ModuleData {
+        ModuleData {
+            module: name.to_string(),
+            data: json!({ "text": text }),
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_min_width_left_and_right_align() {
+        let left = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "git"
+            min_width = 6
+            "#,
+        );
+        let output = left.render(&context(), &[module("git", "\x1b[1mab\x1b[22m")]).unwrap();
+        assert_eq!(output, "\x1b[1mab\x1b[22m    ");
+
+        let right = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "git"
+            min_width = 6
+            align = "right"
+            "#,
+        );
+        let output = right.render(&context(), &[module("git", "\x1b[1mab\x1b[22m")]).unwrap();
+        assert_eq!(output, "    \x1b[1mab\x1b[22m");
+    }
+
+    #[test]
+    fn test_center_align_measures_wide_chars() {
+        let center = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "node"
+            min_width = 7
+            align = "center"
+            "#,
+        );
+        // "⚡" occupies two columns
+        let output = center.render(&context(), &[module("node", "⚡v2")]).unwrap();
+        assert_eq!(output, " ⚡v2  ");
+    }
+
+    #[test]
+    fn test_max_width_truncates_with_ellipsis() {
+        let truncating = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "git"
+            max_width = 5
+            "#,
+        );
+        let output = truncating
+            .render(&context(), &[module("git", "\x1b[31mfeature/long\x1b[39m")])
+            .unwrap();
+        assert_eq!(output, "\x1b[31mfeat…\x1b[39m");
+        assert_eq!(visible_width(&output), 5);
+
+        let output = truncating.render(&context(), &[module("git", "main")]).unwrap();
+        assert_eq!(output, "main");
+    }
+
+    #[test]
+    fn test_module_bg_color_overrides_theme_background() {
+        let themed = renderer(
+            r##"
+            [theme]
+            name = "test"
+            background = "#000000"
+            [[segments]]
+            module = "exitcode"
+            separator = "|"
+            [[segments]]
+            module = "time"
+            "##,
+        );
+        let failed = ModuleData {
+            module: "exitcode".to_string(),
+            data: json!({ "text": "1", "bg_color": "red" }),
+            cached: false,
+        };
+        let output = themed.render(&context(), &[failed, module("time", "12:00")]).unwrap();
+        let theme_bg = "\x1b[48;2;0;0;0m";
+        assert_eq!(
+            output,
+            format!("{bg}\x1b[41m1{bg}{bg}|12:00{bg}\x1b[0m", bg = theme_bg)
+        );
+
+        let plain = renderer(
+            r##"
+            [theme]
+            name = "test"
+            color_palette = { success = "#00ff00" }
+            [[segments]]
+            module = "exitcode"
+            "##,
+        );
+        let succeeded = ModuleData {
+            module: "exitcode".to_string(),
+            data: json!({ "text": "0", "bg_color": "success" }),
+            cached: false,
+        };
+        let output = plain.render(&context(), &[succeeded]).unwrap();
+        assert_eq!(output, "\x1b[48;2;0;255;0m0\x1b[49m");
+    }
+
+    #[test]
+    fn test_module_suggested_color() {
+        let dirty = ModuleData {
+            module: "git".to_string(),
+            data: json!({ "text": "main ✗", "color": "yellow" }),
+            cached: false,
+        };
+
+        let uncolored = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "git"
+            "#,
+        );
+        let output = uncolored.render(&context(), std::slice::from_ref(&dirty)).unwrap();
+        assert_eq!(output, "\x1b[33mmain ✗\x1b[39m");
+        let segments = uncolored.render_json(&context(), std::slice::from_ref(&dirty)).unwrap();
+        assert_eq!(segments[0].fg.as_deref(), Some("yellow"));
+
+        // An explicit segment color wins over the module's suggestion
+        let colored = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "git"
+            color = "blue"
+            "#,
+        );
+        let output = colored.render(&context(), std::slice::from_ref(&dirty)).unwrap();
+        assert_eq!(output, "\x1b[34mmain ✗\x1b[39m");
+        let segments = colored.render_json(&context(), &[dirty]).unwrap();
+        assert_eq!(segments[0].fg.as_deref(), Some("blue"));
+    }
+
+    #[test]
+    fn test_if_equals_rules() {
+        let renderer = renderer(
+            r#"
+            [theme]
+            name = "test"
+
+            [[segments]]
+            module = "kubernetes"
+            rules = [{ condition = "if_equals", value = "namespace=production" }]
+
+            [[segments]]
+            module = "git"
+            rules = [{ condition = "if_not_equals", value = "branch=main" }]
+            "#,
+        );
+        let segments = &renderer.theme().segments;
+        let data = |module: &str, field: &str, value: &str| ModuleData {
+            module: module.to_string(),
+            data: serde_json::json!({ "text": value, field: value }),
+            cached: false,
+        };
+
+        let k8s = &segments[0];
+        assert!(renderer.should_display_segment(k8s, &data("kubernetes", "namespace", "production")).unwrap());
+        assert!(!renderer.should_display_segment(k8s, &data("kubernetes", "namespace", "staging")).unwrap());
+        assert!(!renderer.should_display_segment(k8s, &data("kubernetes", "context", "production")).unwrap());
+
+        let git = &segments[1];
+        assert!(!renderer.should_display_segment(git, &data("git", "branch", "main")).unwrap());
+        assert!(renderer.should_display_segment(git, &data("git", "branch", "feature")).unwrap());
+    }
+
+    #[test]
+    fn test_non_string_values() {
+        let renderer = renderer(
+            r#"
+            [theme]
+            name = "test"
+
+            [[segments]]
+            module = "jobs"
+
+            [[segments]]
+            module = "git"
+            format = "{branch} {ahead}"
+            rules = [
+                { condition = "if_equals", value = "dirty=true" },
+                { condition = "if_equals", value = "ahead=2.0" },
+                { condition = "if_not_empty", value = "branch" },
+            ]
+            "#,
+        );
+        let jobs = ModuleData { module: "jobs".to_string(), data: json!({ "text": 3 }), cached: false };
+        let git = |dirty: bool, branch: serde_json::Value| ModuleData {
+            module: "git".to_string(),
+            data: json!({ "text": "", "branch": branch, "dirty": dirty, "ahead": 2 }),
+            cached: false,
+        };
+
+        let output = renderer.render(&context(), &[jobs.clone(), git(true, json!("main"))]).unwrap();
+        assert_eq!(output, "3main 2");
+        assert_eq!(renderer.render(&context(), &[jobs.clone(), git(false, json!("main"))]).unwrap(), "3");
+        assert_eq!(renderer.render(&context(), &[jobs, git(true, json!(null))]).unwrap(), "3");
+    }
+
+    #[test]
+    fn test_nested_field_paths() {
+        let renderer = renderer(
+            r#"
+            [theme]
+            name = "test"
+
+            [[segments]]
+            module = "git"
+            format = "{info.branch}@{info.remote.name}"
+            rules = [
+                { condition = "if_not_empty", value = "info.branch" },
+                { condition = "if_equals", value = "info.remote.name=origin" },
+            ]
+            "#,
+        );
+        let segment = &renderer.theme().segments[0];
+        let present = ModuleData {
+            module: "git".to_string(),
+            data: json!({ "text": "main", "info": { "branch": "main", "remote": { "name": "origin" } } }),
+            cached: false,
+        };
+        assert!(renderer.should_display_segment(segment, &present).unwrap());
+        assert_eq!(renderer.render_segment(segment, &present, ColorSupport::TrueColor, false).unwrap(), "main@origin");
+
+        let absent = module("git", "main");
+        assert!(!renderer.should_display_segment(segment, &absent).unwrap());
+        assert_eq!(interpolate("{info.branch}@{info.remote.name}", &absent.data), "@");
+        assert_eq!(lookup_field(&absent.data, "info.branch"), None);
+    }
+
+    #[test]
+    fn test_render_json_segments() {
+        let renderer = renderer(
+            r##"
+            [theme]
+            name = "test"
+            background = "#000000"
+            color_palette = { accent = "#ff8800" }
+
+            [[segments]]
+            module = "cwd"
+            color = "accent"
+            styles = ["bold"]
+
+            [[segments]]
+            module = "symbol"
+            color = "green"
+            "##,
+        );
+        let modules = [module("cwd", "~/src"), module("symbol", "❯")];
+
+        let segments = renderer.render_json(&context(), &modules).unwrap();
+        assert_eq!(
+            serde_json::to_value(&segments).unwrap(),
+            json!([
+                { "module": "cwd", "text": "~/src", "fg": "#ff8800", "bg": "#000000", "styles": ["bold"] },
+                { "module": "symbol", "text": "❯", "fg": "green", "bg": "#000000", "styles": [] },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_gradient_across_segments() {
+        let renderer = renderer(
+            r##"
+            [theme]
+            name = "test"
+            gradient = ["#ff0000", "#0000ff"]
+
+            [[segments]]
+            module = "symbol"
+
+            [[segments]]
+            module = "cwd"
+
+            [[segments]]
+            module = "missing"
+
+            [[segments]]
+            module = "git"
+            "##,
+        );
+        let modules = [module("symbol", ">"), module("cwd", "~"), module("git", "main")];
+
+        let segments = renderer.render_json(&context(), &modules).unwrap();
+        let colors: Vec<_> = segments.iter().map(|s| s.fg.as_deref()).collect();
+        assert_eq!(colors, vec![Some("#ff0000"), Some("#800080"), Some("#0000ff")]);
+
+        let output = renderer.render(&context(), &modules).unwrap();
+        assert!(output.starts_with("\x1b[38;2;255;0;0m>"), "{:?}", output);
+    }
+
+    #[test]
+    fn test_colors_downgrade_to_terminal_support() {
+        let renderer = renderer(
+            r##"
+            [theme]
+            name = "test"
+
+            [[segments]]
+            module = "cwd"
+            color = "#ff8700"
+            "##,
+        );
+        let modules = [module("cwd", "~")];
+        let render = |colors| {
+            let context = ModuleContext { color_support: colors, ..context() };
+            renderer.render(&context, &modules).unwrap()
+        };
+
+        assert_eq!(render(ColorSupport::TrueColor), "\x1b[38;2;255;135;0m~\x1b[39m");
+        assert_eq!(render(ColorSupport::Ansi256), "\x1b[38;5;208m~\x1b[39m");
+        assert_eq!(render(ColorSupport::Ansi16), "\x1b[33m~\x1b[39m");
+        assert_eq!(render(ColorSupport::None), "~");
+
+        assert_eq!(nearest_256((128, 128, 128)), 244);
+        assert_eq!(rgb_to_ansi((0, 0, 0), ColorSupport::Ansi16, true), "\x1b[40m");
+    }
+
+    #[test]
+    fn test_no_escapes_without_color_support() {
+        let renderer = renderer(
+            r##"
+            [theme]
+            name = "test"
+            background = "#303030"
+
+            [[segments]]
+            module = "cwd"
+            color = "#ff8700"
+            separator = " #"
+            "##,
+        );
+        let context = ModuleContext { color_support: ColorSupport::None, ..context() };
+        assert_eq!(renderer.render(&context, &[module("cwd", "~")]).unwrap(), "~ #");
+    }
+
+    #[test]
+    fn test_fill_segment_stretches_to_terminal_width() {
+        let renderer = renderer(
+            r#"
+            [theme]
+            name = "test"
+
+            [[segments]]
+            module = "cwd"
+
+            [[segments]]
+            module = "fill"
+            fill_char = "-"
+
+            [[segments]]
+            module = "symbol"
+            "#,
+        );
+        let modules = [module("cwd", "~/src"), module("symbol", "❯")];
+        let mut context = context();
+        context.terminal_width = Some(20);
+
+        let output = renderer.render(&context, &modules).unwrap();
+        assert_eq!(output, format!("~/src{}❯", "-".repeat(14)));
+        assert_eq!(visible_width(&output), 20);
+
+        // Two fills share the free space, the first taking the remainder
+        assert_eq!(expand_fills("a\u{0}b\u{0}c", &["-", "."], 10, 0), "a----b...c");
+        assert_eq!(expand_fills("ab\u{0}", &["="], 6, 2), "ab==");
+    }
+
+    #[test]
+    fn test_terminal_title_and_cwd_reports() {
+        let titled = renderer(
+            r#"
+            [theme]
+            name = "test"
+            terminal_title = "{user}@{host}: {cwd}"
+            report_cwd = true
+
+            [[segments]]
+            module = "symbol"
+            "#,
+        );
+        let mut context = context();
+        context.current_dir = std::path::PathBuf::from("/srv/my project");
+        context.terminal_width = Some(20);
+
+        let output = titled.render(&context, &[module("symbol", "$")]).unwrap();
+        assert_eq!(
+            output,
+            "\x1b]0;user@host: /srv/my project\x07\x1b]7;file://host/srv/my%20project\x07$"
+        );
+        assert_eq!(visible_width(&output), 1);
+        assert_eq!(visible_width("\x1b]0;title\x1b\\ab"), 2);
+
+        let (reports, prompt) = split_terminal_reports(&output);
+        assert_eq!(reports, "\x1b]0;user@host: /srv/my project\x07\x1b]7;file://host/srv/my%20project\x07");
+        assert_eq!(prompt, "$");
+
+        // Plain themes emit neither sequence
+        let plain = renderer("[theme]\nname = \"test\"\n\n[[segments]]\nmodule = \"symbol\"\n");
+        assert_eq!(plain.render(&context, &[module("symbol", "$")]).unwrap(), "$");
+        assert_eq!(split_terminal_reports("\x1b[31m$"), ("", "\x1b[31m$"));
+    }
+
+    #[test]
+    fn test_terminal_title_shortens_home() {
+        let title = |cwd: &str| {
+            let context = ModuleContext { current_dir: cwd.into(), ..context() };
+            terminal_title("{cwd}", &context, Some(std::path::Path::new("/home/al")))
+        };
+        assert_eq!(title("/home/al"), "\x1b]0;~\x07");
+        assert_eq!(title("/home/al/src"), "\x1b]0;~/src\x07");
+        assert_eq!(title("/home/alice"), "\x1b]0;/home/alice\x07");
+    }
+
+    #[test]
+    fn test_global_segment_separator() {
+        let theme = r#"
+            [theme]
+            name = "test"
+            segment_separator = "slash"
+
+            [[segments]]
+            module = "user"
+
+            [[segments]]
+            module = "missing"
+
+            [[segments]]
+            module = "cwd"
+            separator = " > "
+
+            [[segments]]
+            module = "git"
+
+            [[segments]]
+            module = "symbol"
+            "#;
+        let modules = [module("user", "me"), module("cwd", "~"), module("git", "main"), module("symbol", "$")];
+
+        let output = renderer(theme).render(&context(), &modules).unwrap();
+        assert_eq!(output, "me / ~ > main / $");
+
+        let custom = theme.replace("\"slash\"", "\" | \"");
+        let output = renderer(&custom).render(&context(), &modules).unwrap();
+        assert_eq!(output, "me | ~ > main | $");
+    }
+
+    #[test]
+    fn test_rule_match_modes() {
+        let renderer = renderer(
+            r#"
+            [theme]
+            name = "test"
+
+            [[segments]]
+            module = "git"
+            rules = [
+                { condition = "if_equals", value = "branch=main" },
+                { condition = "if_not_empty", value = "dirty" },
+            ]
+
+            [[segments]]
+            module = "git"
+            match = "any"
+            rules = [
+                { condition = "if_equals", value = "branch=main" },
+                { condition = "if_not_empty", value = "dirty" },
+            ]
+
+            [[segments]]
+            module = "git"
+            match = "any"
+            rules = [
+                { condition = "if_equals", value = "branch=main" },
+                { condition = "if_typo", value = "branch" },
+                { condition = "if_equals", value = "branch" },
+            ]
+            "#,
+        );
+        let segments = &renderer.theme().segments;
+        let git = |branch: &str, dirty: &str| ModuleData {
+            module: "git".to_string(),
+            data: serde_json::json!({ "text": branch, "branch": branch, "dirty": dirty }),
+            cached: false,
+        };
+
+        let all = &segments[0];
+        assert_eq!(all.rule_match, RuleMatch::All);
+        assert!(renderer.should_display_segment(all, &git("main", "*")).unwrap());
+        assert!(!renderer.should_display_segment(all, &git("main", "")).unwrap());
+        assert!(!renderer.should_display_segment(all, &git("dev", "*")).unwrap());
+
+        let any = &segments[1];
+        assert_eq!(any.rule_match, RuleMatch::Any);
+        assert!(renderer.should_display_segment(any, &git("main", "")).unwrap());
+        assert!(renderer.should_display_segment(any, &git("dev", "*")).unwrap());
+        assert!(!renderer.should_display_segment(any, &git("dev", "")).unwrap());
+
+        // Unknown and malformed rules don't make an `any` segment show
+        let skipped = &segments[2];
+        assert!(renderer.should_display_segment(skipped, &git("main", "")).unwrap());
+        assert!(!renderer.should_display_segment(skipped, &git("dev", "")).unwrap());
+    }
+
+    #[test]
+    fn test_render_prompt_without_daemon() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let theme_path = temp_dir.path().join("theme.toml");
+        std::fs::write(&config_path, "modules = [\"cwd\"]\n").unwrap();
+        std::fs::write(
+            &theme_path,
+            r#"
+            [theme]
+            name = "library"
+            [[segments]]
+            module = "symbol"
+            separator = " "
+            [[segments]]
+            module = "cwd"
+            [[segments]]
+            module = "not-registered"
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::load_from(&config_path).unwrap();
+        let theme = Theme::load_from(&theme_path).unwrap();
+        let mut context = context();
+        context.current_dir = std::path::PathBuf::from("/srv/project");
+
+        let output = render_prompt(&config, &theme, &context).unwrap();
+        assert_eq!(output, " ⚡ /srv/project");
+    }
+
+    #[test]
+    fn test_icon_ascii_fallback_from_context() {
+        let with_icon = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "git"
+            icon = "\ue0a0"
+            "#,
+        );
+
+        let context = ModuleContext { ascii_icons: true, ..context() };
+        let output = with_icon.render(&context, &[module("git", "main")]).unwrap();
+
+        assert_eq!(output, "git: main");
+    }
+
+    #[test]
+    fn test_icon_ascii_fallback_from_env() {
+        let with_icon = renderer(
+            r#"
+            [theme]
+            name = "test"
+            [[segments]]
+            module = "git"
+            icon = "\ue0a0"
+            "#,
+        );
+        let render = || {
+            let context = ModuleContext::from_env().unwrap();
+            assert_eq!(context.ascii_icons, crate::icons::ascii_mode());
+            let context = ModuleContext { color_support: ColorSupport::None, ..context };
+            with_icon.render(&context, &[module("git", "main")]).unwrap()
+        };
+
+        std::env::remove_var("NO_NERD_FONT");
+        std::env::set_var("ZIRON_ASCII", "1");
+        assert!(crate::icons::ascii_mode());
+        assert_eq!(render(), "git: main");
+        std::env::remove_var("ZIRON_ASCII");
+
+        std::env::set_var("NO_NERD_FONT", "");
+        assert!(crate::icons::ascii_mode());
+        assert_eq!(render(), "git: main");
+        std::env::remove_var("NO_NERD_FONT");
+
+        assert!(!crate::icons::ascii_mode());
+        assert_eq!(render(), "\u{e0a0} main");
+    }
+}
@@ -1,12 +1,13 @@
 //! Theme system for prompt rendering
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
 
 /// Theme configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Theme {
     #[serde(rename = "theme")]
     pub config: ThemeConfig,
@@ -14,9 +15,12 @@ pub struct Theme {
     pub segments: Vec<Segment>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ThemeConfig {
     pub name: String,
+    /// Parent theme (name, or a `.toml` path relative to this file) whose settings and segments this one overrides
+    #[serde(default)]
+    pub extends: Option<String>,
     /// Color preset name (dark, light, solarized, etc.)
     #[serde(default)]
     pub preset: Option<String>,
@@ -35,23 +39,122 @@ pub struct ThemeConfig {
     /// Custom color palette
     #[serde(default)]
     pub color_palette: Option<std::collections::HashMap<String, String>>,
+    /// Hex colors interpolated across the visible segments that have no color of their own
+    #[serde(default)]
+    pub gradient: Option<Vec<String>>,
+    /// Separator between segments without their own: "space", "slash", "powerline" or literal text
+    #[serde(default)]
+    pub segment_separator: Option<String>,
+    /// Terminal title template (OSC 0), e.g. `"{user}@{host}: {cwd}"`
+    #[serde(default)]
+    pub terminal_title: Option<String>,
+    /// Report the working directory to the terminal (OSC 7) so new tabs open there
+    #[serde(default)]
+    pub report_cwd: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ThemeConfig {
+    /// The resolved global segment separator, with named styles expanded
+    pub fn segment_separator(&self) -> Option<&str> {
+        self.segment_separator.as_deref().map(|style| match style {
+            "space" => " ",
+            "slash" => " / ",
+            "powerline" => " \u{e0b1} ",
+            custom => custom,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Segment {
     pub module: String,
     pub color: Option<String>,
     pub separator: Option<String>,
     #[serde(default)]
     pub rules: Vec<Rule>,
+    /// Whether all rules (default) or any rule must pass for the segment to show
+    #[serde(default, rename = "match")]
+    pub rule_match: RuleMatch,
+    /// Pad the segment text to at least this many visible columns
+    #[serde(default)]
+    pub min_width: Option<usize>,
+    /// Truncate the segment text (with an ellipsis) beyond this many visible columns
+    #[serde(default)]
+    pub max_width: Option<usize>,
+    /// Alignment of the text within `min_width`
+    #[serde(default)]
+    pub align: Align,
+    /// Icon (usually a Nerd Font glyph) shown before the text
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Replacement for `icon` when Nerd Fonts are disabled; defaults to the module's ASCII fallback
+    #[serde(default)]
+    pub ascii_icon: Option<String>,
+    /// Template for the segment text, e.g. `"{info.branch} {ahead}"`; defaults to the module's `text`
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Text styles such as `bold`, passed on to JSON renderers
+    #[serde(default)]
+    pub styles: Vec<String>,
+    /// Character repeated by a `module = "fill"` segment to stretch the line to the terminal width
+    #[serde(default)]
+    pub fill_char: Option<String>,
+}
+
+impl Segment {
+    /// Whether this is a `fill` segment rather than a module
+    pub fn is_fill(&self) -> bool {
+        self.module.trim().eq_ignore_ascii_case("fill")
+    }
+
+    /// The character a fill segment repeats (a space by default)
+    pub fn fill_char(&self) -> &str {
+        self.fill_char.as_deref().filter(|c| !c.is_empty()).unwrap_or(" ")
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Text alignment within a padded segment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+/// How a segment's rules combine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleMatch {
+    #[default]
+    All,
+    Any,
+}
+
+/// Segment display rule
+///
+/// `if_exists` and `if_not_empty` take a field name as `value`. `if_equals` and
+/// `if_not_equals` take `"field=expected"`, e.g. `value = "namespace=production"`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Rule {
     pub condition: String,
     pub value: serde_json::Value,
 }
 
+/// Colors `hash_color` picks from; black and white are left out so text stays readable
+const HASH_COLORS: &[&str] = &["red", "green", "yellow", "blue", "magenta", "cyan"];
+
+/// A stable color for a name such as a hostname, so each machine's prompt looks distinct
+///
+/// Uses FNV-1a rather than `DefaultHasher`, whose output may change between Rust releases.
+pub fn hash_color(name: &str) -> &'static str {
+    let hash = name
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    HASH_COLORS[(hash % HASH_COLORS.len() as u64) as usize]
+}
+
 /// Color theme presets
 pub fn get_color_preset(preset_name: &str) -> Option<std::collections::HashMap<String, String>> {
     let presets: std::collections::HashMap<&str, std::collections::HashMap<&str, &str>> = [
@@ -103,10 +206,9 @@ pub fn get_color_preset(preset_name: &str) -> Option<std::collections::HashMap<S
 impl Theme {
     /// Load a theme from a TOML file
     pub fn load_from(path: &Path) -> Result<Self> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| Error::Theme(format!("Failed to read theme file: {}", e)))?;
-
-        let mut theme: Theme = toml::from_str(&content)
+        let table = Self::read_inherited(path, &mut Vec::new())?;
+        let mut theme: Theme = table
+            .try_into()
             .map_err(|e| Error::Theme(format!("Failed to parse theme: {}", e)))?;
         
         // Apply color preset if specified
@@ -126,9 +228,156 @@ impl Theme {
         Ok(theme)
     }
 
+    /// Read a theme file with the theme it `extends` merged underneath
+    fn read_inherited(path: &Path, chain: &mut Vec<PathBuf>) -> Result<toml::Table> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if chain.contains(&canonical) {
+            return Err(Error::Theme(format!("Theme inheritance cycle through {}", path.display())));
+        }
+        chain.push(canonical);
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Theme(format!("Failed to read theme file: {}", e)))?;
+        let mut table: toml::Table = toml::from_str(&content)
+            .map_err(|e| Error::Theme(format!("Failed to parse theme: {}", e)))?;
+
+        let parent = table
+            .get("theme")
+            .and_then(|config| config.get("extends"))
+            .and_then(|extends| extends.as_str())
+            .map(|extends| Self::parent_path(path, extends));
+        let Some(parent) = parent else {
+            return Ok(table);
+        };
+
+        let mut base = Self::read_inherited(&parent, chain)?;
+        if let Some(toml::Value::Array(segments)) = table.remove("segments") {
+            merge_segments(&mut base, "segments", segments);
+        }
+        if let Some(toml::Value::Table(config)) = table.get_mut("theme") {
+            if let Some(toml::Value::Array(segments)) = config.remove("right_segments") {
+                if let Some(toml::Value::Table(base_config)) = base.get_mut("theme") {
+                    merge_segments(base_config, "right_segments", segments);
+                }
+            }
+        }
+        crate::config::merge_tables(&mut base, table);
+        Ok(base)
+    }
+
+    /// Path of the theme named by `extends` in the theme file at `path`
+    fn parent_path(path: &Path, extends: &str) -> PathBuf {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        if extends.ends_with(".toml") {
+            return dir.join(extends);
+        }
+        // Sibling theme directory (`themes/<child>/theme.toml` -> `themes/<parent>/theme.toml`)
+        let sibling = dir.parent().map(|themes| themes.join(extends).join("theme.toml"));
+        sibling.filter(|sibling| sibling.exists()).unwrap_or_else(|| Self::path_for(extends))
+    }
+
+    /// JSON Schema of the theme file format, for editor completion and validation
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(Theme)).unwrap_or_default()
+    }
+
     /// Get the default theme path
     pub fn default_path() -> Result<PathBuf> {
-        Ok(PathBuf::from("themes").join("default").join("theme.toml"))
+        Ok(Self::path_for("default"))
+    }
+
+    /// Path of a named theme (`themes/<name>/theme.toml`)
+    pub fn path_for(name: &str) -> PathBuf {
+        PathBuf::from("themes").join(name).join("theme.toml")
     }
 }
 
+/// Overlay child segments onto the parent's list under `key`: a segment for a module the
+/// parent already has is merged into it in place, others are appended
+fn merge_segments(parent: &mut toml::Table, key: &str, segments: Vec<toml::Value>) {
+    let toml::Value::Array(list) = parent.entry(key).or_insert_with(|| toml::Value::Array(Vec::new())) else {
+        parent.insert(key.to_string(), toml::Value::Array(segments));
+        return;
+    };
+
+    for segment in segments {
+        let module = segment.get("module").and_then(|m| m.as_str());
+        let existing = list
+            .iter_mut()
+            .find(|existing| module.is_some() && existing.get("module").and_then(|m| m.as_str()) == module);
+        match (existing, segment) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(segment)) => {
+                crate::config::merge_tables(existing, segment);
+            }
+            (_, segment) => list.push(segment),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_inheritance() {
+        let themes = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, content: &str| {
+            let dir = themes.path().join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("theme.toml"), content).unwrap();
+            dir.join("theme.toml")
+        };
+        write(
+            "base",
+            r##"
+            [theme]
+            name = "base"
+            background = "#000000"
+            color_palette = { accent = "#111111", muted = "#222222" }
+
+            [[segments]]
+            module = "cwd"
+            color = "blue"
+
+            [[segments]]
+            module = "git"
+            color = "green"
+            icon = "git"
+            "##,
+        );
+        let child = write(
+            "child",
+            r##"
+            [theme]
+            name = "child"
+            extends = "base"
+            color_palette = { accent = "#333333" }
+
+            [[segments]]
+            module = "git"
+            color = "red"
+
+            [[segments]]
+            module = "symbol"
+            "##,
+        );
+
+        let theme = Theme::load_from(&child).unwrap();
+        assert_eq!(theme.config.name, "child");
+        assert_eq!(theme.config.background.as_deref(), Some("#000000"));
+        let palette = theme.config.color_palette.unwrap();
+        assert_eq!(palette["accent"], "#333333");
+        assert_eq!(palette["muted"], "#222222");
+
+        let modules: Vec<_> = theme.segments.iter().map(|s| s.module.as_str()).collect();
+        assert_eq!(modules, vec!["cwd", "git", "symbol"]);
+        assert_eq!(theme.segments[1].color.as_deref(), Some("red"));
+        assert_eq!(theme.segments[1].icon.as_deref(), Some("git"));
+
+        // A theme that ends up extending itself is rejected
+        let looped = write("loop", "[theme]\nname = \"loop\"\nextends = \"other\"\n");
+        write("other", "[theme]\nname = \"other\"\nextends = \"loop\"\n");
+        let err = Theme::load_from(&looped).unwrap_err();
+        assert!(err.to_string().contains("cycle"), "{}", err);
+    }
+}
@@ -2,67 +2,85 @@
 
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use ziron_core::error::{Error, Result};
 
 /// Daemon PID file path
 pub fn get_pid_file() -> Result<PathBuf> {
-    let home = std::env::var("HOME")
-        .map_err(|_| Error::Config("HOME not set".to_string()))?;
-    Ok(PathBuf::from(home)
-        .join(".config")
-        .join("ziron")
-        .join("ziron-daemon.pid"))
+    ziron_core::paths::pid_file()
 }
 
 /// Check if daemon is running
 pub fn is_running() -> bool {
-    if let Ok(pid_file) = get_pid_file() {
-        if pid_file.exists() {
-            if let Ok(pid_str) = fs::read_to_string(&pid_file) {
-                if let Ok(pid) = pid_str.trim().parse::<u32>() {
-                    // Check if process is still running using /proc (Linux) or kill -0
-                    #[cfg(target_os = "linux")]
-                    {
-                        let proc_path = format!("/proc/{}", pid);
-                        if std::path::Path::new(&proc_path).exists() {
-                            return true;
-                        } else {
-                            // PID file exists but process is dead, clean it up
-                            let _ = fs::remove_file(&pid_file);
-                        }
-                    }
-                    #[cfg(not(target_os = "linux"))]
-                    {
-                        // For non-Linux, just check if PID file exists
-                        // In production, could use libc::kill(pid, 0)
-                        return true;
-                    }
-                }
-            }
+    match get_pid_file() {
+        Ok(pid_file) => is_running_at(&pid_file),
+        Err(_) => false,
+    }
+}
+
+/// Check if the daemon named by a PID file is running, cleaning up the file of a dead one
+fn is_running_at(pid_file: &Path) -> bool {
+    if !pid_file.exists() {
+        return false;
+    }
+
+    // A daemon that died between creating the file and writing its PID leaves it empty
+    let Some(pid) = fs::read_to_string(pid_file).ok().and_then(|pid_str| pid_str.trim().parse::<u32>().ok()) else {
+        let _ = fs::remove_file(pid_file);
+        return false;
+    };
+
+    // Check if process is still running using /proc (Linux) or kill -0
+    #[cfg(target_os = "linux")]
+    {
+        let proc_path = format!("/proc/{}", pid);
+        if std::path::Path::new(&proc_path).exists() {
+            return true;
         }
+        // PID file exists but process is dead, clean it up
+        let _ = fs::remove_file(pid_file);
+        false
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        // For non-Linux, just check if PID file exists
+        // In production, could use libc::kill(pid, 0)
+        let _ = pid;
+        true
     }
-    false
 }
 
-/// Write PID file
-pub fn write_pid_file() -> Result<()> {
-    let pid_file = get_pid_file()?;
-    
+/// Write the PID file, or return `false` when another daemon already holds it
+///
+/// The file is created exclusively, so of two daemons started at once only one gets it.
+pub fn acquire_pid_file() -> Result<bool> {
+    acquire_pid_file_at(&get_pid_file()?)
+}
+
+fn acquire_pid_file_at(pid_file: &Path) -> Result<bool> {
     // Create parent directory if it doesn't exist
     if let Some(parent) = pid_file.parent() {
         fs::create_dir_all(parent)
-            .map_err(|e| Error::Config(format!("Failed to create config directory: {}", e)))?;
+            .map_err(|e| Error::Config(format!("Failed to create runtime directory: {}", e)))?;
     }
-    
-    let pid = std::process::id();
-    let mut file = fs::File::create(&pid_file)
-        .map_err(|e| Error::Config(format!("Failed to create PID file: {}", e)))?;
-    
-    file.write_all(pid.to_string().as_bytes())
-        .map_err(|e| Error::Config(format!("Failed to write PID file: {}", e)))?;
-    
-    Ok(())
+
+    // A second attempt follows removing the file of a dead daemon
+    for _ in 0..2 {
+        match fs::OpenOptions::new().write(true).create_new(true).open(pid_file) {
+            Ok(mut file) => {
+                file.write_all(std::process::id().to_string().as_bytes())
+                    .map_err(|e| Error::Config(format!("Failed to write PID file: {}", e)))?;
+                return Ok(true);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if is_running_at(pid_file) {
+                    return Ok(false);
+                }
+            }
+            Err(e) => return Err(Error::Config(format!("Failed to create PID file: {}", e))),
+        }
+    }
+    Ok(false)
 }
 
 /// Remove PID file
@@ -88,3 +106,40 @@ pub fn get_daemon_pid() -> Option<u32> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_acquired_once() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("ziron/ziron-daemon.pid");
+
+        assert!(acquire_pid_file_at(&pid_file).unwrap());
+        assert_eq!(fs::read_to_string(&pid_file).unwrap(), std::process::id().to_string());
+        // This process is alive, so a second daemon stays out
+        assert!(!acquire_pid_file_at(&pid_file).unwrap());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_stale_pid_file_replaced() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("ziron-daemon.pid");
+        fs::write(&pid_file, u32::MAX.to_string()).unwrap();
+
+        assert!(acquire_pid_file_at(&pid_file).unwrap());
+        assert_eq!(fs::read_to_string(&pid_file).unwrap(), std::process::id().to_string());
+    }
+
+    #[test]
+    fn test_empty_pid_file_replaced() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("ziron-daemon.pid");
+        fs::write(&pid_file, "").unwrap();
+
+        assert!(acquire_pid_file_at(&pid_file).unwrap());
+        assert_eq!(fs::read_to_string(&pid_file).unwrap(), std::process::id().to_string());
+    }
+}
@@ -6,16 +6,20 @@ mod watchers;
 use ziron_core::cache::Cache;
 use ziron_core::config::Config;
 use ziron_core::error::Result;
+use ziron_core::event::Event as ZironEvent;
 use ziron_core::ipc::{Message, MessagePayload, Request, Response};
-use ziron_core::module::{ModuleContext, ModuleData, ModuleRegistry};
+use ziron_core::module::{normalize_module_name, ModuleContext, ModuleData, ModuleRegistry};
 use ziron_core::prompt::PromptRenderer;
 use ziron_core::theme::Theme;
+use std::borrow::Cow;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::UnixListener;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Semaphore};
+use tokio::task::JoinSet;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -60,23 +64,20 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Check if daemon is already running
-    if daemon::is_running() {
+    // Claim the PID file; another daemon holding it is already running
+    if !daemon::acquire_pid_file()? {
         eprintln!("Daemon is already running");
         return Ok(());
     }
 
-    // Write PID file
-    daemon::write_pid_file()?;
-
     // Setup signal handlers for graceful shutdown
     let mut shutdown = setup_signal_handlers();
 
     let config = Config::load().unwrap_or_default();
-    let mut registry = ModuleRegistry::new();
+    let mut registry = ModuleRegistry::with_builtins();
 
     // Load modules
-    load_modules(&mut registry)?;
+    ziron_modules::register_builtin_modules(&mut registry);
 
     // Load theme
     let theme = if let Some(theme_name) = &config.theme {
@@ -92,8 +93,8 @@ async fn main() -> Result<()> {
     let cache = Cache::new(cache_ttl, 1000);
 
     // Setup event system
-    let (event_tx, _event_rx) = broadcast::channel(100);
-    
+    let (event_tx, _event_rx) = broadcast::channel::<ZironEvent>(100);
+
     // Start file system watcher for current directory
     let mut watcher_manager = watchers::WatcherManager::new(cache.clone(), event_tx.clone());
     if let Ok(current_dir) = std::env::current_dir() {
@@ -116,6 +117,12 @@ async fn main() -> Result<()> {
     // Start IPC server
     let socket_path = get_socket_path()?;
     if socket_path.exists() {
+        // Only a stale socket is removed; one that still answers belongs to a running daemon
+        if std::os::unix::net::UnixStream::connect(&socket_path).is_ok() {
+            eprintln!("Daemon is already listening on {}", socket_path.display());
+            daemon::remove_pid_file()?;
+            return Ok(());
+        }
         std::fs::remove_file(&socket_path)?;
     }
 
@@ -139,9 +146,10 @@ async fn main() -> Result<()> {
                         let renderer_clone = renderer.clone();
                         let cache_clone = cache.clone();
                         let config_clone = config.clone();
+                        let event_tx_clone = event_tx.clone();
                         let request_id = request_id_counter.fetch_add(1, Ordering::Relaxed);
                         tokio::spawn(async move {
-                            if let Err(e) = handle_client(&mut stream, &registry_clone, &renderer_clone, &cache_clone, &config_clone, request_id).await {
+                            if let Err(e) = handle_client(&mut stream, &registry_clone, &renderer_clone, &cache_clone, &config_clone, &event_tx_clone, request_id).await {
                                 tracing::error!("Error handling client: {}", e);
                             }
                         });
@@ -185,12 +193,13 @@ async fn handle_client(
     renderer: &PromptRenderer,
     cache: &Cache,
     config: &Config,
+    event_tx: &broadcast::Sender<ZironEvent>,
     request_id: u64,
 ) -> Result<()> {
     // Read message length (4 bytes)
     let mut len_buf = [0u8; 4];
     stream.read_exact(&mut len_buf).await?;
-    let msg_len = u32::from_le_bytes(len_buf) as usize;
+    let msg_len = ziron_core::ipc::message_len(len_buf)?;
 
     // Read message data
     let mut buffer = vec![0u8; msg_len];
@@ -205,50 +214,39 @@ async fn handle_client(
         MessagePayload::Request(request) => {
             match request {
                 Request::GetPrompt { context } => {
-                    let mut module_data = Vec::new();
-                    
-                    // Get modules from theme segments (what should be displayed)
-                    // Fallback to config.modules if theme has no segments
-                    let modules_to_fetch: Vec<String> = if !renderer.theme().segments.is_empty() {
-                        renderer.theme().segments.iter()
-                            .map(|s| s.module.clone())
-                            .collect()
-                    } else {
-                        config.modules.clone()
-                    };
-                    
-                    // Fetch data from modules (with caching)
-                    for module_name in &modules_to_fetch {
-                        let cache_key = format!("{}:{}", module_name, context.current_dir.display());
-                        
-                        // Try to get from cache first
-                        if let Some(cached_data) = cache.get(&cache_key) {
-                            module_data.push(cached_data);
-                        } else {
-                            // Fetch fresh data
-                            if let Some(data) = fetch_module_data(module_name, context, registry).await? {
-                                // Store in cache
-                                cache.set(cache_key, data.clone());
-                                module_data.push(data);
-                            }
-                        }
-                    }
+                    let (config, renderer) = project_settings(config, renderer, context);
+                    let module_data = prompt_module_data(context, registry, &renderer, cache, &config, event_tx).await?;
                     // Render prompt
                     let prompt = renderer.render(context, &module_data)?;
                     Response::Prompt(prompt)
                 }
+                Request::GetPromptJson { context } => {
+                    let (config, renderer) = project_settings(config, renderer, context);
+                    let module_data = prompt_module_data(context, registry, &renderer, cache, &config, event_tx).await?;
+                    Response::PromptSegments(renderer.render_json(context, &module_data)?)
+                }
                 Request::GetModuleData { module, context } => {
                     let cache_key = format!("{}:{}", module, context.current_dir.display());
+                    let volatile = config.module_volatile(&normalize_module_name(module));
                     
                     // Try cache first
-                    if let Some(cached_data) = cache.get(&cache_key) {
+                    if let Some(cached_data) = (!volatile).then(|| cache.get(&cache_key)).flatten() {
                         Response::ModuleData(cached_data)
-                    } else if let Some(data) = fetch_module_data(module, context, registry).await? {
-                        // Store in cache
-                        cache.set(cache_key, data.clone());
-                        Response::ModuleData(data)
                     } else {
-                        Response::Error(format!("Module {} not found", module))
+                        let fetched = if volatile {
+                            fetch_module_data(module, context, registry, config).await?
+                        } else {
+                            fetch_or_last_good(module, context, registry, cache, &cache_key, config).await?
+                        };
+                        match fetched {
+                            Some(data) => {
+                                if !volatile && !data.cached {
+                                    store_fetched(cache, event_tx, cache_key, &data, context, config);
+                                }
+                                Response::ModuleData(data)
+                            }
+                            None => Response::Error(format!("Module {} not found", module)),
+                        }
                     }
                 }
                 Request::InvalidateCache { module } => {
@@ -261,22 +259,35 @@ async fn handle_client(
                         hits: stats.hits,
                         misses: stats.misses,
                         size: stats.size,
+                        modules: stats.modules,
                     }
                 }
                 Request::Shutdown => {
                     // Send response before shutting down
-                    let response_msg = Message::new_response(request_id, Response::Ok);
-                    let response_data = response_msg.serialize()
-                        .map_err(|e| ziron_core::error::Error::Config(format!("Failed to serialize response: {}", e)))?;
-                    let len = response_data.len() as u32;
-                    stream.write_all(&len.to_le_bytes()).await?;
-                    stream.write_all(&response_data).await?;
-                    stream.flush().await?;
-                    
+                    write_response(stream, request_id, Response::Ok).await?;
+
                     // Trigger shutdown
                     daemon::remove_pid_file()?;
                     std::process::exit(0);
                 }
+                Request::DirectoryChanged { context } => {
+                    if config.performance.prewarm {
+                        tokio::spawn(prewarm_directory(
+                            context.clone(),
+                            registry.clone(),
+                            renderer.clone(),
+                            cache.clone(),
+                            config.clone(),
+                            event_tx.clone(),
+                        ));
+                    }
+                    let _ = event_tx.send(ZironEvent::directory_change(context.current_dir.display().to_string()));
+                    Response::Ok
+                }
+                Request::SubscribeEvents => {
+                    // Subscriptions keep the connection open instead of answering once
+                    return stream_events(stream, event_tx.subscribe(), request_id).await;
+                }
                 Request::HealthCheck => {
                     Response::Health {
                         status: "ok".to_string(),
@@ -291,51 +302,200 @@ async fn handle_client(
     };
 
     // Send response
-    let response_msg = Message::new_response(request_id, response);
-    let response_data = response_msg.serialize()
-        .map_err(|e| ziron_core::error::Error::Config(format!("Failed to serialize response: {}", e)))?;
-    let len = response_data.len() as u32;
-    stream.write_all(&len.to_le_bytes()).await?;
-    stream.write_all(&response_data).await?;
+    write_response(stream, request_id, response).await
+}
+
+/// Write a length-prefixed response message
+async fn write_response<W: AsyncWrite + Unpin>(stream: &mut W, request_id: u64, response: Response) -> Result<()> {
+    let frame = Message::new_response(request_id, response).to_frame()?;
+    stream.write_all(&frame).await?;
     stream.flush().await?;
 
     Ok(())
 }
 
+/// Forward broadcast events to a subscribed client until it disconnects
+///
+/// The subscription is acknowledged with `Response::Ok` before the first event. The client
+/// sends nothing further, so a read returning end-of-file means it went away.
+async fn stream_events<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    mut events: broadcast::Receiver<ZironEvent>,
+    request_id: u64,
+) -> Result<()> {
+    write_response(stream, request_id, Response::Ok).await?;
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let mut buf = [0u8; 64];
+    loop {
+        tokio::select! {
+            read = reader.read(&mut buf) => match read {
+                Ok(0) | Err(_) => return Ok(()),
+                Ok(_) => {}
+            },
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let response = Response::Event {
+                        event_type: event.event_type,
+                        data: event.data.to_string(),
+                    };
+                    if write_response(&mut writer, request_id, response).await.is_err() {
+                        // Client went away
+                        return Ok(());
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event subscriber lagged, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        }
+    }
+}
+
+/// The config and renderer for a prompt, with the directory's `.ziron.toml` overrides applied
+fn project_settings<'a>(
+    config: &'a Config,
+    renderer: &'a PromptRenderer,
+    context: &ModuleContext,
+) -> (Cow<'a, Config>, Cow<'a, PromptRenderer>) {
+    let (config, theme) = ziron_core::project::resolve_for_dir(config, renderer.theme(), &context.current_dir);
+    let renderer = match theme {
+        Cow::Owned(theme) => Cow::Owned(PromptRenderer::new(theme)),
+        Cow::Borrowed(_) => Cow::Borrowed(renderer),
+    };
+    (config, renderer)
+}
+
+/// Fetch (with caching) the data of every module the prompt displays
+async fn prompt_module_data(
+    context: &ModuleContext,
+    registry: &ModuleRegistry,
+    renderer: &PromptRenderer,
+    cache: &Cache,
+    config: &Config,
+    event_tx: &broadcast::Sender<ZironEvent>,
+) -> Result<Vec<ModuleData>> {
+    let mut module_data = Vec::new();
+    
+    // Get modules from theme segments (what should be displayed)
+    // Fallback to config.modules if theme has no segments
+    let modules_to_fetch = ziron_core::prompt::prompt_modules(config, renderer.theme());
+    
+    // Fetch data from modules (with caching)
+    for module_name in &modules_to_fetch {
+        // Volatile modules (e.g. `time`) change every prompt, so they skip the cache entirely
+        if config.module_volatile(module_name) {
+            if let Some(data) = fetch_module_data(module_name, context, registry, config).await? {
+                module_data.push(data);
+            }
+            continue;
+        }
+
+        let cache_key = format!("{}:{}", module_name, context.current_dir.display());
+        
+        // Try to get from cache first
+        if let Some(cached_data) = cache.get(&cache_key) {
+            module_data.push(cached_data);
+        } else {
+            // Fetch fresh data, or keep showing the last good data if that fails
+            if let Some(data) = fetch_or_last_good(module_name, context, registry, cache, &cache_key, config).await? {
+                if !data.cached {
+                    store_fetched(cache, event_tx, cache_key, &data, context, config);
+                }
+                module_data.push(data);
+            }
+        }
+    }
+    Ok(module_data)
+}
+
+/// Fetch the prompt modules missing from the cache for a directory
+///
+/// At most `prewarm_concurrency` modules run at once, and whatever has not finished
+/// within `prewarm_budget_ms` is left for the next prompt to fetch.
+async fn prewarm_directory(
+    context: ModuleContext,
+    registry: ModuleRegistry,
+    renderer: PromptRenderer,
+    cache: Cache,
+    config: Config,
+    event_tx: broadcast::Sender<ZironEvent>,
+) {
+    let (config, renderer) = project_settings(&config, &renderer, &context);
+    let permits = Arc::new(Semaphore::new(config.performance.prewarm_concurrency.max(1)));
+    let context = Arc::new(context);
+    let config = Arc::new(config.into_owned());
+    let mut fetches = JoinSet::new();
+
+    for module_name in ziron_core::prompt::prompt_modules(&config, renderer.theme()) {
+        let cache_key = format!("{}:{}", module_name, context.current_dir.display());
+        if config.module_volatile(&module_name) || cache.contains(&cache_key) {
+            continue;
+        }
+        let permits = permits.clone();
+        let registry = registry.clone();
+        let context = context.clone();
+        let config = config.clone();
+        fetches.spawn(async move {
+            let _permit = permits.acquire_owned().await.ok()?;
+            let fetched = tokio::task::spawn_blocking(move || registry.fetch(&module_name, &context, &config)).await.ok()?;
+            match fetched {
+                Ok(data) => data.map(|data| (cache_key, data)),
+                Err(e) => {
+                    tracing::debug!("Pre-warming {} failed: {}", cache_key, e);
+                    None
+                }
+            }
+        });
+    }
+
+    let deadline = tokio::time::sleep(Duration::from_millis(config.performance.prewarm_budget_ms));
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            fetched = fetches.join_next() => match fetched {
+                Some(Ok(Some((cache_key, data)))) => store_fetched(&cache, &event_tx, cache_key, &data, &context, &config),
+                Some(_) => {}
+                None => break,
+            },
+            _ = &mut deadline => {
+                tracing::debug!("Pre-warm budget exhausted for {}", context.current_dir.display());
+                fetches.abort_all();
+                break;
+            }
+        }
+    }
+}
+
+/// Cache freshly fetched data, announcing a module change only when its content differs
+///
+/// The data stays cached for the module's `cache_ttl_ms` if set, else the cache's TTL.
+fn store_fetched(
+    cache: &Cache,
+    event_tx: &broadcast::Sender<ZironEvent>,
+    cache_key: String,
+    data: &ModuleData,
+    context: &ModuleContext,
+    config: &Config,
+) {
+    let changed = match config.module_cache_ttl(&data.module) {
+        Some(ttl) => cache.set_if_changed_with_ttl(cache_key, data.clone(), ttl),
+        None => cache.set_if_changed(cache_key, data.clone()),
+    };
+    if changed {
+        let path = context.current_dir.display().to_string();
+        let _ = event_tx.send(ZironEvent::module_changed(data.module.clone(), path));
+    }
+}
+
 async fn fetch_module_data(
     module_name: &str,
     context: &ModuleContext,
-    _registry: &ModuleRegistry,
+    registry: &ModuleRegistry,
+    config: &Config,
 ) -> Result<Option<ModuleData>> {
-    // Call the actual module implementation
-    // Since Rust is statically compiled, we use a match statement
-    let result = match module_name {
-        "git" => ziron_module_git::GitModule::fetch_data(context),
-        "sysinfo" => ziron_module_sysinfo::SysInfoModule::fetch_data(context),
-        "exitcode" => exitcode::ExitCodeModule::fetch_data(context),
-        "timer" => timer::TimerModule::fetch_data(context),
-        "time" => time::TimeModule::fetch_data(context),
-        "venv" => venv::VenvModule::fetch_data(context),
-        "node" => node::NodeModule::fetch_data(context),
-        "rust" => rust::RustModule::fetch_data(context),
-        "conda" => conda::CondaModule::fetch_data(context),
-        "svn" => ziron_module_svn::SvnModule::fetch_data(context),
-        "mercurial" => ziron_module_mercurial::MercurialModule::fetch_data(context),
-        "docker" => ziron_module_docker::DockerModule::fetch_data(context),
-        "kubernetes" => ziron_module_kubernetes::KubernetesModule::fetch_data(context),
-        "aws" => ziron_module_aws::AwsModule::fetch_data(context),
-        "gcp" => ziron_module_gcp::GcpModule::fetch_data(context),
-        "azure" => ziron_module_azure::AzureModule::fetch_data(context),
-        "terraform" => ziron_module_terraform::TerraformModule::fetch_data(context),
-        "go" => ziron_module_go::GoModule::fetch_data(context),
-        _ => {
-            // Unknown module, return None
-            return Ok(None);
-        }
-    };
-    
-    match result {
-        Ok(data) => Ok(Some(data)),
+    match registry.fetch(module_name, context, config) {
+        Ok(data) => Ok(data),
         Err(e) => {
             tracing::warn!("Error fetching data for module {}: {}", module_name, e);
             Ok(None)
@@ -343,23 +503,261 @@ async fn fetch_module_data(
     }
 }
 
-fn load_modules(_registry: &mut ModuleRegistry) -> Result<()> {
-    // In a real implementation, scan modules directory
-    // For now, just return OK
-    Ok(())
+/// Fetch a module, falling back to its last good cached data when the fetch fails
+///
+/// A fetch fails when the module returns an error or data with an `error` key (e.g. `git`
+/// while another process holds the index lock). Fallback data is marked `cached`.
+async fn fetch_or_last_good(
+    module_name: &str,
+    context: &ModuleContext,
+    registry: &ModuleRegistry,
+    cache: &Cache,
+    cache_key: &str,
+    config: &Config,
+) -> Result<Option<ModuleData>> {
+    let fetched = registry.fetch(module_name, context, config);
+    let failed = match &fetched {
+        Ok(data) => data.as_ref().is_some_and(|data| data.data.get("error").is_some()),
+        Err(e) => {
+            tracing::warn!("Error fetching data for module {}: {}", module_name, e);
+            true
+        }
+    };
+
+    if failed {
+        let grace = Duration::from_millis(config.performance.error_grace_ms);
+        if let Some(data) = cache.last_good(cache_key, grace) {
+            tracing::debug!("Showing last good data for module {}", module_name);
+            return Ok(Some(data));
+        }
+    }
+    Ok(fetched.unwrap_or(None))
 }
 
 fn load_theme(name: &str) -> Result<Theme> {
-    let theme_path = PathBuf::from("themes").join(name).join("theme.toml");
-    Theme::load_from(&theme_path)
+    Theme::load_from(&Theme::path_for(name))
 }
 
 fn get_socket_path() -> Result<PathBuf> {
-    let home = std::env::var("HOME")
-        .map_err(|_| ziron_core::error::Error::Config("HOME not set".to_string()))?;
-    Ok(PathBuf::from(home)
-        .join(".config")
-        .join("ziron")
-        .join("ziron.sock"))
+    Config::socket_path()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ziron_core::event::EventType;
+
+    #[test]
+    fn test_identical_refetch_emits_no_change_event() {
+        let cache = Cache::new(Duration::from_secs(60), 100);
+        let (event_tx, mut event_rx) = broadcast::channel(16);
+        let context = ModuleContext::for_test("/repo");
+        let data = ModuleData {
+            module: "git".to_string(),
+            data: serde_json::json!({ "text": "main" }),
+            cached: false,
+        };
+
+        store_fetched(&cache, &event_tx, "git:/repo".to_string(), &data, &context, &Config::default());
+        let event = event_rx.try_recv().unwrap();
+        assert_eq!(event.event_type, EventType::ModuleChanged);
+
+        // The watcher invalidates, the module is re-fetched with the same result
+        cache.invalidate(None);
+        store_fetched(&cache, &event_tx, "git:/repo".to_string(), &data, &context, &Config::default());
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    static PREWARM_FETCHES: AtomicU64 = AtomicU64::new(0);
+
+    fn counted_fetch(context: &ModuleContext, _: &Config) -> Result<ModuleData> {
+        PREWARM_FETCHES.fetch_add(1, Ordering::SeqCst);
+        Ok(ModuleData {
+            module: "prewarm-a".to_string(),
+            data: serde_json::json!({ "text": context.current_dir.display().to_string(), "user": context.user }),
+            cached: false,
+        })
+    }
+
+    fn other_counted_fetch(context: &ModuleContext, config: &Config) -> Result<ModuleData> {
+        let mut data = counted_fetch(context, config)?;
+        data.module = "prewarm-b".to_string();
+        Ok(data)
+    }
+
+    #[tokio::test]
+    async fn test_reported_directory_change_prewarms_prompt_modules() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut registry = ModuleRegistry::new();
+        registry.register_fetcher("prewarm-a", counted_fetch);
+        registry.register_fetcher("prewarm-b", other_counted_fetch);
+        let renderer = PromptRenderer::new(
+            toml::from_str("[theme]\nname = \"test\"\n[[segments]]\nmodule = \"prewarm-a\"\n[[segments]]\nmodule = \"prewarm-b\"\n").unwrap(),
+        );
+        let cache = Cache::new(Duration::from_secs(60), 100);
+        let mut config = Config::default();
+        config.performance.prewarm = true;
+        let (event_tx, _) = broadcast::channel(16);
+        let mut changes = event_tx.subscribe();
+
+        // The client's own context is used, not the daemon's environment
+        let context = ModuleContext {
+            user: "client".to_string(),
+            ..ModuleContext::for_test(dir.path())
+        };
+        let (mut server, mut client) = tokio::net::UnixStream::pair().unwrap();
+        let request = Message::new_request(1, Request::DirectoryChanged { context: context.clone() }).serialize().unwrap();
+        client.write_all(&(request.len() as u32).to_le_bytes()).await.unwrap();
+        client.write_all(&request).await.unwrap();
+        handle_client(&mut server, &registry, &renderer, &cache, &config, &event_tx, 1).await.unwrap();
+        assert!(matches!(read_response(&mut client).await, Response::Ok));
+
+        // Wait until both modules have been stored
+        let mut stored = 0;
+        while stored < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), changes.recv()).await.unwrap().unwrap();
+            if event.event_type == EventType::ModuleChanged {
+                stored += 1;
+            }
+        }
+
+        let fetches = PREWARM_FETCHES.load(Ordering::SeqCst);
+        let before = cache.stats();
+        let data = prompt_module_data(&context, &registry, &renderer, &cache, &config, &event_tx).await.unwrap();
+
+        assert_eq!(data.len(), 2);
+        assert!(data.iter().all(|data| data.cached && data.data["user"] == "client"));
+        let after = cache.stats();
+        assert_eq!(after.hits, before.hits + 2);
+        assert_eq!(after.misses, before.misses);
+        assert_eq!(PREWARM_FETCHES.load(Ordering::SeqCst), fetches);
+    }
+
+    static VOLATILE_FETCHES: AtomicU64 = AtomicU64::new(0);
+
+    fn volatile_fetch(_: &ModuleContext, _: &Config) -> Result<ModuleData> {
+        let fetches = VOLATILE_FETCHES.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(ModuleData {
+            module: "time".to_string(),
+            data: serde_json::json!({ "text": fetches.to_string() }),
+            cached: false,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_volatile_module_bypasses_cache() {
+        let mut registry = ModuleRegistry::new();
+        registry.register_fetcher("time", volatile_fetch);
+        let renderer = PromptRenderer::new(
+            toml::from_str("[theme]\nname = \"test\"\n[[segments]]\nmodule = \"time\"\n").unwrap(),
+        );
+        let cache = Cache::new(Duration::from_secs(60), 100);
+        let config = Config::default();
+        let (event_tx, _) = broadcast::channel(16);
+        let mut context = ModuleContext::from_env().unwrap();
+        context.current_dir = PathBuf::from("/volatile");
+
+        // A stale entry must not be served for a volatile module
+        cache.set("time:/volatile".to_string(), volatile_fetch(&context, &config).unwrap());
+        let first = prompt_module_data(&context, &registry, &renderer, &cache, &config, &event_tx).await.unwrap();
+        let second = prompt_module_data(&context, &registry, &renderer, &cache, &config, &event_tx).await.unwrap();
+
+        assert!(!first[0].cached && !second[0].cached);
+        assert_ne!(first[0].data, second[0].data);
+        assert_eq!(cache.stats().hits, 0);
+    }
+
+    static FLAKY_FETCHES: AtomicU64 = AtomicU64::new(0);
+
+    fn flaky_fetch(_: &ModuleContext, _: &Config) -> Result<ModuleData> {
+        match FLAKY_FETCHES.fetch_add(1, Ordering::SeqCst) {
+            0 => Ok(ModuleData {
+                module: "flaky".to_string(),
+                data: serde_json::json!({ "text": "main" }),
+                cached: false,
+            }),
+            1 => Err(ziron_core::error::Error::Module("index.lock exists".to_string())),
+            _ => Ok(ModuleData::error("flaky", &ziron_core::error::Error::Module("index.lock exists".to_string()))),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetch_shows_last_good_data() {
+        let mut registry = ModuleRegistry::new();
+        registry.register_fetcher("flaky", flaky_fetch);
+        let renderer = PromptRenderer::new(
+            toml::from_str("[theme]\nname = \"test\"\n[[segments]]\nmodule = \"flaky\"\n").unwrap(),
+        );
+        // Nothing stays fresh, so every prompt fetches again
+        let cache = Cache::new(Duration::ZERO, 100);
+        let config = Config::default();
+        let (event_tx, _) = broadcast::channel(16);
+        let mut context = ModuleContext::from_env().unwrap();
+        context.current_dir = PathBuf::from("/flaky");
+
+        let first = prompt_module_data(&context, &registry, &renderer, &cache, &config, &event_tx).await.unwrap();
+        assert_eq!(first[0].data["text"], "main");
+        assert!(!first[0].cached);
+
+        for _ in 0..2 {
+            let data = prompt_module_data(&context, &registry, &renderer, &cache, &config, &event_tx).await.unwrap();
+            assert_eq!(data[0].data["text"], "main");
+            assert!(data[0].cached);
+        }
+        assert_eq!(FLAKY_FETCHES.load(Ordering::SeqCst), 3);
+
+        // Past the grace period the failure shows
+        let config: Config = toml::from_str("[performance]\nerror_grace_ms = 0").unwrap();
+        let data = prompt_module_data(&context, &registry, &renderer, &cache, &config, &event_tx).await.unwrap();
+        assert!(data[0].data.get("error").is_some());
+    }
+
+    async fn read_response(stream: &mut tokio::net::UnixStream) -> Response {
+        let mut len_buf = [0u8; 4];
+        stream.read_exact(&mut len_buf).await.unwrap();
+        let mut buffer = vec![0u8; u32::from_le_bytes(len_buf) as usize];
+        stream.read_exact(&mut buffer).await.unwrap();
+        match Message::deserialize(&buffer).unwrap().payload {
+            MessagePayload::Response(response) => response,
+            MessagePayload::Request(_) => panic!("expected a response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribed_client_receives_events() {
+        let (event_tx, _) = broadcast::channel(16);
+        let (mut server, mut client) = tokio::net::UnixStream::pair().unwrap();
+        let events = event_tx.subscribe();
+        let subscription = tokio::spawn(async move { stream_events(&mut server, events, 7).await });
+
+        assert!(matches!(read_response(&mut client).await, Response::Ok));
+
+        event_tx.send(ZironEvent::directory_change("/repo".to_string())).unwrap();
+        match read_response(&mut client).await {
+            Response::Event { event_type, data } => {
+                assert_eq!(event_type, EventType::DirectoryChange);
+                assert_eq!(data, r#"{"path":"/repo"}"#);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        // Closing the channel ends the subscription
+        drop(event_tx);
+        subscription.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_disconnected_subscriber_ends_without_events() {
+        let (event_tx, _) = broadcast::channel::<ZironEvent>(16);
+        let (mut server, mut client) = tokio::net::UnixStream::pair().unwrap();
+        let events = event_tx.subscribe();
+        let subscription = tokio::spawn(async move { stream_events(&mut server, events, 7).await });
+
+        assert!(matches!(read_response(&mut client).await, Response::Ok));
+        drop(client);
+
+        // No event is ever sent; the client's end-of-file alone ends the task
+        tokio::time::timeout(Duration::from_secs(5), subscription).await.unwrap().unwrap().unwrap();
+        assert_eq!(event_tx.receiver_count(), 0);
+    }
+}
@@ -3,10 +3,17 @@
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::Path;
 use std::sync::mpsc;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use ziron_core::cache::Cache;
 use ziron_core::event::Event as ZironEvent;
 
+/// Quiet period that ends a burst of file events
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Longest a continuous burst of file events delays the cache invalidation
+const MAX_BURST: Duration = Duration::from_secs(1);
+
 /// Watcher manager for file system and Git changes
 pub struct WatcherManager {
     file_watcher: Option<RecommendedWatcher>,
@@ -32,42 +39,56 @@ impl WatcherManager {
         
         self.file_watcher = Some(watcher);
         
-        // Spawn task to handle file system events
+        // Handle file system events on their own thread, since the channel blocks
         let cache_clone = self.cache.clone();
         let event_tx_clone = self.event_tx.clone();
-        tokio::spawn(async move {
-            Self::handle_file_events(rx, cache_clone, event_tx_clone).await;
-        });
+        std::thread::spawn(move || Self::handle_file_events(rx, cache_clone, event_tx_clone));
         
         Ok(())
     }
 
     /// Handle file system events
-    async fn handle_file_events(
+    ///
+    /// A burst of events (e.g. a build writing many files) is collapsed into one cache
+    /// invalidation and one `DirectoryChange` event, sent once the burst goes quiet.
+    fn handle_file_events(
         rx: mpsc::Receiver<Result<Event, notify::Error>>,
         cache: Cache,
         event_tx: broadcast::Sender<ZironEvent>,
     ) {
-        while let Ok(event_result) = rx.recv() {
-            match event_result {
-                Ok(event) => {
-                    // Invalidate cache for affected paths
-                    // For now, invalidate all cache entries when any file changes
-                    // TODO: Implement more granular cache invalidation
-                    cache.invalidate(None);
-                    
-                    // Emit directory change event
-                    if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)) {
-                        if let Some(path) = event.paths.first() {
-                            if let Some(path_str) = path.to_str() {
-                                let _ = event_tx.send(ZironEvent::directory_change(path_str.to_string()));
-                            }
-                        }
+        while let Ok(first) = rx.recv() {
+            let mut changed = Self::changed_path(first);
+            // Long bursts still flush every `MAX_BURST`
+            let deadline = Instant::now() + MAX_BURST;
+            while let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                match rx.recv_timeout(wait.min(DEBOUNCE)) {
+                    Ok(next) => {
+                        let path = Self::changed_path(next);
+                        changed = changed.or(path);
                     }
+                    Err(_) => break,
                 }
-                Err(e) => {
-                    tracing::warn!("File watcher error: {}", e);
-                }
+            }
+
+            if let Some(path) = changed {
+                // For now, invalidate all cache entries when any file changes
+                // TODO: Implement more granular cache invalidation
+                cache.invalidate(None);
+                let _ = event_tx.send(ZironEvent::directory_change(path));
+            }
+        }
+    }
+
+    /// The first path a modify, create or remove event names
+    fn changed_path(event_result: Result<Event, notify::Error>) -> Option<String> {
+        match event_result {
+            Ok(event) if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)) => {
+                event.paths.first().and_then(|path| path.to_str()).map(|path| path.to_string())
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("File watcher error: {}", e);
+                None
             }
         }
     }
@@ -89,3 +110,40 @@ impl WatcherManager {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind};
+    use ziron_core::event::EventType;
+
+    #[test]
+    fn test_file_event_burst_debounced() {
+        let cache = Cache::new(Duration::from_secs(60), 10);
+        let (event_tx, mut events) = broadcast::channel(16);
+        let (tx, rx) = mpsc::channel();
+        let handler = std::thread::spawn({
+            let cache = cache.clone();
+            move || WatcherManager::handle_file_events(rx, cache, event_tx)
+        });
+
+        let data = ziron_core::module::ModuleData {
+            module: "git".to_string(),
+            data: serde_json::json!({"text": "main"}),
+            cached: false,
+        };
+        cache.set("git:/repo".to_string(), data);
+        for i in 0..50 {
+            let event = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(format!("/repo/file{}", i).into());
+            tx.send(Ok(event)).unwrap();
+        }
+        tx.send(Ok(Event::new(EventKind::Create(CreateKind::File)).add_path("/repo/new".into()))).unwrap();
+        drop(tx);
+        handler.join().unwrap();
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.event_type, EventType::DirectoryChange);
+        assert_eq!(event.data["path"], "/repo/file0");
+        assert!(events.try_recv().is_err());
+        assert!(cache.get("git:/repo").is_none());
+    }
+}
@@ -0,0 +1,74 @@
+//! Built-in module fetchers shared by the daemon and ziron-shell
+
+use ziron_core::module::ModuleRegistry;
+
+/// Register the fetchers of every bundled module crate
+pub fn register_builtin_modules(registry: &mut ModuleRegistry) {
+    registry.register_fetcher("git", |context, config| {
+        let timeout = config.performance.command_timeout();
+        ziron_module_git::GitModule::fetch_data_with_config(context, &config.module_settings("git"), timeout)
+    });
+    registry.register_fetcher("sysinfo", |context, config| {
+        ziron_module_sysinfo::SysInfoModule::fetch_data_with_config(context, &config.module_settings("sysinfo"))
+    });
+    registry.register_fetcher("exitcode", |context, config| {
+        exitcode::ExitCodeModule::fetch_data_with_config(context, &config.module_settings("exitcode"))
+    });
+    registry.register_fetcher("timer", |context, config| {
+        timer::TimerModule::fetch_data_with_config(context, &config.module_settings("timer"))
+    });
+    registry.register_fetcher("time", |context, config| {
+        time::TimeModule::fetch_data_with_config(context, &config.module_settings("time"))
+    });
+    registry.register_fetcher("venv", |context, config| {
+        venv::VenvModule::fetch_data_with_config(context, &config.module_settings("venv"))
+    });
+    registry.register_fetcher("node", |context, config| {
+        node::NodeModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("rust", |context, config| {
+        rust::RustModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("conda", |context, _| conda::CondaModule::fetch_data(context));
+    registry.register_fetcher("svn", |context, config| {
+        ziron_module_svn::SvnModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("mercurial", |context, config| {
+        ziron_module_mercurial::MercurialModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("jj", |context, config| {
+        ziron_module_jj::JjModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("docker", |context, config| {
+        ziron_module_docker::DockerModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("kubernetes", |context, config| {
+        ziron_module_kubernetes::KubernetesModule::fetch_data_with_config(
+            context,
+            &config.module_settings("kubernetes"),
+            config.performance.command_timeout(),
+        )
+    });
+    registry.register_fetcher("aws", |context, _| ziron_module_aws::AwsModule::fetch_data(context));
+    registry.register_fetcher("gcp", |context, config| {
+        ziron_module_gcp::GcpModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("azure", |context, config| {
+        ziron_module_azure::AzureModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("cloud", |context, config| {
+        let timeout = config.performance.command_timeout();
+        ziron_module_cloud::CloudModule::fetch_data_or_empty(context, &config.module_settings("cloud"), timeout)
+    });
+    registry.register_fetcher("terraform", |context, config| {
+        ziron_module_terraform::TerraformModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("go", |context, config| {
+        ziron_module_go::GoModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("helm", |context, _| ziron_module_helm::HelmModule::fetch_data(context));
+    registry.register_fetcher("shell", |context, config| {
+        ziron_module_shell::ShellModule::fetch_data(context, config.performance.command_timeout())
+    });
+    registry.register_fetcher("session", |context, _| ziron_module_session::SessionModule::fetch_data(context));
+}
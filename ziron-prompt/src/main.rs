@@ -1,44 +1,189 @@
 //! Ziron Prompt - Shell prompt binary that communicates with the daemon
 
-use std::path::PathBuf;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::UnixStream;
-use ziron_core::error::Result;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use ziron_core::error::{Error, Result};
+use ziron_core::ipc::{Request, Response};
+use ziron_core::module::ModuleContext;
+
+/// Connection attempts before giving up on the daemon
+const CONNECT_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry, doubled after each attempt (25 + 50 + 100 ms)
+const CONNECT_BACKOFF: Duration = Duration::from_millis(25);
 
 fn get_socket_path() -> Result<PathBuf> {
-    let home = std::env::var("HOME")
-        .map_err(|_| ziron_core::error::Error::Config("HOME not set".to_string()))?;
-    Ok(PathBuf::from(home)
-        .join(".config")
-        .join("ziron")
-        .join("ziron.sock"))
+    ziron_core::config::Config::socket_path()
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let socket_path = get_socket_path()?;
+/// Connect to the daemon socket, retrying with backoff while it starts up
+fn connect_with_retry(socket_path: &Path, attempts: u32, backoff: Duration) -> std::io::Result<UnixStream> {
+    let mut delay = backoff;
+    let mut attempt = 1;
+    loop {
+        match UnixStream::connect(socket_path) {
+            Ok(stream) => return Ok(stream),
+            Err(e) if attempt >= attempts => return Err(e),
+            Err(_) => {
+                std::thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
 
-    // Connect to daemon
-    let mut stream = match UnixStream::connect(&socket_path).await {
-        Ok(stream) => stream,
-        Err(_) => {
-            // Daemon not running, return empty prompt
-            eprintln!("Warning: Ziron daemon not running. Start it with: ziron-daemon");
-            std::process::exit(0);
+/// Connect to the daemon, starting it first with `start_daemon` when its socket is missing
+///
+/// Without a socket file and without auto-start there is nothing to wait for, so the
+/// prompt falls back right away instead of retrying.
+fn connect(socket_path: &Path, start_daemon: impl FnOnce() -> bool) -> Option<UnixStream> {
+    if socket_path.exists() {
+        if let Ok(stream) = connect_with_retry(socket_path, CONNECT_ATTEMPTS, CONNECT_BACKOFF) {
+            return Some(stream);
         }
+    }
+    if !start_daemon() {
+        return None;
+    }
+    connect_with_retry(socket_path, CONNECT_ATTEMPTS, CONNECT_BACKOFF).ok()
+}
+
+/// Start the daemon in the background when `auto_start_daemon` is enabled
+fn spawn_daemon() -> bool {
+    use std::process::{Command, Stdio};
+
+    let enabled = ziron_core::config::Config::load()
+        .map(|config| config.shell.auto_start_daemon)
+        .unwrap_or(false);
+    enabled
+        && Command::new("ziron-daemon")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .is_ok()
+}
+
+/// Request a rendered prompt over a length-prefixed bincode message, as the daemon expects
+fn request_prompt<S: Read + Write>(stream: &mut S, context: ModuleContext) -> Result<String> {
+    match ziron_core::ipc::request(stream, Request::GetPrompt { context })? {
+        Response::Prompt(prompt) => Ok(prompt),
+        Response::Error(e) => Err(Error::Ipc(e)),
+        _ => Err(Error::Ipc("Unexpected response from daemon".to_string())),
+    }
+}
+
+/// A minimal prompt for when the daemon can't render one: the directory and `$`
+fn local_prompt(context: &ModuleContext, home: Option<&Path>) -> String {
+    let cwd = match home.and_then(|home| context.current_dir.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => context.current_dir.display().to_string(),
     };
+    format!("{} $ ", cwd)
+}
+
+fn main() -> Result<()> {
+    let socket_path = get_socket_path()?;
+    let context = ModuleContext::from_env()?;
+    let home = std::env::var_os("HOME").filter(|home| !home.is_empty()).map(PathBuf::from);
 
-    // Send request (empty for now, daemon will use environment)
-    stream.write_all(b"prompt").await?;
-    stream.shutdown().await?;
+    // Connect to daemon
+    let stream = connect(&socket_path, spawn_daemon);
+    if stream.is_none() {
+        eprintln!("Warning: Ziron daemon not running. Start it with: ziron-daemon");
+    }
 
-    // Read response
-    let mut buffer = Vec::new();
-    stream.read_to_end(&mut buffer).await?;
+    let prompt = match stream {
+        Some(mut stream) => match request_prompt(&mut stream, context.clone()) {
+            Ok(prompt) => prompt,
+            Err(e) => {
+                eprintln!("Warning: Failed to get prompt from daemon: {}", e);
+                local_prompt(&context, home.as_deref())
+            }
+        },
+        None => local_prompt(&context, home.as_deref()),
+    };
 
     // Output prompt
-    print!("{}", String::from_utf8_lossy(&buffer));
+    print!("{}", prompt);
 
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_retries_until_socket_appears() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("ziron.sock");
+
+        let listener_path = socket_path.clone();
+        let listener = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(40));
+            let listener = std::os::unix::net::UnixListener::bind(&listener_path).unwrap();
+            listener.accept().unwrap();
+        });
+
+        let stream = connect_with_retry(&socket_path, CONNECT_ATTEMPTS, CONNECT_BACKOFF);
+        assert!(stream.is_ok());
+        listener.join().unwrap();
+
+        let missing = temp_dir.path().join("missing.sock");
+        let start = std::time::Instant::now();
+        assert!(connect_with_retry(&missing, CONNECT_ATTEMPTS, CONNECT_BACKOFF).is_err());
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_missing_socket_without_auto_start_falls_back_at_once() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.sock");
+
+        let start = std::time::Instant::now();
+        let mut asked = false;
+        assert!(connect(&missing, || {
+            asked = true;
+            false
+        })
+        .is_none());
+        assert!(asked);
+        assert!(start.elapsed() < CONNECT_BACKOFF);
+    }
+
+    #[test]
+    fn test_request_prompt_speaks_daemon_protocol() {
+        use ziron_core::ipc::{Message, MessagePayload};
+
+        let (mut client, mut daemon) = UnixStream::pair().unwrap();
+        let daemon = std::thread::spawn(move || {
+            let mut len_buf = [0u8; 4];
+            daemon.read_exact(&mut len_buf).unwrap();
+            let mut buffer = vec![0u8; u32::from_le_bytes(len_buf) as usize];
+            daemon.read_exact(&mut buffer).unwrap();
+            let request = Message::deserialize(&buffer).unwrap();
+            assert!(matches!(request.payload, MessagePayload::Request(Request::GetPrompt { .. })));
+
+            let response = Message::new_response(request.request_id, Response::Prompt("~ > ".to_string()));
+            daemon.write_all(&response.to_frame().unwrap()).unwrap();
+        });
+
+        let context = ModuleContext::from_env().unwrap();
+        assert_eq!(request_prompt(&mut client, context).unwrap(), "~ > ");
+        daemon.join().unwrap();
+    }
+
+    #[test]
+    fn test_local_prompt() {
+        let prompt = |cwd: &str| local_prompt(&ModuleContext::for_test(cwd), Some(Path::new("/home/al")));
+        assert_eq!(prompt("/tmp/ziron-prompt"), "/tmp/ziron-prompt $ ");
+        assert_eq!(prompt("/home/al"), "~ $ ");
+        assert_eq!(prompt("/home/al/src"), "~/src $ ");
+        assert_eq!(prompt("/home/alice"), "/home/alice $ ");
+    }
+}
@@ -4,15 +4,18 @@
 #[derive(Debug, Clone)]
 pub enum Redirection {
     Output(String),      // >
+    OutputClobber(String), // >| (overwrites even with noclobber set)
     Append(String),      // >>
     Input(String),       // <
+    HereDoc(String),     // <<WORD (holds the body, already expanded)
     Error(String),       // 2>
     ErrorAppend(String), // 2>>
     Combined(String),    // &>
     FdOutput(u32, String),  // n> (file descriptor output)
     #[allow(dead_code)]
     FdInput(u32, String),   // n< (file descriptor input)
-    FdDup(u32, u32),        // n>&m (file descriptor duplication)
+    FdDup(u32, u32),        // n>&m (file descriptor duplication): n shares m's redirection
+    DupOriginal(u32),       // n>&m written before m was redirected: m's original target
 }
 
 /// A command to execute
@@ -26,16 +29,55 @@ pub struct Command {
     pub stdin_file: Option<Redirection>,
 }
 
-impl Command {
-    /// Check if this is a built-in command
-    pub fn is_builtin(&self) -> bool {
-        matches!(
-            self.name.as_str(),
-            "cd" | "exit" | "pwd" | "echo" | "export" | "unset" | "history"
-                | "alias" | "unalias" | "type" | "which" | "source" | "jobs" | "fg" | "bg"
-                | "kill" | "wait" | "ulimit" | "umask" | "times" | "pushd" | "popd" | "dirs"
-                | "read" | "printf" | "test" | "true" | "false" | "function"
-        )
+/// Commands joined by pipes
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    pub commands: Vec<Command>,
+    /// Whether the line ended with `&`
+    pub background: bool,
+}
+
+/// Operator joining a pipeline to the one before it in a list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListOperator {
+    /// `&&`: runs if the previous pipeline succeeded
+    And,
+    /// `||`: runs if the previous pipeline failed
+    Or,
+    /// `;`, or the start of the line: always runs
+    Seq,
+}
+
+impl ListOperator {
+    /// Whether the pipeline after this operator runs, given the last exit status
+    pub fn should_run(self, last_exit_code: i32) -> bool {
+        match self {
+            ListOperator::And => last_exit_code == 0,
+            ListOperator::Or => last_exit_code != 0,
+            ListOperator::Seq => true,
+        }
     }
 }
 
+/// Pipelines joined by `&&`, `||` and `;`
+///
+/// Pipelines are kept as source text and parsed only when they run, so their
+/// expansions (`$?`, command substitutions) see the effects of the ones before.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineList {
+    pub pipelines: Vec<(ListOperator, String)>,
+}
+
+/// Builtins that can't be disabled, since no external command can change the shell's state
+pub const REQUIRED_BUILTINS: &[&str] = &["cd", "exit"];
+
+/// Check if a name is a built-in command
+pub fn is_builtin(name: &str) -> bool {
+    matches!(
+        name,
+        "cd" | "exit" | "pwd" | "echo" | "export" | "unset" | "history"
+            | "alias" | "unalias" | "type" | "which" | "source" | "jobs" | "fg" | "bg"
+            | "kill" | "wait" | "ulimit" | "umask" | "times" | "pushd" | "popd" | "dirs"
+            | "read" | "printf" | "test" | "true" | "false" | "function" | "let" | "set"
+    )
+}
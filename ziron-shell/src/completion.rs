@@ -8,26 +8,141 @@ use rustyline::Context;
 use rustyline::Helper;
 use rustyline::Result as RustylineResult;
 use std::env;
+use std::sync::Arc;
+use ziron_core::config::CommandCompletion;
 
 /// Built-in commands for completion
 const BUILTIN_COMMANDS: &[&str] = &[
     "cd", "exit", "pwd", "echo", "export", "unset", "history",
     "alias", "unalias", "type", "which", "source", "jobs", "fg", "bg",
     "kill", "wait", "ulimit", "umask", "times", "pushd", "popd", "dirs",
-    "read", "printf", "test", "true", "false",
+    "read", "printf", "test", "true", "false", "let",
 ];
 
+/// Names of the executables on `PATH`
+fn path_commands() -> std::collections::HashSet<String> {
+    // HashSet for deduplication across PATH directories
+    let mut path_commands_set = std::collections::HashSet::new();
+    if let Ok(path) = env::var("PATH") {
+        for dir in path.split(':') {
+            if let Ok(entries) = std::fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    if let Ok(file_name) = entry.file_name().into_string() {
+                        path_commands_set.insert(file_name);
+                    }
+                }
+            }
+        }
+    }
+    path_commands_set
+}
+
+/// Built-in commands and PATH executables, sorted
+pub fn available_commands() -> Vec<String> {
+    let mut commands: Vec<String> = BUILTIN_COMMANDS.iter().map(|s| s.to_string()).collect();
+    commands.extend(path_commands());
+    commands.sort();
+    commands.dedup();
+    commands
+}
+
+/// Commands within a small edit distance of a mistyped `name`, closest first
+pub fn suggest_commands(name: &str, commands: &[String]) -> Vec<String> {
+    // Short names allow a single edit, so `ls` doesn't suggest every two-letter command
+    let max_distance = if name.chars().count() <= 3 { 1 } else { 2 };
+    let mut matches: Vec<(usize, &String)> = commands
+        .iter()
+        .filter(|command| command.as_str() != name)
+        .map(|command| (edit_distance(name, command), command))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches.into_iter().take(3).map(|(_, command)| command.clone()).collect()
+}
+
+/// Edit distance counting insertions, deletions, substitutions and adjacent transpositions
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
 /// Completion function trait for custom completions
 pub trait CompletionFunction: Send + Sync {
     fn complete(&self, word: &str, line: &str, pos: usize) -> Vec<String>;
 }
 
+/// Completion from a `[completion.commands.<name>]` config entry
+struct ConfiguredCompletion {
+    completion: CommandCompletion,
+    timeout: std::time::Duration,
+    /// Output of `command` for the line before the word being completed
+    last_output: std::sync::Mutex<Option<(String, Vec<String>)>>,
+}
+
+impl ConfiguredCompletion {
+    /// Run the completion command, killing it after the command timeout
+    fn run_command(&self, command: &str) -> Vec<String> {
+        match ziron_core::module::run_command_with_timeout("sh", &["-c", command], None, self.timeout) {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(e) => {
+                tracing::debug!("Completion command '{}' failed: {}", command, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl CompletionFunction for ConfiguredCompletion {
+    fn complete(&self, word: &str, line: &str, pos: usize) -> Vec<String> {
+        let mut candidates = self.completion.candidates.clone();
+        if let Some(command) = &self.completion.command {
+            // Typing more of the same word reuses the output instead of running the command again
+            let key = line[..pos.saturating_sub(word.len())].to_string();
+            let mut last_output = self.last_output.lock().unwrap_or_else(|e| e.into_inner());
+            match last_output.as_ref() {
+                Some((cached_key, output)) if *cached_key == key => candidates.extend(output.iter().cloned()),
+                _ => {
+                    let output = self.run_command(command);
+                    candidates.extend(output.iter().cloned());
+                    *last_output = Some((key, output));
+                }
+            }
+        }
+        candidates
+    }
+}
+
 /// Completion helper for Ziron shell
 pub struct ZironCompleter {
     filename_completer: FilenameCompleter,
     aliases: Vec<String>,
     functions: Vec<String>,
-    completion_functions: std::collections::HashMap<String, Box<dyn CompletionFunction>>,
+    completion_functions: std::collections::HashMap<String, Arc<dyn CompletionFunction>>,
     case_insensitive: bool,
     partial_completion: bool,
 }
@@ -38,7 +153,7 @@ impl Clone for ZironCompleter {
             filename_completer: FilenameCompleter::new(),
             aliases: self.aliases.clone(),
             functions: self.functions.clone(),
-            completion_functions: std::collections::HashMap::new(), // Can't clone trait objects
+            completion_functions: self.completion_functions.clone(),
             case_insensitive: self.case_insensitive,
             partial_completion: self.partial_completion,
         }
@@ -72,6 +187,10 @@ impl ZironCompleter {
         self.aliases.retain(|a| a != alias);
     }
 
+    pub fn clear_aliases(&mut self) {
+        self.aliases.clear();
+    }
+
     pub fn add_function(&mut self, function: String) {
         if !self.functions.contains(&function) {
             self.functions.push(function);
@@ -85,7 +204,23 @@ impl ZironCompleter {
 
     #[allow(dead_code)]
     pub fn register_completion_function(&mut self, command: String, func: Box<dyn CompletionFunction>) {
-        self.completion_functions.insert(command, func);
+        self.completion_functions.insert(command, Arc::from(func));
+    }
+
+    /// Register the argument completions defined in the config
+    pub fn load_config_completions(
+        &mut self,
+        completions: &std::collections::HashMap<String, CommandCompletion>,
+        timeout: std::time::Duration,
+    ) {
+        for (command, completion) in completions {
+            let completion = ConfiguredCompletion {
+                completion: completion.clone(),
+                timeout,
+                last_output: std::sync::Mutex::new(None),
+            };
+            self.completion_functions.insert(command.clone(), Arc::new(completion));
+        }
     }
 
     #[allow(dead_code)]
@@ -106,20 +241,8 @@ impl ZironCompleter {
         // Add functions
         commands.extend(self.functions.iter().cloned());
 
-        // Add PATH executables (optimized: use HashSet for deduplication)
-        let mut path_commands_set = std::collections::HashSet::new();
-        if let Ok(path) = env::var("PATH") {
-            for dir in path.split(':') {
-                if let Ok(entries) = std::fs::read_dir(dir) {
-                    for entry in entries.flatten() {
-                        if let Ok(file_name) = entry.file_name().into_string() {
-                            path_commands_set.insert(file_name);
-                        }
-                    }
-                }
-            }
-        }
-        commands.extend(path_commands_set);
+        // Add PATH executables
+        commands.extend(path_commands());
 
         commands.sort();
         commands
@@ -225,11 +348,17 @@ impl Completer for ZironCompleter {
             }
         }
 
-        // Check for custom completion function for the command
-        if words.len() >= 1 {
+        // Check for custom completion function for the command's arguments
+        let completing_argument = words.len() > 1 || (words.len() == 1 && line_before_cursor.ends_with(' '));
+        if completing_argument {
             let command = words[0];
             if let Some(completion_func) = self.completion_functions.get(command) {
-                let current_word = words.last().copied().unwrap_or("");
+                // After a space a new, still empty word is being completed
+                let current_word = if line_before_cursor.ends_with(' ') {
+                    ""
+                } else {
+                    words.last().copied().unwrap_or("")
+                };
                 let completions = completion_func.complete(current_word, line, pos);
                 if !completions.is_empty() {
                     let matches: Vec<Pair> = completions
@@ -255,33 +384,59 @@ impl Completer for ZironCompleter {
             }
         }
 
-        // Check if we're completing a variable (starts with $)
-        if let Some(last_word) = words.last() {
-            if last_word.starts_with('$') && !last_word.contains('/') {
-                let var_prefix = &last_word[1..];
-                let env_vars = self.get_env_vars();
-                let matches: Vec<Pair> = env_vars
-                    .iter()
-                    .filter(|var| self.matches(var, var_prefix))
-                    .map(|var| {
-                        let replacement = format!("${}", var);
-                        let display = format!("{} (env var)", replacement);
-                        Pair {
-                            display,
-                            replacement,
-                        }
-                    })
-                    .collect();
-
-                if !matches.is_empty() {
-                    let start_pos = line_before_cursor.rfind('$').unwrap_or(pos);
-                    return Ok((start_pos, matches));
-                }
+        // Check if we're completing a variable ($VAR, ${VAR, or inside ${VAR:-$...})
+        if let Some((start_pos, braced, var_prefix)) = variable_prefix(line_before_cursor) {
+            // Don't add a closing brace if the cursor already sits before one
+            let close = if braced && !line[pos..].starts_with('}') { "}" } else { "" };
+            let env_vars = self.get_env_vars();
+            let matches: Vec<Pair> = env_vars
+                .iter()
+                .filter(|var| self.matches(var, var_prefix))
+                .map(|var| {
+                    let replacement = if braced {
+                        format!("${{{}{}", var, close)
+                    } else {
+                        format!("${}", var)
+                    };
+                    let display = format!("{} (env var)", replacement);
+                    Pair {
+                        display,
+                        replacement,
+                    }
+                })
+                .collect();
+
+            if !matches.is_empty() {
+                return Ok((start_pos, matches));
             }
         }
 
         // Otherwise, complete as filename
-        self.filename_completer.complete(line, pos, ctx)
+        let (start_pos, mut matches) = self.filename_completer.complete(line, pos, ctx)?;
+        if matches!(words.first(), Some(&"cd") | Some(&"pushd")) {
+            // Directory-only commands: FilenameCompleter marks directories with a trailing separator
+            matches.retain(|pair| pair.replacement.ends_with(std::path::MAIN_SEPARATOR));
+        }
+        Ok((start_pos, matches))
+    }
+}
+
+/// Locate a variable reference being typed at the end of `line_before_cursor`
+///
+/// Returns the position of its `$`, whether it uses the `${` form, and the
+/// partial variable name.
+fn variable_prefix(line_before_cursor: &str) -> Option<(usize, bool, &str)> {
+    let dollar = line_before_cursor.rfind('$')?;
+    let rest = &line_before_cursor[dollar + 1..];
+    let (braced, name) = match rest.strip_prefix('{') {
+        Some(name) => (true, name),
+        None => (false, rest),
+    };
+
+    if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Some((dollar, braced, name))
+    } else {
+        None
     }
 }
 
@@ -324,3 +479,121 @@ impl Validator for ZironCompleter {}
 
 impl Helper for ZironCompleter {}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyline::history::DefaultHistory;
+
+    fn complete(line: &str, pos: usize) -> (usize, Vec<String>) {
+        let completer = ZironCompleter::new();
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let (start, pairs) = completer.complete(line, pos, &ctx).unwrap();
+        (start, pairs.into_iter().map(|pair| pair.replacement).collect())
+    }
+
+    #[test]
+    fn test_complete_braced_variable() {
+        std::env::set_var("ZIRON_BRACE_TEST", "1");
+
+        let (start, matches) = complete("echo ${ZIRON_BRACE_T", 20);
+        assert_eq!(start, 5);
+        assert_eq!(matches, vec!["${ZIRON_BRACE_TEST}".to_string()]);
+
+        // Cursor before an existing closing brace
+        let (_, matches) = complete("echo ${ZIRON_BRACE_T}", 20);
+        assert_eq!(matches, vec!["${ZIRON_BRACE_TEST".to_string()]);
+
+        // Nested inside a default-value expansion
+        let (start, matches) = complete("echo ${X:-$ZIRON_BRACE_T", 24);
+        assert_eq!(start, 10);
+        assert_eq!(matches, vec!["$ZIRON_BRACE_TEST".to_string()]);
+    }
+
+    #[test]
+    fn test_cd_completes_only_directories() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+        std::fs::write(temp_dir.path().join("file.txt"), "").unwrap();
+        let dir = format!("{}/", temp_dir.path().display());
+
+        let line = format!("cd {}", dir);
+        let (_, matches) = complete(&line, line.len());
+        assert!(matches.iter().any(|m| m.ends_with("subdir/")), "{:?}", matches);
+        assert!(!matches.iter().any(|m| m.contains("file.txt")), "{:?}", matches);
+
+        let line = format!("ls {}", dir);
+        let (_, matches) = complete(&line, line.len());
+        assert!(matches.iter().any(|m| m.ends_with("subdir/")), "{:?}", matches);
+        assert!(matches.iter().any(|m| m.contains("file.txt")), "{:?}", matches);
+    }
+
+    #[test]
+    fn test_complete_home_in_braces() {
+        let (_, matches) = complete("echo ${HO", 9);
+        assert!(matches.contains(&"${HOME}".to_string()), "{:?}", matches);
+    }
+
+    #[test]
+    fn test_config_defined_completion() {
+        let config: ziron_core::config::Config = toml::from_str(
+            "[completion.commands.mytool]\ncandidates = [\"start\", \"stop\"]\ncommand = \"printf 'status\\\\n'\"\n",
+        )
+        .unwrap();
+        let mut completer = ZironCompleter::new();
+        completer.load_config_completions(&config.completion.commands, std::time::Duration::from_secs(5));
+
+        // Clones (as handed to the line editor) keep custom completions
+        let completer = completer.clone();
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let candidates = |line: &str| {
+            let (start, pairs) = completer.complete(line, line.len(), &ctx).unwrap();
+            (start, pairs.into_iter().map(|pair| pair.replacement).collect::<Vec<_>>())
+        };
+
+        assert_eq!(candidates("mytool "), (7, vec!["start".to_string(), "stop".to_string(), "status".to_string()]));
+        assert_eq!(candidates("mytool st"), (7, vec!["start".to_string(), "stop".to_string(), "status".to_string()]));
+        assert_eq!(candidates("mytool sto"), (7, vec!["stop".to_string()]));
+    }
+
+    #[test]
+    fn test_completion_command_cached_and_timed_out() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let runs = dir.path().join("runs");
+        let toml = format!(
+            "[completion.commands.mytool]\ncommand = \"echo run >> '{}'; printf 'status\\\\n'\"\n\n[completion.commands.slowtool]\ncandidates = [\"fast\"]\ncommand = \"sleep 5\"\n",
+            runs.display()
+        );
+        let config: ziron_core::config::Config = toml::from_str(&toml).unwrap();
+        let mut completer = ZironCompleter::new();
+        completer.load_config_completions(&config.completion.commands, std::time::Duration::from_millis(100));
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let candidates = |line: &str| {
+            let (_, pairs) = completer.complete(line, line.len(), &ctx).unwrap();
+            pairs.into_iter().map(|pair| pair.replacement).collect::<Vec<_>>()
+        };
+
+        // Completing more of the same word runs the command once
+        assert_eq!(candidates("mytool "), vec!["status"]);
+        assert_eq!(candidates("mytool st"), vec!["status"]);
+        assert_eq!(std::fs::read_to_string(&runs).unwrap(), "run\n");
+        assert_eq!(candidates("mytool status "), vec!["status"]);
+        assert_eq!(std::fs::read_to_string(&runs).unwrap(), "run\nrun\n");
+
+        let start = std::time::Instant::now();
+        assert_eq!(candidates("slowtool "), vec!["fast"]);
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_suggest_commands_for_typo() {
+        let commands: Vec<String> = ["git", "grep", "gzip", "ls", "cd"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(suggest_commands("gti", &commands), vec!["git"]);
+        // Closest match first
+        assert_eq!(suggest_commands("gerp", &commands), vec!["grep", "gzip"]);
+        assert!(suggest_commands("kubectl", &commands).is_empty());
+        assert_eq!(edit_distance("gti", "git"), 1);
+    }
+}
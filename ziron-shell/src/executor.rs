@@ -1,36 +1,112 @@
 //! Command executor
 
 use crate::command::Command;
+use std::collections::HashSet;
 use std::env;
+use std::os::fd::AsFd;
 use std::process::{Command as ProcessCommand, Stdio};
 use ziron_core::error::{Error, Result};
 
 /// Command executor
-pub struct Executor;
+pub struct Executor {
+    noclobber: bool, // `set -o noclobber`: `>` refuses to overwrite existing files
+    suggest_commands: bool, // suggest similar commands when one isn't found
+    disabled_builtins: HashSet<String>, // builtins that run the external command instead
+}
+
+/// Status to exit with for `exit [N]`, `default` without an argument
+pub fn exit_code_arg(command: &Command, default: i32) -> Result<i32> {
+    match command.args.first() {
+        Some(arg) => arg
+            .parse::<i32>()
+            .map(|code| code & 0xff)
+            .map_err(|_| Error::Config(format!("exit: {}: numeric argument required", arg))),
+        None => Ok(default),
+    }
+}
+
+/// Exit status of a command that couldn't run because of `error`
+///
+/// Commands that weren't found report 127 and anything else 1.
+pub fn exit_status(error: &Error) -> i32 {
+    match error {
+        Error::CommandNotFound(_) => 127,
+        _ => 1,
+    }
+}
+
+/// What the next stage of a pipeline reads
+enum PipeInput {
+    None,
+    /// Read end of the pipe the previous stage writes to
+    Pipe(std::io::PipeReader),
+    /// Output of a builtin, written by the shell
+    Bytes(Vec<u8>),
+}
+
+/// Shell exit status of a finished process: its exit code, or 128 + N if signal N killed it
+fn status_code(status: std::process::ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+    status.code().or_else(|| status.signal().map(|signal| 128 + signal)).unwrap_or(1)
+}
 
 impl Executor {
     pub fn new() -> Self {
-        Self
+        Self { noclobber: false, suggest_commands: true, disabled_builtins: HashSet::new() }
     }
 
-    /// Execute a command
-    pub fn execute(&self, command: &Command) -> Result<()> {
+    /// Set the builtins that run the external command instead
+    ///
+    /// Returns the names that were ignored because they can't be disabled.
+    pub fn set_disabled_builtins(&mut self, names: &[String]) -> Vec<String> {
+        let (required, disabled): (Vec<String>, Vec<String>) = names
+            .iter()
+            .cloned()
+            .partition(|name| crate::command::REQUIRED_BUILTINS.contains(&name.as_str()));
+        self.disabled_builtins = disabled.into_iter().collect();
+        required
+    }
+
+    /// Check if a name runs as a builtin, taking disabled builtins into account
+    pub fn is_builtin(&self, name: &str) -> bool {
+        crate::command::is_builtin(name) && !self.disabled_builtins.contains(name)
+    }
+
+    /// Set whether a missing command suggests similarly named ones
+    pub fn set_suggest_commands(&mut self, suggest_commands: bool) {
+        self.suggest_commands = suggest_commands;
+    }
+
+    /// Whether `>` may overwrite existing files (`set -o noclobber`)
+    pub fn noclobber(&self) -> bool {
+        self.noclobber
+    }
+
+    /// Set the noclobber option
+    pub fn set_noclobber(&mut self, noclobber: bool) {
+        self.noclobber = noclobber;
+    }
+
+    /// Execute a command, returning its exit status
+    ///
+    /// Errors are for commands that couldn't run at all, e.g. ones that weren't found.
+    pub fn execute(&self, command: &Command) -> Result<i32> {
         // Check if command is a script file
         if command.name.ends_with(".ziron") && std::path::Path::new(&command.name).exists() {
             // Script execution is handled in shell.rs
             return Err(Error::Config("Script execution should be handled by shell".to_string()));
         }
         
-        if command.is_builtin() {
+        if self.is_builtin(&command.name) {
             self.execute_builtin(command)
         } else {
             self.execute_external(command)
         }
     }
 
-    /// Execute a built-in command
-    fn execute_builtin(&self, command: &Command) -> Result<()> {
-        match command.name.as_str() {
+    /// Execute a built-in command, returning its exit status
+    fn execute_builtin(&self, command: &Command) -> Result<i32> {
+        let status = match command.name.as_str() {
             "cd" => self.builtin_cd(command),
             "exit" => self.builtin_exit(command),
             "pwd" => self.builtin_pwd(),
@@ -38,92 +114,201 @@ impl Executor {
             "export" => self.builtin_export(command),
             "unset" => self.builtin_unset(command),
             "history" => self.builtin_history(),
-            "type" => self.builtin_type(command),
             "which" => self.builtin_which(command),
             "true" => Ok(()),
-            "false" => Err(Error::Config("Command failed".to_string())),
-            "read" => self.builtin_read(command),
+            "false" => return Ok(1),
+            "read" => return self.builtin_read(command),
             "printf" => self.builtin_printf(command),
-            "test" | "[" => self.builtin_test(command),
+            "test" | "[" => return self.builtin_test(command),
             "ulimit" => self.builtin_ulimit(command),
             "umask" => self.builtin_umask(command),
             "times" => self.builtin_times(),
+            "let" => self.builtin_let(command),
             _ => Err(Error::Config(format!("Unknown builtin: {}", command.name))),
+        };
+        status.map(|()| 0)
+    }
+
+    /// Execute commands joined by pipes, each stage's stdout feeding the next stage's stdin
+    ///
+    /// All stages run concurrently; the pipeline's status is that of the last stage.
+    /// Builtins don't read piped input, and only `echo`, `pwd` and `printf` write into the pipe.
+    pub fn execute_pipeline(&self, commands: &[Command]) -> Result<i32> {
+        if let [command] = commands {
+            return self.execute(command);
+        }
+
+        let mut children = Vec::new();
+        let mut writers = Vec::new();
+        let mut input = PipeInput::None;
+        let mut result = Ok(0);
+        for (i, command) in commands.iter().enumerate() {
+            let last = i + 1 == commands.len();
+
+            if self.is_builtin(&command.name) {
+                if last {
+                    result = self.execute_builtin(command);
+                    break;
+                }
+                input = match self.builtin_output(command) {
+                    Some(Ok(output)) => PipeInput::Bytes(output),
+                    Some(Err(e)) => {
+                        eprintln!("{}", e);
+                        PipeInput::None
+                    }
+                    None => {
+                        // Only the last stage's status counts
+                        let _ = self.execute_builtin(command);
+                        PipeInput::None
+                    }
+                };
+                continue;
+            }
+
+            // An input redirection takes precedence over the pipe
+            let mut feed = None;
+            let stdin = match std::mem::replace(&mut input, PipeInput::None) {
+                _ if command.stdin_file.is_some() => None,
+                PipeInput::Pipe(reader) => Some(Stdio::from(reader)),
+                PipeInput::Bytes(bytes) => {
+                    feed = Some(bytes);
+                    Some(Stdio::piped())
+                }
+                PipeInput::None if i > 0 => Some(Stdio::null()),
+                PipeInput::None => None,
+            };
+
+            let spawned = self.prepare_external(command).and_then(|mut process| {
+                if let Some(stdin) = stdin {
+                    process.stdin(stdin);
+                }
+                if !last && command.stdout.is_none() {
+                    let (reader, writer) = std::io::pipe()?;
+                    // `2>&1` joins stderr to the pipe, not to the shell's own stdout
+                    if let Some(crate::command::Redirection::DupOriginal(1)) = command.stderr {
+                        process.stderr(writer.try_clone()?);
+                    }
+                    process.stdout(writer);
+                    input = PipeInput::Pipe(reader);
+                }
+                self.spawn(&mut process, command)
+            });
+
+            match spawned {
+                Ok(mut child) => {
+                    // Write a builtin's output from another thread so a full pipe can't block the shell
+                    if let (Some(bytes), Some(mut stdin)) = (feed, child.stdin.take()) {
+                        writers.push(std::thread::spawn(move || {
+                            use std::io::Write;
+                            let _ = stdin.write_all(&bytes);
+                        }));
+                    }
+                    children.push((child, last));
+                }
+                Err(e) if last => result = Err(e),
+                // Like other shells, report the failed stage and let the rest of the pipeline run
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        drop(input);
+
+        for (mut child, last) in children {
+            let status = child.wait()?;
+            if last {
+                result = Ok(status_code(status));
+            }
+        }
+        for writer in writers {
+            let _ = writer.join();
+        }
+
+        result
+    }
+
+    /// Output of a builtin that can feed a pipe, or `None` for other builtins
+    fn builtin_output(&self, command: &Command) -> Option<Result<Vec<u8>>> {
+        let output = match command.name.as_str() {
+            "echo" => Ok(format!("{}\n", command.args.join(" "))),
+            "pwd" => env::current_dir()
+                .map(|pwd| format!("{}\n", pwd.display()))
+                .map_err(|e| Error::Config(format!("Failed to get current directory: {}", e))),
+            "printf" => Ok(match command.args.split_first() {
+                Some((format, args)) => format_printf(format, args),
+                None => String::new(),
+            }),
+            _ => return None,
+        };
+        Some(output.map(String::into_bytes))
+    }
+
+    /// Execute an external command, returning its exit status
+    fn execute_external(&self, command: &Command) -> Result<i32> {
+        let mut process = self.prepare_external(command)?;
+        let status = self.spawn(&mut process, command)?.wait()?;
+        Ok(status_code(status))
+    }
+
+    /// Start a prepared external command
+    fn spawn(&self, process: &mut ProcessCommand, command: &Command) -> Result<std::process::Child> {
+        match process.spawn() {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !command.name.contains('/') => {
+                Err(self.command_not_found(&command.name))
+            }
+            child => Ok(child?),
         }
     }
 
-    /// Execute an external command
-    fn execute_external(&self, command: &Command) -> Result<()> {
+    /// Build the process for an external command with its redirections applied
+    fn prepare_external(&self, command: &Command) -> Result<ProcessCommand> {
         use std::fs::OpenOptions;
         
         let mut process = ProcessCommand::new(&command.name);
         process.args(&command.args);
 
         // Set up stdin: file redirection takes precedence over pipe
-        if let Some(ref redir) = command.stdin_file {
-            match redir {
-                crate::command::Redirection::Input(file) => {
-                    if file.starts_with("<(") && file.ends_with(')') {
-                        // Process substitution: <(command)
-                        let cmd = &file[2..file.len()-1];
-                        use std::process::Command as ProcessCommand;
-                        let output = ProcessCommand::new("sh")
-                            .arg("-c")
-                            .arg(cmd)
-                            .output()
-                            .map_err(|e| Error::Config(format!("Process substitution failed: {}", e)))?;
-                        // Create a temporary file with the output
-                        use std::io::Write;
-                        let mut temp_file = tempfile::NamedTempFile::new()
-                            .map_err(|e| Error::Config(format!("Failed to create temp file: {}", e)))?;
-                        temp_file.write_all(&output.stdout)
-                            .map_err(|e| Error::Config(format!("Failed to write process output: {}", e)))?;
-                        let (_, temp_path) = temp_file.keep()
-                            .map_err(|e| Error::Config(format!("Failed to keep temp file: {}", e)))?;
-                        let file_handle = std::fs::File::open(&temp_path)
-                            .map_err(|e| Error::Config(format!("Failed to open temp file: {}", e)))?;
-                        process.stdin(Stdio::from(file_handle));
-                    } else if file.starts_with("<<<") {
-                        // Here-string - create a temporary approach
-                        let content = &file[3..];
-                        // For here-string, we'll use a temporary file approach
-                        // In a full implementation, we'd use a pipe
-                        use std::io::Write;
-                        let mut temp_file = tempfile::NamedTempFile::new()
-                            .map_err(|e| Error::Config(format!("Failed to create temp file: {}", e)))?;
-                        temp_file.write_all(content.as_bytes())
-                            .map_err(|e| Error::Config(format!("Failed to write here-string: {}", e)))?;
-                        let (_, temp_path) = temp_file.keep()
-                            .map_err(|e| Error::Config(format!("Failed to keep temp file: {}", e)))?;
-                        let file_handle = std::fs::File::open(&temp_path)
-                            .map_err(|e| Error::Config(format!("Failed to open temp file: {}", e)))?;
-                        process.stdin(Stdio::from(file_handle));
-                    } else if file.starts_with("<<-") {
-                        // Here-document with dash (strip leading tabs)
-                        // Simplified: treat as empty input for now
-                        // In full implementation, would read until delimiter and strip tabs
-                        process.stdin(Stdio::null());
-                    } else if file.starts_with("<<") {
-                        // Here-document
-                        let delimiter = &file[2..];
-                        let _quoted = delimiter.starts_with('\'') || delimiter.starts_with('"');
-                        // Simplified: treat as empty input for now
-                        // In full implementation, would read until delimiter
-                        // If quoted, no expansion; if not quoted, expand variables
-                        process.stdin(Stdio::null());
-                    } else {
-                        // Regular file input
-                        let file = std::fs::File::open(file)
-                            .map_err(|e| Error::Config(format!("Failed to open file: {}", e)))?;
-                        process.stdin(Stdio::from(file));
-                    }
-                }
-                _ => {
-                    process.stdin(Stdio::inherit());
-                }
+        if let Some(crate::command::Redirection::Input(file)) = &command.stdin_file {
+            if file.starts_with("<(") && file.ends_with(')') {
+                // Process substitution: <(command)
+                let cmd = &file[2..file.len()-1];
+                use std::process::Command as ProcessCommand;
+                let output = ProcessCommand::new("sh")
+                    .arg("-c")
+                    .arg(cmd)
+                    .output()
+                    .map_err(|e| Error::Config(format!("Process substitution failed: {}", e)))?;
+                // Create a temporary file with the output
+                use std::io::Write;
+                let mut temp_file = tempfile::NamedTempFile::new()
+                    .map_err(|e| Error::Config(format!("Failed to create temp file: {}", e)))?;
+                temp_file.write_all(&output.stdout)
+                    .map_err(|e| Error::Config(format!("Failed to write process output: {}", e)))?;
+                let (_, temp_path) = temp_file.keep()
+                    .map_err(|e| Error::Config(format!("Failed to keep temp file: {}", e)))?;
+                let file_handle = std::fs::File::open(&temp_path)
+                    .map_err(|e| Error::Config(format!("Failed to open temp file: {}", e)))?;
+                process.stdin(Stdio::from(file_handle));
+            } else if let Some(content) = file.strip_prefix("<<<") {
+                // Here-string - create a temporary approach
+                // For here-string, we'll use a temporary file approach
+                // In a full implementation, we'd use a pipe
+                use std::io::Write;
+                let mut temp_file = tempfile::NamedTempFile::new()
+                    .map_err(|e| Error::Config(format!("Failed to create temp file: {}", e)))?;
+                temp_file.write_all(content.as_bytes())
+                    .map_err(|e| Error::Config(format!("Failed to write here-string: {}", e)))?;
+                let (_, temp_path) = temp_file.keep()
+                    .map_err(|e| Error::Config(format!("Failed to keep temp file: {}", e)))?;
+                let file_handle = std::fs::File::open(&temp_path)
+                    .map_err(|e| Error::Config(format!("Failed to open temp file: {}", e)))?;
+                process.stdin(Stdio::from(file_handle));
+            } else {
+                // Regular file input
+                let file = std::fs::File::open(file)
+                    .map_err(|e| Error::Config(format!("Failed to open file: {}", e)))?;
+                process.stdin(Stdio::from(file));
             }
-        } else if command.stdin.is_some() {
-            process.stdin(Stdio::piped());
+        } else if let Some(crate::command::Redirection::HereDoc(body)) = &command.stdin_file {
+            process.stdin(here_document(body)?);
         } else {
             process.stdin(Stdio::inherit());
         }
@@ -162,19 +347,29 @@ impl Executor {
                             return Err(Error::Config("Failed to get subprocess stdin".to_string()));
                         }
                     } else {
-                        let file = std::fs::File::create(file)
-                            .map_err(|e| Error::Config(format!("Failed to create file: {}", e)))?;
-                        process.stdout(Stdio::from(file));
+                        let file = open_output(file, self.noclobber)?;
+                        let stdio = share_with_stderr(&mut process, command, file)?;
+                        process.stdout(stdio);
                     }
                 }
+                crate::command::Redirection::OutputClobber(file) => {
+                    let file = open_output(file, false)?;
+                    let stdio = share_with_stderr(&mut process, command, file)?;
+                    process.stdout(stdio);
+                }
                 crate::command::Redirection::Append(file) => {
                     let file = OpenOptions::new()
                         .create(true)
                         .append(true)
                         .open(file)
                         .map_err(|e| Error::Config(format!("Failed to open file: {}", e)))?;
-                    process.stdout(Stdio::from(file));
+                    let stdio = share_with_stderr(&mut process, command, file)?;
+                    process.stdout(stdio);
+                }
+                crate::command::Redirection::DupOriginal(2) => {
+                    process.stdout(dup_shell_fd(std::io::stderr().as_fd())?);
                 }
+                // `1>&2` is applied together with the stderr redirection below
                 _ => {}
             }
         } else {
@@ -184,24 +379,14 @@ impl Executor {
         // Handle stderr redirection
         if let Some(ref redir) = command.stderr {
             match redir {
-                crate::command::Redirection::FdDup(fd_from, fd_to) => {
-                    // File descriptor duplication: n>&m
-                    // In a full implementation, we'd use dup2
-                    // For now, map common cases
-                    if *fd_from == 2 && *fd_to == 1 {
-                        // 2>&1: redirect stderr to stdout (most common case)
-                        // This is handled by setting stderr to inherit from stdout
-                        // We'll set stderr after stdout is configured
-                    } else if *fd_from == 1 && *fd_to == 2 {
-                        // 1>&2: redirect stdout to stderr (less common)
-                        // This is handled by setting stdout to inherit from stderr
-                    }
-                    // Note: Full implementation would require spawning with proper FD handling
+                crate::command::Redirection::DupOriginal(1) => {
+                    process.stderr(dup_shell_fd(std::io::stdout().as_fd())?);
                 }
                 crate::command::Redirection::Error(file) | crate::command::Redirection::Combined(file) => {
                     let file = std::fs::File::create(file)
                         .map_err(|e| Error::Config(format!("Failed to create file: {}", e)))?;
-                    process.stderr(Stdio::from(file));
+                    let stdio = share_with_stdout(&mut process, command, file)?;
+                    process.stderr(stdio);
                 }
                 crate::command::Redirection::ErrorAppend(file) => {
                     let file = OpenOptions::new()
@@ -209,24 +394,29 @@ impl Executor {
                         .append(true)
                         .open(file)
                         .map_err(|e| Error::Config(format!("Failed to open file: {}", e)))?;
-                    process.stderr(Stdio::from(file));
+                    let stdio = share_with_stdout(&mut process, command, file)?;
+                    process.stderr(stdio);
                 }
+                // `2>&1` was applied together with the stdout redirection above
                 _ => {}
             }
         } else {
             process.stderr(Stdio::inherit());
         }
 
-        let status = process.status()?;
+        Ok(process)
+    }
 
-        if !status.success() {
-            return Err(Error::Config(format!(
-                "Command failed with exit code: {}",
-                status.code().unwrap_or(-1)
-            )));
+    /// A "command not found" error, with close matches when suggestions are enabled
+    fn command_not_found(&self, name: &str) -> Error {
+        let mut message = name.to_string();
+        if self.suggest_commands {
+            let suggestions = crate::completion::suggest_commands(name, &crate::completion::available_commands());
+            if !suggestions.is_empty() {
+                message.push_str(&format!("\nDid you mean: {}?", suggestions.join(", ")));
+            }
         }
-
-        Ok(())
+        Error::CommandNotFound(message)
     }
 
     fn builtin_cd(&self, command: &Command) -> Result<()> {
@@ -243,8 +433,8 @@ impl Executor {
         Ok(())
     }
 
-    fn builtin_exit(&self, _command: &Command) -> Result<()> {
-        std::process::exit(0);
+    fn builtin_exit(&self, command: &Command) -> Result<()> {
+        std::process::exit(exit_code_arg(command, 0)?);
     }
 
     fn builtin_pwd(&self) -> Result<()> {
@@ -283,42 +473,13 @@ impl Executor {
         Ok(())
     }
 
-    fn builtin_type(&self, command: &Command) -> Result<()> {
-        if let Some(cmd_name) = command.args.first() {
-            // Check if it's a builtin
-            let builtins = ["cd", "exit", "pwd", "echo", "export", "unset", "history",
-                "alias", "unalias", "type", "which", "source", "jobs", "fg", "bg",
-                "kill", "wait", "ulimit", "umask", "times", "pushd", "popd", "dirs",
-                "read", "printf", "test", "true", "false"];
-            
-            if builtins.contains(&cmd_name.as_str()) {
-                println!("{} is a shell builtin", cmd_name);
-                return Ok(());
-            }
-
-            // Check if it's in PATH
-            if let Ok(path) = std::env::var("PATH") {
-                for dir in path.split(':') {
-                    let full_path = std::path::Path::new(dir).join(cmd_name);
-                    if full_path.exists() && full_path.is_file() {
-                        println!("{} is {}", cmd_name, full_path.display());
-                        return Ok(());
-                    }
-                }
-            }
-
-            println!("{}: not found", cmd_name);
-        }
-        Ok(())
-    }
-
     fn builtin_which(&self, command: &Command) -> Result<()> {
         if let Some(cmd_name) = command.args.first() {
             // Check if it's a builtin
             let builtins = ["cd", "exit", "pwd", "echo", "export", "unset", "history",
                 "alias", "unalias", "type", "which", "source", "jobs", "fg", "bg",
                 "kill", "wait", "ulimit", "umask", "times", "pushd", "popd", "dirs",
-                "read", "printf", "test", "true", "false"];
+                "read", "printf", "test", "true", "false", "let", "set"];
             
             if builtins.contains(&cmd_name.as_str()) {
                 println!("{}: shell builtin command", cmd_name);
@@ -342,20 +503,29 @@ impl Executor {
         Ok(())
     }
 
-    fn builtin_read(&self, command: &Command) -> Result<()> {
-        use std::io::{self, BufRead};
-        let var_name = command.args.first().ok_or_else(|| {
-            Error::Config("read: variable name required".to_string())
-        })?;
+    fn builtin_read(&self, command: &Command) -> Result<i32> {
+        let options = parse_read_args(&command.args)?;
 
-        let stdin = io::stdin();
-        let mut line = String::new();
-        stdin.lock().read_line(&mut line)
-            .map_err(|e| Error::Config(format!("read: failed to read input: {}", e)))?;
-        
-        let value = line.trim_end_matches('\n').trim_end_matches('\r');
-        std::env::set_var(var_name, value);
-        Ok(())
+        #[cfg(unix)]
+        let outcome = {
+            use std::os::unix::io::AsRawFd;
+            read_input(std::io::stdin().as_raw_fd(), &mut std::io::stderr(), &options)
+        };
+        #[cfg(not(unix))]
+        let outcome = read_input(&mut std::io::stdin().lock(), &mut std::io::stderr(), &options);
+
+        match outcome.map_err(|e| Error::Config(format!("read: failed to read input: {}", e)))? {
+            ReadOutcome::Line(value) => {
+                std::env::set_var(&options.var_name, value);
+                Ok(0)
+            }
+            // Like bash, keep what came before the end of input but report failure
+            ReadOutcome::Eof(value) => {
+                std::env::set_var(&options.var_name, value);
+                Ok(1)
+            }
+            ReadOutcome::TimedOut => Err(Error::Config("read: timed out".to_string())),
+        }
     }
 
     fn builtin_printf(&self, command: &Command) -> Result<()> {
@@ -363,46 +533,11 @@ impl Executor {
             return Ok(());
         }
 
-        let format_str = &command.args[0];
-        let mut args = command.args.iter().skip(1);
-        let mut output = String::new();
-        let mut chars = format_str.chars().peekable();
-
-        while let Some(ch) = chars.next() {
-            if ch == '%' {
-                if let Some(next) = chars.next() {
-                    match next {
-                        's' => {
-                            if let Some(arg) = args.next() {
-                                output.push_str(arg);
-                            }
-                        }
-                        'd' | 'i' => {
-                            if let Some(arg) = args.next() {
-                                if let Ok(num) = arg.parse::<i64>() {
-                                    output.push_str(&num.to_string());
-                                } else {
-                                    output.push_str(arg);
-                                }
-                            }
-                        }
-                        '%' => output.push('%'),
-                        _ => {
-                            output.push('%');
-                            output.push(next);
-                        }
-                    }
-                }
-            } else {
-                output.push(ch);
-            }
-        }
-
-        print!("{}", output);
+        print!("{}", format_printf(&command.args[0], &command.args[1..]));
         Ok(())
     }
 
-    fn builtin_test(&self, command: &Command) -> Result<()> {
+    fn builtin_test(&self, command: &Command) -> Result<i32> {
         // Simple test implementation - basic file tests
         if command.args.is_empty() {
             return Err(Error::Config("test: insufficient arguments".to_string()));
@@ -426,9 +561,9 @@ impl Executor {
             "-f" => {
                 if let Some(p) = path {
                     if std::path::Path::new(p).is_file() {
-                        Ok(())
+                        Ok(0)
                     } else {
-                        Err(Error::Config("test failed".to_string()))
+                        Ok(1)
                     }
                 } else {
                     Err(Error::Config("test: path required".to_string()))
@@ -437,9 +572,9 @@ impl Executor {
             "-d" => {
                 if let Some(p) = path {
                     if std::path::Path::new(p).is_dir() {
-                        Ok(())
+                        Ok(0)
                     } else {
-                        Err(Error::Config("test failed".to_string()))
+                        Ok(1)
                     }
                 } else {
                     Err(Error::Config("test: path required".to_string()))
@@ -448,9 +583,9 @@ impl Executor {
             "-e" => {
                 if let Some(p) = path {
                     if std::path::Path::new(p).exists() {
-                        Ok(())
+                        Ok(0)
                     } else {
-                        Err(Error::Config("test failed".to_string()))
+                        Ok(1)
                     }
                 } else {
                     Err(Error::Config("test: path required".to_string()))
@@ -461,9 +596,9 @@ impl Executor {
                 if args.len() >= 3 && args[1] == "=" {
                     let result = args[0] == args[2];
                     if result {
-                        Ok(())
+                        Ok(0)
                     } else {
-                        Err(Error::Config("test failed".to_string()))
+                        Ok(1)
                     }
                 } else {
                     Err(Error::Config(format!("test: unknown operator: {}", op)))
@@ -504,6 +639,16 @@ impl Executor {
         Ok(())
     }
 
+    fn builtin_let(&self, command: &Command) -> Result<()> {
+        if command.args.is_empty() {
+            return Err(Error::Config("let: expression expected".to_string()));
+        }
+        for expr in &command.args {
+            crate::parser::Parser::evaluate_arithmetic_statement(expr)?;
+        }
+        Ok(())
+    }
+
     fn builtin_times(&self) -> Result<()> {
         // Simple times implementation
         println!("0.00s user 0.00s system");
@@ -511,6 +656,418 @@ impl Executor {
     }
 }
 
+/// Expand a printf format string, reusing it while arguments remain (like bash)
+fn format_printf(format: &str, args: &[String]) -> String {
+    let mut output = String::new();
+    let mut args = args.iter().peekable();
+
+    loop {
+        let mut consumed = false;
+        let mut chars = format.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                output.push(ch);
+                continue;
+            }
+            if chars.peek() == Some(&'%') {
+                chars.next();
+                output.push('%');
+                continue;
+            }
+
+            // %[flags][width][.precision]conversion
+            let mut spec = PrintfSpec::default();
+            while let Some(&flag) = chars.peek() {
+                match flag {
+                    '-' => spec.left = true,
+                    '0' => spec.zero = true,
+                    '+' => spec.plus = true,
+                    '#' => spec.alternate = true,
+                    ' ' => {}
+                    _ => break,
+                }
+                chars.next();
+            }
+            spec.width = take_number(&mut chars);
+            if chars.peek() == Some(&'.') {
+                chars.next();
+                spec.precision = Some(take_number(&mut chars).unwrap_or(0));
+            }
+
+            let Some(conversion) = chars.next() else {
+                output.push('%');
+                break;
+            };
+            if !"sdixXofF".contains(conversion) {
+                output.push('%');
+                output.push(conversion);
+                continue;
+            }
+
+            let arg = args.next();
+            consumed |= arg.is_some();
+            output.push_str(&spec.format(conversion, arg.map(String::as_str)));
+        }
+
+        if !consumed || args.peek().is_none() {
+            break;
+        }
+    }
+
+    output
+}
+
+/// Parse a run of decimal digits
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+    digits.parse().ok()
+}
+
+/// A parsed printf conversion specification
+#[derive(Debug, Default)]
+struct PrintfSpec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    alternate: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+impl PrintfSpec {
+    /// Format one argument (missing arguments count as empty / zero)
+    fn format(&self, conversion: char, arg: Option<&str>) -> String {
+        let arg = arg.unwrap_or("");
+        let integer = || -> Option<i64> {
+            let trimmed = arg.trim();
+            if trimmed.is_empty() {
+                Some(0)
+            } else {
+                trimmed.trim_start_matches('+').parse().ok()
+            }
+        };
+
+        let (sign, body) = match conversion {
+            's' => {
+                let text = match self.precision {
+                    Some(precision) => arg.chars().take(precision).collect(),
+                    None => arg.to_string(),
+                };
+                return self.pad("", &text, false);
+            }
+            'd' | 'i' => match integer() {
+                Some(num) => (self.sign(num < 0), num.unsigned_abs().to_string()),
+                // Not a number: print it as-is
+                None => return self.pad("", arg, false),
+            },
+            'x' | 'X' | 'o' => {
+                // Negative numbers print as their two's complement, as in C
+                let num = integer().unwrap_or(0) as u64;
+                let body = match conversion {
+                    'x' => format!("{:x}", num),
+                    'X' => format!("{:X}", num),
+                    _ => format!("{:o}", num),
+                };
+                let prefix = match conversion {
+                    _ if !self.alternate || num == 0 => "",
+                    'x' => "0x",
+                    'X' => "0X",
+                    _ => "0",
+                };
+                (prefix.to_string(), body)
+            }
+            _ => {
+                let num: f64 = arg.trim().parse().unwrap_or(0.0);
+                let precision = self.precision.unwrap_or(6);
+                (self.sign(num.is_sign_negative() && num != 0.0), format!("{:.*}", precision, num.abs()))
+            }
+        };
+
+        self.pad(&sign, &body, true)
+    }
+
+    fn sign(&self, negative: bool) -> String {
+        if negative {
+            "-".to_string()
+        } else if self.plus {
+            "+".to_string()
+        } else {
+            String::new()
+        }
+    }
+
+    /// Pad `prefix` + `body` to the field width; zero padding goes after the prefix
+    fn pad(&self, prefix: &str, body: &str, numeric: bool) -> String {
+        let len = prefix.chars().count() + body.chars().count();
+        let fill = self.width.unwrap_or(0).saturating_sub(len);
+        if self.left {
+            format!("{}{}{}", prefix, body, " ".repeat(fill))
+        } else if self.zero && numeric {
+            format!("{}{}{}", prefix, "0".repeat(fill), body)
+        } else {
+            format!("{}{}{}", " ".repeat(fill), prefix, body)
+        }
+    }
+}
+
+/// Options of the `read` builtin (`read [-s] [-p prompt] [-t seconds] name`)
+#[derive(Debug, Default)]
+struct ReadOptions {
+    prompt: Option<String>,
+    timeout: Option<std::time::Duration>,
+    silent: bool,
+    var_name: String,
+}
+
+fn parse_read_args(args: &[String]) -> Result<ReadOptions> {
+    let mut options = ReadOptions::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" => {
+                let prompt = args
+                    .next()
+                    .ok_or_else(|| Error::Config("read: -p: option requires an argument".to_string()))?;
+                options.prompt = Some(prompt.clone());
+            }
+            "-t" => {
+                let seconds = args
+                    .next()
+                    .ok_or_else(|| Error::Config("read: -t: option requires an argument".to_string()))?;
+                let seconds: f64 = seconds
+                    .parse()
+                    .ok()
+                    .filter(|s: &f64| s.is_finite() && *s >= 0.0)
+                    .ok_or_else(|| Error::Config(format!("read: {}: invalid timeout specification", seconds)))?;
+                options.timeout = Some(std::time::Duration::from_secs_f64(seconds));
+            }
+            "-s" => options.silent = true,
+            "-r" => {} // Backslashes are never interpreted
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(Error::Config(format!("read: {}: invalid option", arg)));
+            }
+            _ => {
+                options.var_name = arg.clone();
+                break;
+            }
+        }
+    }
+
+    if options.var_name.is_empty() {
+        return Err(Error::Config("read: variable name required".to_string()));
+    }
+    Ok(options)
+}
+
+/// What the `read` builtin got from its input
+#[derive(Debug, PartialEq)]
+enum ReadOutcome {
+    /// A full line, without its line ending
+    Line(String),
+    /// End of input before a newline, with the text read up to it
+    Eof(String),
+    /// The `-t` timeout expired before a full line arrived
+    TimedOut,
+}
+
+impl ReadOutcome {
+    fn from_bytes(line: Vec<u8>, complete: bool) -> Self {
+        let line = String::from_utf8_lossy(&line).trim_end_matches('\r').to_string();
+        if complete {
+            Self::Line(line)
+        } else {
+            Self::Eof(line)
+        }
+    }
+}
+
+/// Read one line from `fd`, writing the prompt to `out` first
+#[cfg(unix)]
+fn read_input(
+    fd: std::os::unix::io::RawFd,
+    out: &mut dyn std::io::Write,
+    options: &ReadOptions,
+) -> std::io::Result<ReadOutcome> {
+    if let Some(prompt) = &options.prompt {
+        out.write_all(prompt.as_bytes())?;
+        out.flush()?;
+    }
+
+    let _echo_guard = if options.silent { EchoGuard::disable(fd) } else { None };
+    let deadline = options.timeout.map(|timeout| std::time::Instant::now() + timeout);
+    let mut line = Vec::new();
+
+    let result = loop {
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+            let ready = unsafe { libc::poll(&mut pollfd, 1, remaining.as_millis().min(i32::MAX as u128) as i32) };
+            if ready < 0 {
+                let err = std::io::Error::last_os_error();
+                if err.kind() == std::io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            if ready == 0 {
+                break ReadOutcome::TimedOut;
+            }
+        }
+
+        let mut byte = 0u8;
+        let n = unsafe { libc::read(fd, &mut byte as *mut u8 as *mut libc::c_void, 1) };
+        if n < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if n == 0 || byte == b'\n' {
+            break ReadOutcome::from_bytes(std::mem::take(&mut line), n != 0);
+        }
+        line.push(byte);
+    };
+
+    if options.silent {
+        // The newline typed by the user was not echoed
+        out.write_all(b"\n")?;
+    }
+
+    Ok(result)
+}
+
+/// Read one line from `input`, writing the prompt to `out` first
+///
+/// `-t` and `-s` need a Unix terminal and are rejected here.
+#[cfg(not(unix))]
+fn read_input(
+    input: &mut dyn std::io::BufRead,
+    out: &mut dyn std::io::Write,
+    options: &ReadOptions,
+) -> std::io::Result<ReadOutcome> {
+    if options.timeout.is_some() || options.silent {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "-t and -s are only supported on Unix",
+        ));
+    }
+    if let Some(prompt) = &options.prompt {
+        out.write_all(prompt.as_bytes())?;
+        out.flush()?;
+    }
+
+    let mut line = Vec::new();
+    input.read_until(b'\n', &mut line)?;
+    let complete = line.last() == Some(&b'\n');
+    if complete {
+        line.pop();
+    }
+    Ok(ReadOutcome::from_bytes(line, complete))
+}
+
+/// Turns terminal echo off for its lifetime
+#[cfg(unix)]
+struct EchoGuard {
+    fd: std::os::unix::io::RawFd,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl EchoGuard {
+    /// Disable echo on `fd`; `None` if it isn't a terminal
+    fn disable(fd: std::os::unix::io::RawFd) -> Option<Self> {
+        unsafe {
+            if libc::isatty(fd) != 1 {
+                return None;
+            }
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(fd, &mut original) != 0 {
+                return None;
+            }
+            let mut silent = original;
+            silent.c_lflag &= !libc::ECHO;
+            if libc::tcsetattr(fd, libc::TCSANOW, &silent) != 0 {
+                return None;
+            }
+            Some(Self { fd, original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(self.fd, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Point stderr at stdout's file too when the command has `2>&1`
+fn share_with_stderr(process: &mut ProcessCommand, command: &Command, file: std::fs::File) -> Result<Stdio> {
+    if matches!(command.stderr, Some(crate::command::Redirection::FdDup(2, 1))) {
+        process.stderr(Stdio::from(file.try_clone()?));
+    }
+    Ok(Stdio::from(file))
+}
+
+/// Point stdout at stderr's file too when the command has `1>&2`
+fn share_with_stdout(process: &mut ProcessCommand, command: &Command, file: std::fs::File) -> Result<Stdio> {
+    if matches!(command.stdout, Some(crate::command::Redirection::FdDup(1, 2))) {
+        process.stdout(Stdio::from(file.try_clone()?));
+    }
+    Ok(Stdio::from(file))
+}
+
+/// Stdin reading a here-document body, from an unlinked temp file so large bodies can't block
+fn here_document(body: &str) -> Result<Stdio> {
+    use std::io::Write;
+    let mut temp_file = tempfile::NamedTempFile::new()
+        .map_err(|e| Error::Config(format!("Failed to create temp file: {}", e)))?;
+    temp_file.write_all(body.as_bytes())
+        .map_err(|e| Error::Config(format!("Failed to write here-document: {}", e)))?;
+    let file = temp_file.reopen()
+        .map_err(|e| Error::Config(format!("Failed to open temp file: {}", e)))?;
+    Ok(Stdio::from(file))
+}
+
+/// Hand the shell's own stdout or stderr to a child
+fn dup_shell_fd(fd: std::os::fd::BorrowedFd<'_>) -> Result<Stdio> {
+    Ok(Stdio::from(fd.try_clone_to_owned()?))
+}
+
+/// Open a `>` redirection target, refusing to truncate an existing regular file under noclobber
+fn open_output(file: &str, noclobber: bool) -> Result<std::fs::File> {
+    let path = std::path::Path::new(file);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true);
+    if !noclobber {
+        options.create(true).truncate(true);
+    } else if path.exists() && !path.is_file() {
+        // Devices like /dev/null stay writable, as in bash
+    } else {
+        options.create_new(true);
+    }
+
+    options.open(path).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::AlreadyExists {
+            Error::Config(format!("{}: cannot overwrite existing file", file))
+        } else {
+            Error::Config(format!("Failed to create file: {}", e))
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -529,6 +1086,48 @@ mod tests {
         assert!(executor.execute(&command).is_ok());
     }
 
+    #[test]
+    fn test_disabled_builtin_runs_external_command() {
+        let mut executor = Executor::new();
+        let ignored = executor.set_disabled_builtins(&["echo".to_string(), "cd".to_string()]);
+        assert_eq!(ignored, vec!["cd"]);
+        assert!(!executor.is_builtin("echo"));
+        assert!(executor.is_builtin("cd"));
+
+        // The echo builtin ignores redirections, so output in the file came from the external echo
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out.txt");
+        let command = Command {
+            name: "echo".to_string(),
+            args: vec!["hello".to_string()],
+            stdin: None,
+            stdout: Some(crate::command::Redirection::Output(output.to_string_lossy().into_owned())),
+            stderr: None,
+            stdin_file: None,
+        };
+        executor.execute(&command).unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "hello\n");
+    }
+
+    #[test]
+    fn test_exit_status_for_missing_command() {
+        let executor = Executor::new();
+        let command = Command {
+            name: "ziron-no-such-command".to_string(),
+            args: vec![],
+            stdin: None,
+            stdout: None,
+            stderr: None,
+            stdin_file: None,
+        };
+        let err = executor.execute(&command).unwrap_err();
+        assert!(matches!(err, Error::CommandNotFound(_)), "{}", err);
+        assert_eq!(exit_status(&err), 127);
+
+        // Other errors mentioning the phrase are ordinary failures
+        assert_eq!(exit_status(&Error::Config("x: command not found".to_string())), 1);
+    }
+
     #[test]
     fn test_builtin_true_false() {
         let executor = Executor::new();
@@ -550,7 +1149,112 @@ mod tests {
             stderr: None,
             stdin_file: None,
         };
-        assert!(executor.execute(&false_cmd).is_err());
+        assert_eq!(executor.execute(&false_cmd).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_builtin_let_increment() {
+        let executor = Executor::new();
+        std::env::set_var("ZIRON_LET_X", "41");
+        let command = Command {
+            name: "let".to_string(),
+            args: vec!["ZIRON_LET_X = ZIRON_LET_X + 1".to_string()],
+            stdin: None,
+            stdout: None,
+            stderr: None,
+            stdin_file: None,
+        };
+        assert!(executor.execute(&command).is_ok());
+        assert_eq!(std::env::var("ZIRON_LET_X").unwrap(), "42");
+    }
+
+    fn printf(format: &str, args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        format_printf(format, &args)
+    }
+
+    #[test]
+    fn test_printf_hex_and_octal() {
+        assert_eq!(printf("%x %X %o", &["255", "255", "8"]), "ff FF 10");
+        assert_eq!(printf("%#x %#o", &["255", "8"]), "0xff 010");
+    }
+
+    #[test]
+    fn test_printf_float_precision() {
+        assert_eq!(printf("%.2f", &["3.14159"]), "3.14");
+        assert_eq!(printf("%f", &["1.5"]), "1.500000");
+        assert_eq!(printf("%8.3f|", &["-2.5"]), "  -2.500|");
+    }
+
+    #[test]
+    fn test_printf_width_padding() {
+        assert_eq!(printf("[%5d]", &["42"]), "[   42]");
+        assert_eq!(printf("[%-5d]", &["42"]), "[42   ]");
+        assert_eq!(printf("[%05d]", &["-42"]), "[-0042]");
+        assert_eq!(printf("[%6s][%.2s]", &["abc", "abcdef"]), "[   abc][ab]");
+        assert_eq!(printf("100%%", &[]), "100%");
+    }
+
+    #[test]
+    fn test_printf_recycles_format() {
+        assert_eq!(printf("%s=%d\n", &["a", "1", "b", "2", "c"]), "a=1\nb=2\nc=0\n");
+        assert_eq!(printf("x", &["unused"]), "x");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_prompt() {
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+
+        let (mut writer, reader) = UnixStream::pair().unwrap();
+        writer.write_all(b"alice\n").unwrap();
+
+        let args: Vec<String> = ["-p", "Name: ", "NAME"].iter().map(|s| s.to_string()).collect();
+        let options = parse_read_args(&args).unwrap();
+        let mut out = Vec::new();
+        let value = read_input(reader.as_raw_fd(), &mut out, &options).unwrap();
+
+        assert_eq!(value, ReadOutcome::Line("alice".to_string()));
+        assert_eq!(out, b"Name: ");
+        assert_eq!(options.var_name, "NAME");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_timeout() {
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+
+        let (_writer, reader) = UnixStream::pair().unwrap();
+        let args: Vec<String> = ["-t", "0.1", "ANSWER"].iter().map(|s| s.to_string()).collect();
+        let options = parse_read_args(&args).unwrap();
+
+        let start = std::time::Instant::now();
+        let value = read_input(reader.as_raw_fd(), &mut Vec::new(), &options).unwrap();
+        assert_eq!(value, ReadOutcome::TimedOut);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+        assert!(parse_read_args(&["-t".to_string(), "soon".to_string(), "X".to_string()]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_end_of_input() {
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+
+        let options = parse_read_args(&["LINE".to_string()]).unwrap();
+        let (mut writer, reader) = UnixStream::pair().unwrap();
+        writer.write_all(b"partial").unwrap();
+        drop(writer);
+
+        let value = read_input(reader.as_raw_fd(), &mut Vec::new(), &options).unwrap();
+        assert_eq!(value, ReadOutcome::Eof("partial".to_string()));
+        let value = read_input(reader.as_raw_fd(), &mut Vec::new(), &options).unwrap();
+        assert_eq!(value, ReadOutcome::Eof(String::new()));
     }
 
     #[test]
@@ -584,5 +1288,116 @@ mod tests {
         let content = std::fs::read_to_string(&output_path).unwrap();
         assert_eq!(content.trim(), "test");
     }
-}
 
+    #[test]
+    fn test_noclobber_refuses_overwrite() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("existing.txt");
+        std::fs::write(&output_path, "keep\n").unwrap();
+        let output_path = output_path.to_str().unwrap().to_string();
+
+        let mut executor = Executor::new();
+        executor.set_noclobber(true);
+        let command = |redirection: crate::command::Redirection| Command {
+            name: "sh".to_string(),
+            args: vec!["-c".to_string(), "echo new".to_string()],
+            stdin: None,
+            stdout: Some(redirection),
+            stderr: None,
+            stdin_file: None,
+        };
+
+        let err = executor
+            .execute(&command(crate::command::Redirection::Output(output_path.clone())))
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot overwrite existing file"), "{}", err);
+        assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "keep\n");
+
+        // New files and devices are still fine
+        let new_path = temp_dir.path().join("new.txt").to_str().unwrap().to_string();
+        assert!(executor.execute(&command(crate::command::Redirection::Output(new_path))).is_ok());
+        assert!(executor.execute(&command(crate::command::Redirection::Output("/dev/null".to_string()))).is_ok());
+    }
+
+    #[test]
+    fn test_clobber_override() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("existing.txt");
+        std::fs::write(&output_path, "keep\n").unwrap();
+        let output_path = output_path.to_str().unwrap().to_string();
+
+        let mut executor = Executor::new();
+        executor.set_noclobber(true);
+        let commands = crate::parser::Parser::parse(&format!("sh -c 'echo new' >| {}", output_path)).unwrap();
+        assert_eq!(commands.len(), 1);
+        assert!(matches!(&commands[0].stdout, Some(crate::command::Redirection::OutputClobber(path)) if *path == output_path));
+
+        executor.execute(&commands[0]).unwrap();
+        assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "new\n");
+    }
+
+    #[test]
+    fn test_fd_dup_follows_redirection_order() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let executor = Executor::new();
+        let script = "sh -c 'echo out; echo err >&2'";
+
+        let both = temp_dir.path().join("both.txt").to_str().unwrap().to_string();
+        let commands = crate::parser::Parser::parse(&format!("{} > {} 2>&1", script, both)).unwrap();
+        assert!(matches!(commands[0].stderr, Some(crate::command::Redirection::FdDup(2, 1))));
+        executor.execute(&commands[0]).unwrap();
+        assert_eq!(std::fs::read_to_string(&both).unwrap(), "out\nerr\n");
+
+        // Duplicating first keeps stderr on the original stdout
+        let only_out = temp_dir.path().join("out.txt").to_str().unwrap().to_string();
+        let commands = crate::parser::Parser::parse(&format!("{} 2>&1 > {}", script, only_out)).unwrap();
+        assert!(matches!(commands[0].stderr, Some(crate::command::Redirection::DupOriginal(1))));
+        assert_eq!(commands[0].args, vec!["-c", "echo out; echo err >&2"]);
+        executor.execute(&commands[0]).unwrap();
+        assert_eq!(std::fs::read_to_string(&only_out).unwrap(), "out\n");
+
+        let commands = crate::parser::Parser::parse(&format!("{} 2> {} 1>&2", script, both)).unwrap();
+        executor.execute(&commands[0]).unwrap();
+        assert_eq!(std::fs::read_to_string(&both).unwrap(), "out\nerr\n");
+    }
+
+    fn command(name: &str, args: &[&str]) -> Command {
+        Command {
+            name: name.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            stdin: None,
+            stdout: None,
+            stderr: None,
+            stdin_file: None,
+        }
+    }
+
+    #[test]
+    fn test_pipeline_connects_stages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out.txt");
+        let executor = Executor::new();
+
+        // Builtin output feeds the next stage
+        let mut tr = command("tr", &["a-z", "A-Z"]);
+        tr.stdout = Some(crate::command::Redirection::Output(output.to_string_lossy().into_owned()));
+        executor.execute_pipeline(&[command("echo", &["hello"]), tr.clone()]).unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "HELLO\n");
+
+        // External stages pipe into each other
+        executor
+            .execute_pipeline(&[command("sh", &["-c", "echo b; echo a"]), command("sort", &[]), tr])
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "A\nB\n");
+    }
+
+    #[test]
+    fn test_pipeline_status_is_last_stage() {
+        let executor = Executor::new();
+        assert_eq!(executor.execute_pipeline(&[command("false", &[]), command("true", &[])]).unwrap(), 0);
+        assert_eq!(executor.execute_pipeline(&[command("true", &[]), command("sh", &["-c", "exit 3"])]).unwrap(), 3);
+    }
+}
@@ -0,0 +1,119 @@
+//! Ctrl-R history search
+
+use rustyline::history::History;
+use rustyline::{Cmd, ConditionalEventHandler, Event, EventContext, KeyEvent, Movement, RepeatCount};
+use std::sync::{Arc, Mutex};
+use ziron_core::config::HistorySearch;
+
+/// Bind Ctrl-R to the configured history search
+///
+/// Fuzzy search needs the history entries, which key handlers can't reach through the editor,
+/// so they are shared through `entries`.
+pub fn bind_history_search<H: rustyline::Helper, I: History>(
+    editor: &mut rustyline::Editor<H, I>,
+    mode: HistorySearch,
+    entries: Arc<Mutex<Vec<String>>>,
+) {
+    let handler = match mode {
+        HistorySearch::Substring => rustyline::EventHandler::Simple(Cmd::ReverseSearchHistory),
+        HistorySearch::Fuzzy => rustyline::EventHandler::Conditional(Box::new(FuzzyHistorySearch::new(entries))),
+    };
+    editor.bind_sequence(KeyEvent::ctrl('R'), handler);
+}
+
+/// Ctrl-R handler replacing the line with the latest history entry fuzzily matching it
+///
+/// Pressing Ctrl-R again steps to older matches of the same query. On an empty line the
+/// regular reverse-i-search runs instead.
+pub struct FuzzyHistorySearch {
+    entries: Arc<Mutex<Vec<String>>>,
+    state: Mutex<SearchState>,
+}
+
+#[derive(Default)]
+struct SearchState {
+    query: String,
+    skip: usize,
+    last_match: Option<String>,
+}
+
+impl FuzzyHistorySearch {
+    pub fn new(entries: Arc<Mutex<Vec<String>>>) -> Self {
+        Self {
+            entries,
+            state: Mutex::new(SearchState::default()),
+        }
+    }
+}
+
+impl ConditionalEventHandler for FuzzyHistorySearch {
+    fn handle(&self, _evt: &Event, _n: RepeatCount, _positive: bool, ctx: &EventContext) -> Option<Cmd> {
+        let line = ctx.line();
+        if line.is_empty() {
+            return None;
+        }
+
+        let mut state = self.state.lock().ok()?;
+        // Continue the previous search while the line still shows its last match
+        if state.last_match.as_deref() == Some(line) {
+            state.skip += 1;
+        } else {
+            state.query = line.to_string();
+            state.skip = 0;
+        }
+
+        let entries = self.entries.lock().ok()?;
+        match find_match(&entries, &state.query, state.skip) {
+            Some(entry) => {
+                state.last_match = Some(entry.clone());
+                Some(Cmd::Replace(Movement::WholeLine, Some(entry)))
+            }
+            None => {
+                // Past the oldest match; stay on the current one
+                state.skip = state.skip.saturating_sub(1);
+                Some(Cmd::Noop)
+            }
+        }
+    }
+}
+
+/// The `skip`-th most recent distinct entry fuzzily matching `query`
+pub fn find_match(entries: &[String], query: &str, skip: usize) -> Option<String> {
+    let mut seen = std::collections::HashSet::new();
+    entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.as_str() != query && fuzzy_matches(query, entry))
+        .filter(|entry| seen.insert(entry.as_str()))
+        .nth(skip)
+        .cloned()
+}
+
+/// Whether all characters of `query` appear in `entry` in order (case-insensitive)
+fn fuzzy_matches(query: &str, entry: &str) -> bool {
+    let mut chars = entry.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|q| chars.any(|c| c == q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_find_match() {
+        let entries: Vec<String> = ["git status", "cargo build", "git stash", "git status", "ls"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(find_match(&entries, "gst", 0), Some("git status".to_string()));
+        assert_eq!(find_match(&entries, "gst", 1), Some("git stash".to_string()));
+        assert_eq!(find_match(&entries, "gst", 2), None);
+        assert_eq!(find_match(&entries, "CB", 0), Some("cargo build".to_string()));
+        assert_eq!(find_match(&entries, "xyz", 0), None);
+    }
+}
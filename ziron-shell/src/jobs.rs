@@ -85,6 +85,23 @@ impl JobManager {
         }
     }
 
+    /// Mark running jobs whose process has exited as done, reaping the process
+    #[cfg(unix)]
+    pub fn reap_finished(&self) {
+        let mut jobs = self.jobs.lock().unwrap();
+        for job in jobs.iter_mut().filter(|j| j.status == JobStatus::Running) {
+            let mut status = 0;
+            let pid = unsafe { libc::waitpid(job.pid as libc::pid_t, &mut status, libc::WNOHANG) };
+            if pid == job.pid as libc::pid_t {
+                let code = libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status));
+                job.status = JobStatus::Done(code);
+            } else if pid < 0 {
+                // Not our child any more, so it was already reaped
+                job.status = JobStatus::Done(None);
+            }
+        }
+    }
+
     pub fn remove_job(&self, id: usize) {
         let mut jobs = self.jobs.lock().unwrap();
         jobs.retain(|j| j.id != id);
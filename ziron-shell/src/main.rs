@@ -8,6 +8,7 @@ use ziron_core::theme::Theme;
 mod command;
 mod completion;
 mod executor;
+mod history;
 mod jobs;
 mod parser;
 mod shell;
@@ -19,13 +20,18 @@ fn main() -> Result<()> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
-    // Load configuration
-    let config = Config::load().unwrap_or_default();
+    // Load configuration; $HISTFILE and $HISTSIZE take precedence over it
+    let mut config = Config::load().unwrap_or_default();
+    config.apply_env_overrides();
 
-    // Load theme
-    let theme = if let Some(theme_name) = &config.theme {
-        let theme_path = std::path::PathBuf::from("themes").join(theme_name).join("theme.toml");
-        Theme::load_from(&theme_path)?
+    // `-c <command>` (or `--once <command>`) runs one command line and exits with its status
+    let once = once_command(std::env::args().skip(1));
+
+    // Load theme; `-c` never shows a prompt, so it doesn't need one
+    let theme = if once.is_some() {
+        Theme::default()
+    } else if let Some(theme_name) = &config.theme {
+        Theme::load_from(&Theme::path_for(theme_name))?
     } else {
         Theme::load_from(&Theme::default_path()?)?
     };
@@ -35,9 +41,22 @@ fn main() -> Result<()> {
     // Create shell instance
     let mut shell = ZironShell::new(config, renderer)?;
 
-    // Run shell
-    shell.run()?;
+    if let Some(line) = once {
+        std::process::exit(shell.run_once(&line));
+    }
+
+    // Run shell, exiting with the last command's status
+    let code = shell.run()?;
+    std::process::exit(code);
+}
 
-    Ok(())
+/// The command line passed with `-c` or `--once`, if any
+fn once_command(mut args: impl Iterator<Item = String>) -> Option<String> {
+    while let Some(arg) = args.next() {
+        if arg == "-c" || arg == "--once" {
+            return args.next();
+        }
+    }
+    None
 }
 
@@ -1,12 +1,47 @@
 //! Command parser with advanced features
 
-use crate::command::{Command, Redirection};
+use std::collections::VecDeque;
+
+use crate::command::{Command, ListOperator, Pipeline, PipelineList, Redirection};
 use ziron_core::error::{Error, Result};
 
+/// Default field separators for word splitting
+pub const DEFAULT_IFS: &str = " \t\n";
+
+/// Limits that stop pathological inputs from recursing or expanding without bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpansionLimits {
+    /// Maximum nesting of brace expansions and command substitutions
+    pub max_depth: usize,
+    /// Maximum number of words a single brace expansion may generate
+    pub max_words: usize,
+    /// How long a command substitution may run before it is killed
+    pub substitution_timeout: std::time::Duration,
+    /// Maximum bytes of output captured from a command substitution
+    pub max_substitution_output: usize,
+}
+
+impl Default for ExpansionLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 32,
+            max_words: 10_000,
+            substitution_timeout: std::time::Duration::from_secs(10),
+            max_substitution_output: 1024 * 1024,
+        }
+    }
+}
+
 /// Context for variable expansion (script arguments, etc.)
 pub struct ExpansionContext {
     pub script_args: Vec<String>,
     pub last_exit_code: Option<i32>,
+    /// Field separators used to split unquoted expansions ($IFS)
+    pub ifs: String,
+    /// Exit status of the last command substitution run while expanding
+    pub substitution_status: std::cell::Cell<Option<i32>>,
+    /// Expansion depth and size limits
+    pub limits: ExpansionLimits,
 }
 
 impl Default for ExpansionContext {
@@ -14,10 +49,41 @@ impl Default for ExpansionContext {
         Self {
             script_args: Vec::new(),
             last_exit_code: None,
+            ifs: DEFAULT_IFS.to_string(),
+            substitution_status: std::cell::Cell::new(None),
+            limits: ExpansionLimits::default(),
         }
     }
 }
 
+/// C-style arithmetic for loop: `for (( init; cond; step )); do body; done`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArithFor {
+    pub init: String,
+    pub condition: String,
+    pub step: String,
+    pub body: Vec<String>,
+}
+
+/// A here-document operator (`<<WORD` or `<<-WORD`) on a command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct HereDoc {
+    /// Line that ends the body
+    pub delimiter: String,
+    /// `<<-`: leading tabs are stripped from the body and the delimiter line
+    pub strip_tabs: bool,
+    /// A quoted delimiter turns off expansion in the body
+    pub quoted: bool,
+}
+
+/// A command's words, with its redirections split off
+struct RedirectedWords {
+    words: Vec<String>,
+    stdout: Option<Redirection>,
+    stderr: Option<Redirection>,
+    stdin: Option<Redirection>,
+}
+
 /// Command parser
 pub struct Parser;
 
@@ -28,12 +94,61 @@ impl Parser {
         Self::parse_with_context(line, &ExpansionContext::default())
     }
     
+    /// Remove backslash-newline line continuations (kept literally inside single quotes)
+    pub fn join_continuations(line: &str) -> String {
+        let mut result = String::with_capacity(line.len());
+        let mut chars = line.chars().peekable();
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\'' if !in_double_quote => {
+                    in_single_quote = !in_single_quote;
+                    result.push(ch);
+                }
+                '"' if !in_single_quote => {
+                    in_double_quote = !in_double_quote;
+                    result.push(ch);
+                }
+                '\\' if !in_single_quote => match chars.next() {
+                    Some('\n') => {}
+                    // An escaped character (including `\\`) can't start a continuation
+                    Some(next) => {
+                        result.push(ch);
+                        result.push(next);
+                    }
+                    None => result.push(ch),
+                },
+                _ => result.push(ch),
+            }
+        }
+
+        result
+    }
+
+    /// Parse a command line into a pipeline
+    #[allow(dead_code)] // Used in tests
+    pub fn parse_pipeline(line: &str) -> Result<Pipeline> {
+        Self::parse_pipeline_with_context(line, &ExpansionContext::default())
+    }
+
     /// Parse a command line into commands with expansion context
+    ///
+    /// A trailing `&` is dropped; use `parse_pipeline_with_context` to run it in the background.
     pub fn parse_with_context(line: &str, ctx: &ExpansionContext) -> Result<Vec<Command>> {
+        Ok(Self::parse_pipeline_with_context(line, ctx)?.commands)
+    }
+
+    /// Parse a command line into a pipeline with expansion context
+    pub fn parse_pipeline_with_context(line: &str, ctx: &ExpansionContext) -> Result<Pipeline> {
         let mut commands = Vec::new();
+        let (line, mut heredocs) = Self::take_heredocs(line, ctx)?;
+        let joined = Self::join_continuations(&line);
+        let (line, background) = Self::strip_background(&joined);
         
         if line.trim().is_empty() {
-            return Ok(commands);
+            return Ok(Pipeline { commands, background });
         }
 
         // Split by pipes, handling quoted strings
@@ -46,13 +161,10 @@ impl Parser {
             }
 
             // Parse tokens with quote handling and redirection
-            let (tokens, stdout_redir, stderr_redir, stdin_redir) = Self::parse_with_redirection(trimmed, ctx)?;
-            if tokens.is_empty() {
+            let parsed = Self::parse_with_redirection(trimmed, ctx, &mut heredocs)?;
+            let Some((name, args)) = parsed.words.split_first() else {
                 continue;
-            }
-
-            let name = tokens[0].clone();
-            let args = tokens[1..].to_vec();
+            };
 
             // Determine stdin: pipe from previous command OR input redirection
             let stdin_cmd = if i > 0 {
@@ -62,18 +174,411 @@ impl Parser {
             };
 
             let command = Command {
-                name,
-                args,
+                name: name.clone(),
+                args: args.to_vec(),
                 stdin: stdin_cmd,
-                stdout: stdout_redir,
-                stderr: stderr_redir,
-                stdin_file: stdin_redir,
+                stdout: parsed.stdout,
+                stderr: parsed.stderr,
+                stdin_file: parsed.stdin,
             };
 
             commands.push(command);
         }
 
-        Ok(commands)
+        Ok(Pipeline { commands, background })
+    }
+
+    /// Here-document operators on a command line, in order
+    pub fn heredocs(line: &str) -> Vec<HereDoc> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut heredocs = Vec::new();
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '\\' if !in_single_quote => i += 1,
+                '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+                '"' if !in_single_quote => in_double_quote = !in_double_quote,
+                // `<<` but not the here-string `<<<`
+                '<' if !in_single_quote
+                    && !in_double_quote
+                    && chars.get(i + 1) == Some(&'<')
+                    && chars.get(i + 2) != Some(&'<')
+                    && (i == 0 || chars[i - 1] != '<') =>
+                {
+                    i += 2;
+                    let strip_tabs = chars.get(i) == Some(&'-');
+                    if strip_tabs {
+                        i += 1;
+                    }
+                    while matches!(chars.get(i), Some(' ' | '\t')) {
+                        i += 1;
+                    }
+
+                    // The delimiter word, with quoting removed
+                    let mut delimiter = String::new();
+                    let mut quoted = false;
+                    let mut quote = None;
+                    while let Some(&ch) = chars.get(i) {
+                        match (quote, ch) {
+                            (Some(q), _) if ch == q => quote = None,
+                            (Some(_), _) => delimiter.push(ch),
+                            (None, '\'' | '"') => {
+                                quote = Some(ch);
+                                quoted = true;
+                            }
+                            (None, '\\') => {
+                                quoted = true;
+                                i += 1;
+                                if let Some(&next) = chars.get(i) {
+                                    delimiter.push(next);
+                                }
+                            }
+                            (None, ' ' | '\t' | ';' | '|' | '&' | '<' | '>' | '(' | ')') => break,
+                            (None, _) => delimiter.push(ch),
+                        }
+                        i += 1;
+                    }
+                    if !delimiter.is_empty() {
+                        heredocs.push(HereDoc { delimiter, strip_tabs, quoted });
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        heredocs
+    }
+
+    /// Whether a command line still needs lines for its here-document bodies
+    pub fn needs_heredoc_lines(text: &str) -> bool {
+        let mut lines = text.lines();
+        let Some(first) = lines.next() else {
+            return false;
+        };
+        Self::heredocs(first).iter().any(|heredoc| !Self::take_heredoc_body(&mut lines, heredoc).1)
+    }
+
+    /// Collect a body up to its delimiter line; the flag is false if the text ran out first
+    fn take_heredoc_body<'a>(lines: &mut impl Iterator<Item = &'a str>, heredoc: &HereDoc) -> (String, bool) {
+        let mut body = String::new();
+        for line in lines {
+            let line = if heredoc.strip_tabs { line.trim_start_matches('\t') } else { line };
+            if line == heredoc.delimiter {
+                return (body, true);
+            }
+            body.push_str(line);
+            body.push('\n');
+        }
+        (body, false)
+    }
+
+    /// Split the here-document bodies off the lines following a command line
+    ///
+    /// Returns the command line and the bodies in operator order, expanded unless their
+    /// delimiter was quoted. A body missing its delimiter line is an error, as in sh.
+    fn take_heredocs(text: &str, ctx: &ExpansionContext) -> Result<(String, VecDeque<String>)> {
+        let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+        let heredocs = Self::heredocs(first);
+        if heredocs.is_empty() {
+            return Ok((text.to_string(), VecDeque::new()));
+        }
+
+        let mut lines = rest.lines();
+        let mut bodies = VecDeque::new();
+        for heredoc in &heredocs {
+            let (body, terminated) = Self::take_heredoc_body(&mut lines, heredoc);
+            if !terminated {
+                return Err(Error::Config(format!(
+                    "Here-document delimited by end of input (wanted `{}`)",
+                    heredoc.delimiter
+                )));
+            }
+            bodies.push_back(if heredoc.quoted { body } else { Self::expand_heredoc(&body, ctx)? });
+        }
+        if lines.any(|line| !line.trim().is_empty()) {
+            return Err(Error::Config("Unexpected text after here-document".to_string()));
+        }
+
+        Ok((first.to_string(), bodies))
+    }
+
+    /// Expand parameters, command substitutions and arithmetic in a here-document body
+    ///
+    /// Unlike words, bodies aren't split, globbed or tilde-expanded.
+    fn expand_heredoc(body: &str, ctx: &ExpansionContext) -> Result<String> {
+        // Arithmetic first, so `$((` isn't taken for a command substitution
+        let body = Self::expand_arithmetic(body)?;
+        let body = Self::expand_command_substitution(&body, ctx)?;
+
+        let chars: Vec<char> = body.chars().collect();
+        let mut result = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match (chars[i], chars.get(i + 1)) {
+                ('\\', Some(&next @ ('$' | '\\' | '`'))) => {
+                    result.push(next);
+                    i += 2;
+                }
+                ('$', Some('?')) => {
+                    result.push_str(&ctx.last_exit_code.unwrap_or(0).to_string());
+                    i += 2;
+                }
+                ('$', Some('{')) => match chars[i + 2..].iter().position(|&ch| ch == '}') {
+                    Some(len) => {
+                        let name: String = chars[i + 2..i + 2 + len].iter().collect();
+                        result.push_str(&std::env::var(name).unwrap_or_default());
+                        i += len + 3;
+                    }
+                    None => {
+                        result.push('$');
+                        i += 1;
+                    }
+                },
+                ('$', Some(&next)) if next.is_alphanumeric() || next == '_' => {
+                    let name: String = chars[i + 1..]
+                        .iter()
+                        .take_while(|ch| ch.is_alphanumeric() || **ch == '_')
+                        .collect();
+                    i += name.len() + 1;
+                    result.push_str(&std::env::var(name).unwrap_or_default());
+                }
+                (ch, _) => {
+                    result.push(ch);
+                    i += 1;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Split a trailing `&` (with or without a space before it) off a line
+    ///
+    /// `&&`, `>&`, an escaped `\&` and a quoted `&` don't count.
+    fn strip_background(line: &str) -> (&str, bool) {
+        let trimmed = line.trim_end();
+        let Some(rest) = trimmed.strip_suffix('&') else {
+            return (line, false);
+        };
+        if rest.ends_with(['&', '>', '|']) {
+            return (line, false);
+        }
+
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+        let mut escape_next = false;
+        for ch in rest.chars() {
+            if escape_next {
+                escape_next = false;
+                continue;
+            }
+            match ch {
+                '\\' if !in_single_quote => escape_next = true,
+                '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+                '"' if !in_single_quote => in_double_quote = !in_double_quote,
+                _ => {}
+            }
+        }
+        if escape_next || in_single_quote || in_double_quote {
+            return (line, false);
+        }
+        (rest, true)
+    }
+
+    /// Parse a C-style `for (( init; cond; step )); do body; done` loop.
+    /// Returns `None` if the line is not an arithmetic for loop.
+    pub fn parse_arith_for(line: &str) -> Result<Option<ArithFor>> {
+        let rest = match line.trim().strip_prefix("for") {
+            Some(rest) if rest.trim_start().starts_with("((") => rest.trim_start(),
+            _ => return Ok(None),
+        };
+
+        let header_end = rest
+            .find("))")
+            .ok_or_else(|| Error::Config("for: missing `))'".to_string()))?;
+        let header: Vec<&str> = rest[2..header_end].split(';').map(|s| s.trim()).collect();
+        if header.len() != 3 {
+            return Err(Error::Config("for: expected `(( init; condition; step ))'".to_string()));
+        }
+
+        let rest = rest[header_end + 2..].trim_start();
+        let rest = rest.strip_prefix(';').unwrap_or(rest).trim();
+        let body = rest
+            .strip_prefix("do")
+            .filter(|body| body.starts_with(char::is_whitespace))
+            .and_then(|body| body.trim_end().strip_suffix("done"))
+            .ok_or_else(|| Error::Config("for: expected `do ... done'".to_string()))?;
+
+        Ok(Some(ArithFor {
+            init: header[0].to_string(),
+            condition: header[1].to_string(),
+            step: header[2].to_string(),
+            body: Self::split_statements(body)?,
+        }))
+    }
+
+    /// Split a line on `;`, respecting quoted strings
+    pub fn split_statements(line: &str) -> Result<Vec<String>> {
+        let mut statements = Vec::new();
+        let mut current = String::new();
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+        let mut escape_next = false;
+
+        for ch in line.chars() {
+            if escape_next {
+                current.push(ch);
+                escape_next = false;
+                continue;
+            }
+
+            match ch {
+                '\\' if !in_single_quote => {
+                    escape_next = true;
+                    current.push(ch);
+                }
+                '\'' if !in_double_quote => {
+                    in_single_quote = !in_single_quote;
+                    current.push(ch);
+                }
+                '"' if !in_single_quote => {
+                    in_double_quote = !in_double_quote;
+                    current.push(ch);
+                }
+                ';' if !in_single_quote && !in_double_quote => {
+                    if !current.trim().is_empty() {
+                        statements.push(current.trim().to_string());
+                    }
+                    current.clear();
+                }
+                _ => current.push(ch),
+            }
+        }
+
+        if in_single_quote || in_double_quote {
+            return Err(Error::Config("Unclosed quote".to_string()));
+        }
+
+        if !current.trim().is_empty() {
+            statements.push(current.trim().to_string());
+        }
+
+        Ok(statements)
+    }
+
+    /// Split a line into pipelines joined by `&&`, `||` and `;`, respecting quoted strings
+    ///
+    /// Here-document bodies on the lines after the first go with the pipeline that reads them.
+    pub fn parse_list(line: &str) -> Result<PipelineList> {
+        let (head, rest) = match line.split_once('\n') {
+            Some((first, rest)) if !Self::heredocs(first).is_empty() => (first, rest),
+            _ => (line, ""),
+        };
+
+        let mut pipelines = Vec::new();
+        let mut operator = ListOperator::Seq;
+        let mut current = String::new();
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+        let mut escape_next = false;
+        let mut subst_depth = 0;
+        let mut chars = head.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if escape_next {
+                current.push(ch);
+                escape_next = false;
+                continue;
+            }
+
+            let quoted = in_single_quote || in_double_quote || subst_depth > 0;
+            let next_operator = match ch {
+                '\\' if !in_single_quote => {
+                    escape_next = true;
+                    None
+                }
+                '\'' if !in_double_quote => {
+                    in_single_quote = !in_single_quote;
+                    None
+                }
+                '"' if !in_single_quote => {
+                    in_double_quote = !in_double_quote;
+                    None
+                }
+                '(' if !in_single_quote && current.ends_with('$') => {
+                    subst_depth += 1;
+                    None
+                }
+                ')' if !in_single_quote && subst_depth > 0 => {
+                    subst_depth -= 1;
+                    None
+                }
+                '&' if !quoted && chars.peek() == Some(&'&') => Some(ListOperator::And),
+                '|' if !quoted && chars.peek() == Some(&'|') && !current.ends_with('>') => Some(ListOperator::Or),
+                ';' if !quoted => Some(ListOperator::Seq),
+                _ => None,
+            };
+
+            let Some(next_operator) = next_operator else {
+                current.push(ch);
+                continue;
+            };
+            if next_operator != ListOperator::Seq {
+                chars.next();
+            }
+            let pipeline = current.trim();
+            if pipeline.is_empty() {
+                if next_operator != ListOperator::Seq || operator != ListOperator::Seq {
+                    let token = match next_operator {
+                        ListOperator::And => "&&",
+                        ListOperator::Or => "||",
+                        ListOperator::Seq => ";",
+                    };
+                    return Err(Error::Config(format!("Syntax error near `{}`", token)));
+                }
+            } else {
+                pipelines.push((operator, pipeline.to_string()));
+            }
+            operator = next_operator;
+            current.clear();
+        }
+
+        if in_single_quote || in_double_quote {
+            return Err(Error::Config("Unclosed quote".to_string()));
+        }
+        if !current.trim().is_empty() {
+            pipelines.push((operator, current.trim().to_string()));
+        } else if operator != ListOperator::Seq {
+            return Err(Error::Config("Syntax error: unexpected end of line".to_string()));
+        }
+
+        // Hand each pipeline the bodies of its own here-documents
+        let mut lines = rest.lines();
+        for (_, pipeline) in pipelines.iter_mut() {
+            for heredoc in Self::heredocs(pipeline) {
+                for line in lines.by_ref() {
+                    pipeline.push('\n');
+                    pipeline.push_str(line);
+                    let line = if heredoc.strip_tabs { line.trim_start_matches('\t') } else { line };
+                    if line == heredoc.delimiter {
+                        break;
+                    }
+                }
+            }
+        }
+        if let Some((_, last)) = pipelines.last_mut() {
+            for line in lines {
+                last.push('\n');
+                last.push_str(line);
+            }
+        }
+
+        Ok(PipelineList { pipelines })
     }
 
     /// Split line by pipes, respecting quoted strings
@@ -83,6 +588,7 @@ impl Parser {
         let mut in_single_quote = false;
         let mut in_double_quote = false;
         let mut escape_next = false;
+        let mut subst_depth = 0;
 
         for ch in line.chars() {
             if escape_next {
@@ -104,7 +610,16 @@ impl Parser {
                     in_double_quote = !in_double_quote;
                     current.push(ch);
                 }
-                '|' if !in_single_quote && !in_double_quote => {
+                '(' if !in_single_quote && current.ends_with('$') => {
+                    subst_depth += 1;
+                    current.push(ch);
+                }
+                ')' if !in_single_quote && subst_depth > 0 => {
+                    subst_depth -= 1;
+                    current.push(ch);
+                }
+                // `>|` is a redirection, not a pipe
+                '|' if !in_single_quote && !in_double_quote && subst_depth == 0 && !current.ends_with('>') => {
                     parts.push(current.trim().to_string());
                     current.clear();
                 }
@@ -127,13 +642,27 @@ impl Parser {
 
     /// Tokenize a command string, handling quotes and expansion
     fn tokenize(line: &str, ctx: &ExpansionContext) -> Result<Vec<String>> {
-        let mut tokens = Vec::new();
+        // Each token remembers whether any part of it was quoted or escaped
+        let mut tokens: Vec<(String, bool)> = Vec::new();
         let mut current = String::new();
+        let mut current_quoted = false;
         let mut in_single_quote = false;
         let mut in_double_quote = false;
         let mut escape_next = false;
+        let mut subst_depth = 0;
 
         for ch in line.chars() {
+            // $(...) is kept verbatim (spaces, quotes and all) for the substitution to run
+            if subst_depth > 0 {
+                current.push(ch);
+                match ch {
+                    '(' => subst_depth += 1,
+                    ')' => subst_depth -= 1,
+                    _ => {}
+                }
+                continue;
+            }
+
             if escape_next {
                 if in_single_quote {
                     // In single quotes, only \' is special
@@ -166,17 +695,25 @@ impl Parser {
             match ch {
                 '\\' if !in_single_quote => {
                     escape_next = true;
+                    current_quoted = true;
                 }
                 '\'' if !in_double_quote => {
                     in_single_quote = !in_single_quote;
+                    current_quoted = true;
                 }
                 '"' if !in_single_quote => {
                     in_double_quote = !in_double_quote;
+                    current_quoted = true;
+                }
+                '(' if !in_single_quote && current.ends_with('$') => {
+                    subst_depth = 1;
+                    current.push(ch);
                 }
                 ' ' | '\t' if !in_single_quote && !in_double_quote => {
                     if !current.is_empty() {
-                        tokens.push(std::mem::take(&mut current));
+                        tokens.push((std::mem::take(&mut current), current_quoted));
                     }
+                    current_quoted = false;
                 }
                 _ => {
                     current.push(ch);
@@ -189,19 +726,68 @@ impl Parser {
         }
 
         if !current.is_empty() {
-            tokens.push(current);
+            tokens.push((current, current_quoted));
         }
 
-        // Apply expansions
-        let expanded_tokens: Result<Vec<String>> = tokens.iter()
-            .map(|token| Self::expand_token(token, ctx))
-            .collect();
+        // Apply expansions like bash: braces in the unquoted source word first
+        // (never in NAME=value words), then parameter and command substitution,
+        // whose unquoted results are word-split on IFS and globbed, each
+        // resulting word becoming its own argument
+        let mut expanded_tokens = Vec::new();
+        for (token, quoted) in tokens {
+            if quoted {
+                expanded_tokens.push(Self::expand_token(&token, ctx)?);
+                continue;
+            }
+            let words = if Self::is_assignment(&token) {
+                vec![token]
+            } else {
+                Self::expand_brace(&token, &ctx.limits, 0)?
+            };
+            for word in words {
+                let value = Self::expand_token(&word, ctx)?;
+                let splittable = word.contains('$') || word.contains('`');
+                if splittable && !Self::is_assignment(&word) {
+                    for field in Self::split_fields(&value, &ctx.ifs) {
+                        expanded_tokens.extend(Self::expand_glob(&field));
+                    }
+                } else {
+                    expanded_tokens.extend(Self::expand_glob(&value));
+                }
+            }
+        }
+
+        Ok(expanded_tokens)
+    }
 
-        expanded_tokens
+    /// Check whether a word has the form NAME=value
+    pub fn is_assignment(token: &str) -> bool {
+        match token.split_once('=') {
+            Some((name, _)) => Self::is_identifier(name),
+            None => false,
+        }
+    }
+
+    /// Split an expansion result into fields on any IFS character
+    fn split_fields(value: &str, ifs: &str) -> Vec<String> {
+        if ifs.is_empty() {
+            return vec![value.to_string()];
+        }
+        value
+            .split(|c| ifs.contains(c))
+            .filter(|field| !field.is_empty())
+            .map(|field| field.to_string())
+            .collect()
     }
 
     /// Parse tokens and extract redirections
-    fn parse_with_redirection(line: &str, ctx: &ExpansionContext) -> Result<(Vec<String>, Option<Redirection>, Option<Redirection>, Option<Redirection>)> {
+    ///
+    /// Here-documents take their bodies from the front of `heredocs`.
+    fn parse_with_redirection(
+        line: &str,
+        ctx: &ExpansionContext,
+        heredocs: &mut VecDeque<String>,
+    ) -> Result<RedirectedWords> {
         let tokens = Self::tokenize(line, ctx)?;
         let mut result_tokens = Vec::new();
         let mut stdout_redir: Option<Redirection> = None;
@@ -220,6 +806,13 @@ impl Parser {
                         continue;
                     }
                 }
+                ">|" => {
+                    if i + 1 < tokens.len() {
+                        stdout_redir = Some(Redirection::OutputClobber(tokens[i + 1].clone()));
+                        i += 2;
+                        continue;
+                    }
+                }
                 ">>" => {
                     if i + 1 < tokens.len() {
                         stdout_redir = Some(Redirection::Append(tokens[i + 1].clone()));
@@ -245,20 +838,10 @@ impl Parser {
                         }
                     }
                 }
-                "<<" => {
-                    // Here-document delimiter
+                "<<" | "<<-" => {
+                    // Here-document; the delimiter was already used to collect the body
                     if i + 1 < tokens.len() {
-                        let delimiter = tokens[i + 1].clone();
-                        stdin_redir = Some(Redirection::Input(format!("<<{}", delimiter)));
-                        i += 2;
-                        continue;
-                    }
-                }
-                "<<-" => {
-                    // Here-document with dash (strip leading tabs)
-                    if i + 1 < tokens.len() {
-                        let delimiter = tokens[i + 1].clone();
-                        stdin_redir = Some(Redirection::Input(format!("<<-{}", delimiter)));
+                        stdin_redir = Some(Redirection::HereDoc(heredocs.pop_front().unwrap_or_default()));
                         i += 2;
                         continue;
                     }
@@ -296,7 +879,21 @@ impl Parser {
                     }
                 }
                 _ => {
-                    // Check for file descriptor redirection: n>, n<, n>&m
+                    // `2>&1` and `1>&2` point at the other stream as redirected so far, so
+                    // `>file 2>&1` sends both to the file while `2>&1 >file` keeps stderr on stdout
+                    if let Some((fd_from, fd_to)) = Self::parse_fd_dup(token) {
+                        match (fd_from, fd_to) {
+                            (2, 1) if stdout_redir.is_some() => stderr_redir = Some(Redirection::FdDup(2, 1)),
+                            (2, 1) => stderr_redir = Some(Redirection::DupOriginal(1)),
+                            (1, 2) if stderr_redir.is_some() => stdout_redir = Some(Redirection::FdDup(1, 2)),
+                            (1, 2) => stdout_redir = Some(Redirection::DupOriginal(2)),
+                            _ => {}
+                        }
+                        i += 1;
+                        continue;
+                    }
+
+                    // Check for file descriptor redirection: n>, n<
                     if let Some(fd_redir) = Self::parse_fd_redirection(token, &tokens, &mut i)? {
                         match fd_redir {
                             (Some(stdout), None, None) => stdout_redir = stdout,
@@ -307,7 +904,10 @@ impl Parser {
                         continue;
                     }
                     // Check for process substitution in token: <(command) or >(command)
-                    if token.starts_with("<(") && token.ends_with(')') {
+                    if token.starts_with("<<") && !token.starts_with("<<<") {
+                        // Here-document with the delimiter attached: <<EOF
+                        stdin_redir = Some(Redirection::HereDoc(heredocs.pop_front().unwrap_or_default()));
+                    } else if token.starts_with("<(") && token.ends_with(')') {
                         // Input process substitution
                         let cmd = &token[2..token.len()-1];
                         stdin_redir = Some(Redirection::Input(format!("<({})", cmd)));
@@ -323,10 +923,22 @@ impl Parser {
             i += 1;
         }
 
-        Ok((result_tokens, stdout_redir, stderr_redir, stdin_redir))
+        Ok(RedirectedWords {
+            words: result_tokens,
+            stdout: stdout_redir,
+            stderr: stderr_redir,
+            stdin: stdin_redir,
+        })
+    }
+
+    /// Parse a file descriptor duplication token, `n>&m` or `>&m` (fd 1)
+    fn parse_fd_dup(token: &str) -> Option<(u32, u32)> {
+        let (from, to) = token.split_once(">&")?;
+        let fd_from = if from.is_empty() { 1 } else { from.parse().ok()? };
+        Some((fd_from, to.parse().ok()?))
     }
 
-    /// Parse file descriptor redirection: n>, n<, n>&m
+    /// Parse file descriptor redirection: n>, n<
     fn parse_fd_redirection(
         token: &str,
         tokens: &[String],
@@ -364,26 +976,6 @@ impl Parser {
             }
         }
         
-        // Check for n>&m (file descriptor duplication)
-        if let Some(pos) = token.find(">&") {
-            if pos > 0 && pos + 2 < token.len() {
-                if let Ok(fd_from) = token[..pos].parse::<u32>() {
-                    let rest = &token[pos + 2..];
-                    if let Ok(fd_to) = rest.parse::<u32>() {
-                        let _redir = Redirection::FdDup(fd_from, fd_to);
-                        *i += 1;
-                        // Map to appropriate redirection based on file descriptors
-                        if fd_from == 1 {
-                            return Ok(Some((Some(Some(Redirection::Output(format!("&{}", fd_to)))), None, None)));
-                        } else if fd_from == 2 {
-                            return Ok(Some((None, Some(Some(Redirection::Error(format!("&{}", fd_to)))), None)));
-                        }
-                        return Ok(Some((None, None, None)));
-                    }
-                }
-            }
-        }
-        
         Ok(None)
     }
 
@@ -397,7 +989,7 @@ impl Parser {
             return Ok("0".to_string()); // Default to 0 if not set
         }
         // First handle command substitution
-        let token = Self::expand_command_substitution(token)?;
+        let token = Self::expand_command_substitution(token, ctx)?;
         
         // Handle arithmetic expansion
         let token = Self::expand_arithmetic(&token)?;
@@ -533,6 +1125,11 @@ impl Parser {
                         i = j;
                         continue;
                     }
+                } else if chars[i + 1] == '?' {
+                    // $? - exit status of the last command
+                    result.push_str(&ctx.last_exit_code.unwrap_or(0).to_string());
+                    i += 2;
+                    continue;
                 } else if chars[i + 1] == '#' {
                     // $# - number of script arguments
                     result.push_str(&ctx.script_args.len().to_string());
@@ -606,15 +1203,20 @@ impl Parser {
             i += 1;
         }
 
-        // Apply brace expansion
-        let result = Self::expand_brace(&result)?;
-        
-        // Apply globbing
-        Self::expand_glob(&result)
+        Ok(result)
     }
 
-    /// Expand brace expressions: {a,b,c} or {1..10} or {a,{b,c}} or prefix{a,b}suffix
-    fn expand_brace(token: &str) -> Result<String> {
+    /// Expand brace expressions into words: {a,b,c} or {1..10} or {a,{b,c}} or prefix{a,b}suffix
+    ///
+    /// Adjacent groups multiply: `{a,b}{c,d}` gives `ac ad bc bd`.
+    fn expand_brace(token: &str, limits: &ExpansionLimits, depth: usize) -> Result<Vec<String>> {
+        if depth > limits.max_depth {
+            return Err(Error::Config(format!(
+                "Brace expansion nested deeper than {} levels",
+                limits.max_depth
+            )));
+        }
+
         let mut result = String::new();
         let mut i = 0;
         let chars: Vec<char> = token.chars().collect();
@@ -635,30 +1237,26 @@ impl Parser {
                 brace_depth -= 1;
                 if brace_depth == 0 {
                     if let Some(start) = brace_start {
-                        // Extract prefix (text before the brace)
-                        let prefix = if start > 0 {
-                            token[..start].to_string()
-                        } else {
-                            String::new()
-                        };
+                        // Prefix (text before the brace) is what has been copied so far
+                        let prefix = std::mem::take(&mut result);
                         
                         // Extract suffix (text after the brace)
-                        let suffix = if i + 1 < token.len() {
-                            token[i + 1..].to_string()
-                        } else {
-                            String::new()
-                        };
+                        let suffix: String = chars[i + 1..].iter().collect();
                         
-                        let brace_content = &token[start + 1..i];
-                        let expanded = Self::expand_brace_content(brace_content, &prefix, &suffix)?;
-                        result.push_str(&expanded);
-                        // Skip the suffix since we already included it in the expansion
-                        if !suffix.is_empty() {
-                            i += suffix.chars().count();
+                        let brace_content: String = chars[start + 1..i].iter().collect();
+                        let alternatives = Self::brace_alternatives(&brace_content, limits, depth)?;
+                        let suffixes = Self::expand_brace(&suffix, limits, depth)?;
+                        if alternatives.len().saturating_mul(suffixes.len()) > limits.max_words {
+                            return Err(Self::too_many_words(limits));
                         }
-                        brace_start = None;
-                        i += 1;
-                        continue;
+
+                        let mut words = Vec::with_capacity(alternatives.len() * suffixes.len());
+                        for alternative in &alternatives {
+                            for suffix in &suffixes {
+                                words.push(format!("{}{}{}", prefix, alternative, suffix));
+                            }
+                        }
+                        return Ok(words);
                     }
                 }
             }
@@ -671,82 +1269,131 @@ impl Parser {
 
         if brace_depth > 0 {
             // Unclosed brace - return as-is
-            return Ok(token.to_string());
+            return Ok(vec![token.to_string()]);
         }
 
-        Ok(result)
+        Ok(vec![result])
     }
 
-    /// Expand brace content: a,b,c or 1..10 or {a,{b,c}} with prefix/suffix
-    fn expand_brace_content(content: &str, prefix: &str, suffix: &str) -> Result<String> {
-        // Check for range expansion: {1..10}
-        if let Some(range_pos) = content.find("..") {
-            let start_str = content[..range_pos].trim();
-            let end_str = content[range_pos + 2..].trim();
-            
-            if let (Ok(start), Ok(end)) = (start_str.parse::<i32>(), end_str.parse::<i32>()) {
-                let mut result = Vec::new();
-                let step = if start <= end { 1 } else { -1 };
-                let mut current = start;
-                while (step > 0 && current <= end) || (step < 0 && current >= end) {
-                    result.push(format!("{}{}{}", prefix, current, suffix));
-                    current += step;
-                }
-                return Ok(result.join(" "));
-            }
+    /// Words of a brace group's content: a,b,c or 1..10 or a,{b,c}
+    fn brace_alternatives(content: &str, limits: &ExpansionLimits, nesting: usize) -> Result<Vec<String>> {
+        // Check for range expansion: {1..10}, {01..10}, {0..10..2}, {a..e}
+        if let Some(words) = Self::expand_brace_range(content, limits)? {
+            return Ok(words);
         }
 
         // Handle nested braces and comma-separated expansion
         let mut parts = Vec::new();
         let mut current_part = String::new();
         let mut depth = 0;
-        let mut i = 0;
-        let chars: Vec<char> = content.chars().collect();
-        
-        while i < chars.len() {
-            match chars[i] {
+        for ch in content.chars() {
+            match ch {
                 '{' => {
                     depth += 1;
-                    current_part.push(chars[i]);
+                    current_part.push(ch);
                 }
                 '}' => {
                     depth -= 1;
-                    current_part.push(chars[i]);
+                    current_part.push(ch);
                 }
                 ',' if depth == 0 => {
                     // At top level, split on comma
                     parts.push(current_part.trim().to_string());
                     current_part.clear();
                 }
-                _ => {
-                    current_part.push(chars[i]);
-                }
+                _ => current_part.push(ch),
             }
-            i += 1;
         }
-        
         if !current_part.is_empty() {
             parts.push(current_part.trim().to_string());
         }
-        
+
         // Expand each part (recursively handle nested braces)
-        let mut expanded_parts = Vec::new();
+        let mut alternatives = Vec::new();
         for part in parts {
-            // Check if this part contains nested braces
             if part.contains('{') && part.contains('}') {
-                // Recursively expand nested braces
-                let nested_expanded = Self::expand_brace(&part)?;
-                // Split the nested expansion and apply prefix/suffix to each
-                for nested_part in nested_expanded.split_whitespace() {
-                    expanded_parts.push(format!("{}{}{}", prefix, nested_part, suffix));
-                }
+                alternatives.extend(Self::expand_brace(&part, limits, nesting + 1)?);
             } else {
-                // Simple part, just apply prefix/suffix
-                expanded_parts.push(format!("{}{}{}", prefix, part, suffix));
+                alternatives.push(part);
+            }
+            if alternatives.len() > limits.max_words {
+                return Err(Self::too_many_words(limits));
             }
         }
-        
-        Ok(expanded_parts.join(" "))
+
+        Ok(alternatives)
+    }
+
+    fn too_many_words(limits: &ExpansionLimits) -> Error {
+        Error::Config(format!("Brace expansion would generate more than {} words", limits.max_words))
+    }
+
+    /// Expand a sequence expression `start..end[..step]`; `None` if `content` isn't one
+    ///
+    /// Numeric ranges keep the width of zero-padded endpoints; single letters give
+    /// character ranges. Ranges may descend; the sign of the step is ignored.
+    fn expand_brace_range(content: &str, limits: &ExpansionLimits) -> Result<Option<Vec<String>>> {
+        let fields: Vec<&str> = content.split("..").map(str::trim).collect();
+        let (start_str, end_str, step) = match fields[..] {
+            [start, end] => (start, end, 1),
+            [start, end, step] => match step.parse::<i64>() {
+                Ok(step) => (start, end, step.unsigned_abs().max(1)),
+                Err(_) => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        let (start, end, width, alphabetic) = if let (Ok(start), Ok(end)) =
+            (start_str.parse::<i64>(), end_str.parse::<i64>())
+        {
+            let padded = |s: &str| {
+                let digits = s.trim_start_matches('-');
+                digits.len() > 1 && digits.starts_with('0')
+            };
+            let width = if padded(start_str) || padded(end_str) {
+                start_str.len().max(end_str.len())
+            } else {
+                0
+            };
+            (start, end, width, false)
+        } else {
+            let letter = |s: &str| {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_alphabetic() => Some(c as i64),
+                    _ => None,
+                }
+            };
+            match (letter(start_str), letter(end_str)) {
+                (Some(start), Some(end)) => (start, end, 0, true),
+                _ => return Ok(None),
+            }
+        };
+
+        let count = (start.abs_diff(end) / step)
+            .checked_add(1)
+            .filter(|&count| count <= limits.max_words as u64)
+            .ok_or_else(|| Self::too_many_words(limits))?;
+
+        // Checked steps, so ranges reaching the ends of i64 can't overflow
+        let next = |value: &i64| {
+            if start <= end {
+                value.checked_add_unsigned(step)
+            } else {
+                value.checked_sub_unsigned(step)
+            }
+        };
+        let words = std::iter::successors(Some(start), next)
+            .take(count as usize)
+            .map(|value| {
+                if alphabetic {
+                    (value as u8 as char).to_string()
+                } else {
+                    format!("{:0width$}", value, width = width)
+                }
+            })
+            .collect();
+        Ok(Some(words))
     }
 
     /// Expand arithmetic expressions: $((expression))
@@ -765,10 +1412,14 @@ impl Parser {
                 
                 while j < chars.len() && depth > 0 {
                     match chars[j] {
-                        '(' => depth += 1,
+                        '(' => {
+                            depth += 1;
+                            expr.push(chars[j]);
+                        }
                         ')' => {
                             depth -= 1;
-                            if depth > 0 {
+                            // The closing `))` isn't part of the expression
+                            if depth > 1 {
                                 expr.push(chars[j]);
                             }
                         }
@@ -793,6 +1444,74 @@ impl Parser {
         Ok(result)
     }
 
+    /// Evaluate an arithmetic statement, storing assignments (`x = e`, `x += e`, `i++`, `--i`)
+    /// in the environment. Returns the statement's value.
+    pub fn evaluate_arithmetic_statement(expr: &str) -> Result<i64> {
+        let expr = expr.trim();
+
+        // Post-increment/decrement: value before the update
+        for (op, delta) in [("++", 1), ("--", -1)] {
+            if let Some(name) = expr.strip_suffix(op).map(str::trim) {
+                if Self::is_identifier(name) {
+                    let value = Self::arithmetic_variable(name)?;
+                    std::env::set_var(name, (value + delta).to_string());
+                    return Ok(value);
+                }
+            }
+            if let Some(name) = expr.strip_prefix(op).map(str::trim) {
+                if Self::is_identifier(name) {
+                    let value = Self::arithmetic_variable(name)? + delta;
+                    std::env::set_var(name, value.to_string());
+                    return Ok(value);
+                }
+            }
+        }
+
+        // Assignment: the first `=` that isn't part of `==`, `!=`, `<=` or `>=`
+        let bytes = expr.as_bytes();
+        let assign_pos = (0..bytes.len()).find(|&i| {
+            bytes[i] == b'='
+                && bytes.get(i + 1) != Some(&b'=')
+                && (i == 0 || !matches!(bytes[i - 1], b'=' | b'!' | b'<' | b'>'))
+        });
+        if let Some(pos) = assign_pos {
+            let (target, op) = match expr[..pos].trim_end() {
+                t if t.ends_with(['+', '-', '*', '/', '%']) => (t[..t.len() - 1].trim(), Some(&t[t.len() - 1..])),
+                t => (t, None),
+            };
+            if Self::is_identifier(target) {
+                let rhs = Self::evaluate_arithmetic(&expr[pos + 1..])?;
+                let value = match op {
+                    Some(op) => {
+                        let current = Self::arithmetic_variable(target)?;
+                        Self::evaluate_arithmetic(&format!("{} {} {}", current, op, rhs))?
+                    }
+                    None => rhs,
+                };
+                std::env::set_var(target, value.to_string());
+                return Ok(value);
+            }
+        }
+
+        Self::evaluate_arithmetic(expr)
+    }
+
+    /// Check whether a string is a valid variable name
+    fn is_identifier(name: &str) -> bool {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Read a variable as a number for arithmetic; unset or empty variables are 0
+    fn arithmetic_variable(name: &str) -> Result<i64> {
+        match std::env::var(name) {
+            Ok(value) if !value.trim().is_empty() => value.trim().parse::<i64>()
+                .map_err(|_| Error::Config(format!("Variable {} is not a number", name))),
+            _ => Ok(0),
+        }
+    }
+
     /// Evaluate an arithmetic expression
     fn evaluate_arithmetic(expr: &str) -> Result<i64> {
         let expr = expr.trim();
@@ -939,11 +1658,16 @@ impl Parser {
             }
         }
 
+        // Bare variable names, as in `i < 5`
+        if Self::is_identifier(expr) {
+            return Self::arithmetic_variable(expr);
+        }
+
         Err(Error::Config(format!("Invalid arithmetic expression: {}", expr)))
     }
 
     /// Expand command substitution: $(command) or `command`
-    fn expand_command_substitution(token: &str) -> Result<String> {
+    fn expand_command_substitution(token: &str, ctx: &ExpansionContext) -> Result<String> {
         let mut result = String::new();
         let mut i = 0;
         let chars: Vec<char> = token.chars().collect();
@@ -957,7 +1681,15 @@ impl Parser {
                 
                 while j < chars.len() && depth > 0 {
                     match chars[j] {
-                        '(' => depth += 1,
+                        '(' => {
+                            depth += 1;
+                            if depth > ctx.limits.max_depth {
+                                return Err(Error::Config(format!(
+                                    "Command substitution nested deeper than {} levels",
+                                    ctx.limits.max_depth
+                                )));
+                            }
+                        }
                         ')' => depth -= 1,
                         _ => {}
                     }
@@ -969,7 +1701,8 @@ impl Parser {
 
                 if depth == 0 {
                     // Execute command
-                    let output = Self::execute_substitution(&command)?;
+                    let (output, status) = Self::execute_substitution(&command, &ctx.limits)?;
+                    ctx.substitution_status.set(Some(status));
                     result.push_str(&output);
                     i = j;
                     continue;
@@ -991,7 +1724,8 @@ impl Parser {
 
                 if j < chars.len() {
                     // Execute command
-                    let output = Self::execute_substitution(&command)?;
+                    let (output, status) = Self::execute_substitution(&command, &ctx.limits)?;
+                    ctx.substitution_status.set(Some(status));
                     result.push_str(&output);
                     i = j + 1;
                     continue;
@@ -1005,43 +1739,107 @@ impl Parser {
         Ok(result)
     }
 
-    /// Execute a command substitution
-    fn execute_substitution(command: &str) -> Result<String> {
-        use std::process::Command;
-        let output = Command::new("sh")
+    /// Execute a command substitution, returning its output and exit status
+    ///
+    /// Only trailing newlines are stripped from the output, as in POSIX shells. The command is
+    /// killed once it exceeds the timeout or output cap in `limits`.
+    fn execute_substitution(command: &str, limits: &ExpansionLimits) -> Result<(String, i32)> {
+        use std::io::Read;
+        use std::os::unix::process::CommandExt;
+        use std::process::{Command, Stdio};
+        use std::time::Instant;
+
+        let deadline = Instant::now() + limits.substitution_timeout;
+        let mut child = Command::new("sh")
             .arg("-c")
             .arg(command)
-            .output()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            // Own process group, so a timeout also kills whatever the command started
+            .process_group(0)
+            .spawn()
             .map_err(|e| Error::Config(format!("Command substitution failed: {}", e)))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        Ok(stdout.trim_end().to_string())
+
+        // Read on a separate thread so the timeout holds even while the command is silent
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let max_output = limits.max_substitution_output;
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut output = Vec::new();
+            let result = (&mut stdout)
+                .take(max_output as u64 + 1)
+                .read_to_end(&mut output)
+                .map(|_| output);
+            let _ = tx.send(result);
+        });
+
+        let kill = |child: &mut std::process::Child| {
+            unsafe {
+                libc::kill(-(child.id() as i32), libc::SIGKILL);
+            }
+            let _ = child.wait();
+        };
+
+        let output = match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Ok(output)) if output.len() > max_output => {
+                kill(&mut child);
+                return Err(Error::Config(format!(
+                    "Command substitution produced more than {} bytes of output",
+                    max_output
+                )));
+            }
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => {
+                kill(&mut child);
+                return Err(Error::Config(format!("Command substitution failed: {}", e)));
+            }
+            Err(_) => {
+                kill(&mut child);
+                return Err(Error::Config(format!(
+                    "Command substitution timed out after {:?}",
+                    limits.substitution_timeout
+                )));
+            }
+        };
+
+        // The command may close its output and keep running
+        let status = loop {
+            match child.try_wait()? {
+                Some(status) => break status,
+                None if Instant::now() >= deadline => {
+                    kill(&mut child);
+                    return Err(Error::Config(format!(
+                        "Command substitution timed out after {:?}",
+                        limits.substitution_timeout
+                    )));
+                }
+                None => std::thread::sleep(std::time::Duration::from_millis(5)),
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output);
+        Ok((stdout.trim_end_matches('\n').to_string(), status.code().unwrap_or(1)))
     }
 
-    /// Expand glob patterns
-    fn expand_glob(pattern: &str) -> Result<String> {
+    /// Expand a glob pattern into the paths it matches
+    fn expand_glob(pattern: &str) -> Vec<String> {
         // Check if pattern contains glob characters
         if pattern.contains('*') || pattern.contains('?') || pattern.contains('[') {
-            match glob::glob(pattern) {
-                Ok(paths) => {
-                    let matches: Vec<String> = paths
-                        .filter_map(|p| p.ok())
-                        .map(|p| p.to_string_lossy().to_string())
-                        .collect();
-                    
-                    if matches.is_empty() {
-                        // No matches, return pattern as-is
-                        Ok(pattern.to_string())
-                    } else {
-                        // Return first match (simplified - in real shell, all matches would be separate args)
-                        Ok(matches[0].clone())
-                    }
+            if let Ok(paths) = glob::glob(pattern) {
+                // Matches come back sorted
+                let matches: Vec<String> = paths
+                    .filter_map(|p| p.ok())
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect();
+                if !matches.is_empty() {
+                    return matches;
                 }
-                Err(_) => Ok(pattern.to_string()),
             }
-        } else {
-            Ok(pattern.to_string())
         }
+
+        // No glob characters or no matches: the pattern is kept as-is
+        vec![pattern.to_string()]
     }
 }
 
@@ -1057,6 +1855,18 @@ mod basic_tests {
         assert_eq!(commands[0].args, vec!["-la"]);
     }
 
+    #[test]
+    fn test_parse_line_continuation() {
+        let commands = Parser::parse("echo hello \\\nworld | gr\\\nep wor").unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].args, vec!["hello", "world"]);
+        assert_eq!(commands[1].name, "grep");
+
+        assert_eq!(Parser::join_continuations("echo \"a\\\nb\""), "echo \"ab\"");
+        assert_eq!(Parser::join_continuations("echo 'a\\\nb'"), "echo 'a\\\nb'");
+        assert_eq!(Parser::join_continuations("echo a\\\\\nb"), "echo a\\\\\nb");
+    }
+
     #[test]
     fn test_parse_pipe() {
         let commands = Parser::parse("ls | grep test").unwrap();
@@ -1089,11 +1899,172 @@ mod basic_tests {
         assert_eq!(commands[0].args[0], "test_value");
     }
 
+    #[test]
+    fn test_unquoted_variable_word_splitting() {
+        std::env::set_var("ZIRON_SPLIT_VAR", "a b\tc");
+        let commands = Parser::parse("echo $ZIRON_SPLIT_VAR").unwrap();
+        assert_eq!(commands[0].args, vec!["a", "b", "c"]);
+
+        std::env::set_var("ZIRON_EMPTY_VAR", "");
+        let commands = Parser::parse("echo $ZIRON_EMPTY_VAR end").unwrap();
+        assert_eq!(commands[0].args, vec!["end"]);
+
+        let commands = Parser::parse("export SPLIT=$ZIRON_SPLIT_VAR").unwrap();
+        assert_eq!(commands[0].args, vec!["SPLIT=a b\tc"]);
+    }
+
+    #[test]
+    fn test_quoted_variable_not_split() {
+        std::env::set_var("ZIRON_QUOTED_VAR", "a b c");
+        let commands = Parser::parse(r#"echo "$ZIRON_QUOTED_VAR""#).unwrap();
+        assert_eq!(commands[0].args, vec!["a b c"]);
+    }
+
+    #[test]
+    fn test_custom_ifs_word_splitting() {
+        std::env::set_var("ZIRON_PATH_VAR", "/bin:/usr/bin x");
+        let ctx = ExpansionContext {
+            ifs: ":".to_string(),
+            ..Default::default()
+        };
+        let commands = Parser::parse_with_context("ls $ZIRON_PATH_VAR", &ctx).unwrap();
+        assert_eq!(commands[0].args, vec!["/bin", "/usr/bin x"]);
+    }
+
+    #[test]
+    fn test_command_substitution_status() {
+        let ctx = ExpansionContext::default();
+        let commands = Parser::parse_with_context("echo $(exit 3)", &ctx).unwrap();
+        assert_eq!(commands[0].name, "echo");
+        assert_eq!(ctx.substitution_status.get(), Some(3));
+
+        let ctx = ExpansionContext::default();
+        Parser::parse_with_context("echo `true`", &ctx).unwrap();
+        assert_eq!(ctx.substitution_status.get(), Some(0));
+
+        let ctx = ExpansionContext::default();
+        Parser::parse_with_context("echo plain", &ctx).unwrap();
+        assert_eq!(ctx.substitution_status.get(), None);
+    }
+
+    #[test]
+    fn test_command_substitution_strips_only_trailing_newlines() {
+        let (output, status) = Parser::execute_substitution("printf '  a\\n\\nb  \\n\\n'", &ExpansionLimits::default()).unwrap();
+        assert_eq!(output, "  a\n\nb  ");
+        assert_eq!(status, 0);
+    }
+
+    #[test]
+    fn test_command_substitution_timeout() {
+        let limits = ExpansionLimits {
+            substitution_timeout: std::time::Duration::from_millis(200),
+            ..Default::default()
+        };
+        let started = std::time::Instant::now();
+        let err = Parser::execute_substitution("sleep 5; echo done", &limits).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_command_substitution_output_cap() {
+        let limits = ExpansionLimits {
+            max_substitution_output: 1024,
+            ..Default::default()
+        };
+        let err = Parser::execute_substitution("yes", &limits).unwrap_err();
+        assert!(err.to_string().contains("more than 1024 bytes"), "{}", err);
+
+        let (output, _) = Parser::execute_substitution("printf 'abc'", &limits).unwrap();
+        assert_eq!(output, "abc");
+    }
+
+    fn brace_range(content: &str) -> Vec<String> {
+        Parser::expand_brace_range(content, &ExpansionLimits::default())
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_brace_ranges() {
+        assert_eq!(brace_range("1..3"), vec!["1", "2", "3"]);
+        assert_eq!(brace_range("3..1"), vec!["3", "2", "1"]);
+        assert_eq!(brace_range("01..03"), vec!["01", "02", "03"]);
+        assert_eq!(brace_range("8..010"), vec!["008", "009", "010"]);
+        assert_eq!(brace_range("0..10..2"), vec!["0", "2", "4", "6", "8", "10"]);
+        assert_eq!(brace_range("10..0..-5"), vec!["10", "5", "0"]);
+        assert_eq!(brace_range("a..e"), vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(brace_range("e..a..2"), vec!["e", "c", "a"]);
+        assert!(Parser::expand_brace_range("a,b", &ExpansionLimits::default()).unwrap().is_none());
+        assert!(Parser::expand_brace_range("a..zz", &ExpansionLimits::default()).unwrap().is_none());
+
+        let commands = Parser::parse("echo file{01..03}.txt").unwrap();
+        assert_eq!(commands[0].args, vec!["file01.txt", "file02.txt", "file03.txt"]);
+    }
+
+    #[test]
+    fn test_brace_explosion_limit() {
+        let err = Parser::parse("echo {1..100000000}").unwrap_err();
+        assert!(err.to_string().contains("more than 10000 words"), "{}", err);
+
+        let ctx = ExpansionContext {
+            limits: ExpansionLimits { max_words: 2, ..Default::default() },
+            ..Default::default()
+        };
+        assert!(Parser::parse_with_context("echo {a,b,c}", &ctx).is_err());
+        assert!(Parser::parse_with_context("echo {a,b}", &ctx).is_ok());
+        assert!(Parser::parse_with_context("echo {a,b}{c,d}", &ctx).is_err());
+
+        let err = Parser::parse(&format!("echo {}", "{a,b}".repeat(14))).unwrap_err();
+        assert!(err.to_string().contains("more than 10000 words"), "{}", err);
+
+        let err = Parser::parse("echo {-9223372036854775808..9223372036854775807}").unwrap_err();
+        assert!(err.to_string().contains("more than 10000 words"), "{}", err);
+        let err = Parser::parse("echo {9223372036854775807..-9223372036854775808..1}").unwrap_err();
+        assert!(err.to_string().contains("more than 10000 words"), "{}", err);
+        let words = Parser::parse("echo {9223372036854775806..9223372036854775807}").unwrap()[0].args.clone();
+        assert_eq!(words, ["9223372036854775806", "9223372036854775807"]);
+    }
+
+    #[test]
+    fn test_deep_substitution_limit() {
+        let depth = 40;
+        let line = format!("echo {}x{}", "$(echo ".repeat(depth), ")".repeat(depth));
+        let err = Parser::parse(&line).unwrap_err();
+        assert!(err.to_string().contains("nested deeper than 32 levels"), "{}", err);
+
+        let nested_braces = format!("echo {}a{}", "{x,".repeat(depth), "}".repeat(depth));
+        assert!(Parser::parse(&nested_braces).is_err());
+    }
+
+    #[test]
+    fn test_trailing_ampersand_backgrounds_pipeline() {
+        let pipeline = Parser::parse_pipeline("sleep 1 &").unwrap();
+        assert!(pipeline.background);
+        assert_eq!(pipeline.commands.len(), 1);
+        assert_eq!(pipeline.commands[0].args, vec!["1"]);
+
+        let pipeline = Parser::parse_pipeline("a | b &").unwrap();
+        assert!(pipeline.background);
+        assert_eq!(pipeline.commands.len(), 2);
+        assert_eq!(pipeline.commands[1].name, "b");
+        assert!(pipeline.commands[1].args.is_empty());
+
+        let pipeline = Parser::parse_pipeline("cmd&").unwrap();
+        assert!(pipeline.background);
+        assert_eq!(pipeline.commands[0].name, "cmd");
+
+        for foreground in ["a && b", "echo '&'", "echo \\&", "cmd 2>&1"] {
+            assert!(!Parser::parse_pipeline(foreground).unwrap().background, "{}", foreground);
+        }
+    }
+
     #[test]
     fn test_script_argument_expansion() {
         let ctx = ExpansionContext {
             script_args: vec!["arg1".to_string(), "arg2".to_string()],
             last_exit_code: None,
+            ..Default::default()
         };
         let commands = Parser::parse_with_context("echo $1", &ctx).unwrap();
         assert_eq!(commands.len(), 1);
@@ -1105,10 +2076,50 @@ mod basic_tests {
         assert_eq!(commands.len(), 1);
     }
 
+    #[test]
+    fn test_parse_arith_for() {
+        let parsed = Parser::parse_arith_for("for (( i=0; i<5; i++ )); do echo $i; echo 'a;b'; done")
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.init, "i=0");
+        assert_eq!(parsed.condition, "i<5");
+        assert_eq!(parsed.step, "i++");
+        assert_eq!(parsed.body, vec!["echo $i", "echo 'a;b'"]);
+
+        assert!(Parser::parse_arith_for("format disk").unwrap().is_none());
+        assert!(Parser::parse_arith_for("for (( i=0; i<5 )); do echo; done").is_err());
+        assert!(Parser::parse_arith_for("for (( i=0; i<5; i++ )) echo").is_err());
+    }
+
+    #[test]
+    fn test_arithmetic_statements() {
+        assert_eq!(Parser::evaluate_arithmetic_statement("ZIRON_ARITH_X = 4").unwrap(), 4);
+        assert_eq!(Parser::evaluate_arithmetic_statement("ZIRON_ARITH_X = ZIRON_ARITH_X + 1").unwrap(), 5);
+        assert_eq!(Parser::evaluate_arithmetic_statement("ZIRON_ARITH_X *= 2").unwrap(), 10);
+        assert_eq!(Parser::evaluate_arithmetic_statement("ZIRON_ARITH_X++").unwrap(), 10);
+        assert_eq!(Parser::evaluate_arithmetic_statement("--ZIRON_ARITH_X").unwrap(), 10);
+        assert_eq!(Parser::evaluate_arithmetic_statement("ZIRON_ARITH_X <= 10").unwrap(), 1);
+        assert_eq!(std::env::var("ZIRON_ARITH_X").unwrap(), "10");
+    }
+
     #[test]
     fn test_brace_expansion() {
         let commands = Parser::parse("echo {a,b,c}").unwrap();
         assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].args, vec!["a", "b", "c"]);
+
+        let commands = Parser::parse("echo {a,b}{c,d} x{1..2}{a,b}").unwrap();
+        assert_eq!(commands[0].args, vec!["ac", "ad", "bc", "bd", "x1a", "x1b", "x2a", "x2b"]);
+
+        let commands = Parser::parse("printf '[%s]' pre{x,y}post '{a,b}'").unwrap();
+        assert_eq!(commands[0].args, vec!["[%s]", "prexpost", "preypost", "{a,b}"]);
+
+        // Only the source word is brace-expanded, never an assignment or an expansion result
+        std::env::set_var("ZIRON_BRACE_VAR", "{a,b}");
+        let commands = Parser::parse("echo $ZIRON_BRACE_VAR {$ZIRON_BRACE_VAR,c}").unwrap();
+        assert_eq!(commands[0].args, vec!["{a,b}", "{a,b}", "c"]);
+        let commands = Parser::parse("X={a,b}").unwrap();
+        assert_eq!((commands[0].name.as_str(), commands[0].args.len()), ("X={a,b}", 0));
     }
 
     #[test]
@@ -1131,4 +2142,107 @@ mod basic_tests {
         assert_eq!(commands.len(), 1);
         assert!(commands[0].stdin_file.is_some());
     }
+
+    fn heredoc_body(line: &str) -> String {
+        let commands = Parser::parse(line).unwrap();
+        match &commands[0].stdin_file {
+            Some(Redirection::HereDoc(body)) => body.clone(),
+            other => panic!("expected a here-document, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_here_document_expansion() {
+        std::env::set_var("ZIRON_HEREDOC_NAME", "world");
+        assert_eq!(heredoc_body("cat <<EOF\nhello $ZIRON_HEREDOC_NAME\n${ZIRON_HEREDOC_NAME}!\nEOF"), "hello world\nworld!\n");
+        assert_eq!(heredoc_body("cat << EOF\ncost: \\$5 * 2 = $((5 * 2))\nEOF"), "cost: $5 * 2 = 10\n");
+
+        // A quoted delimiter keeps the body literal
+        assert_eq!(heredoc_body("cat <<'EOF'\nhello $ZIRON_HEREDOC_NAME\nEOF"), "hello $ZIRON_HEREDOC_NAME\n");
+        assert_eq!(heredoc_body("cat <<\"EOF\"\n$(echo no)\nEOF"), "$(echo no)\n");
+    }
+
+    #[test]
+    fn test_here_document_strip_tabs() {
+        assert_eq!(heredoc_body("cat <<-END\n\tindented\n\t\tmore\n\tEND"), "indented\nmore\n");
+        assert_eq!(heredoc_body("cat <<END\n\tkept\nEND"), "\tkept\n");
+    }
+
+    #[test]
+    fn test_unterminated_here_document() {
+        let err = Parser::parse("cat <<EOF\nline").unwrap_err();
+        assert!(err.to_string().contains("wanted `EOF`"), "{}", err);
+        assert!(Parser::parse("cat <<A <<B\none\nA\ntwo").is_err());
+    }
+
+    #[test]
+    fn test_needs_heredoc_lines() {
+        assert!(Parser::needs_heredoc_lines("cat <<EOF"));
+        assert!(Parser::needs_heredoc_lines("cat <<EOF\nline"));
+        assert!(!Parser::needs_heredoc_lines("cat <<EOF\nline\nEOF"));
+        assert!(!Parser::needs_heredoc_lines("cat <<< EOF"));
+        assert!(!Parser::needs_heredoc_lines("echo '<<EOF'"));
+        assert!(Parser::needs_heredoc_lines("cat <<A <<B\nA"));
+        assert_eq!(
+            Parser::heredocs("cat <<-'END' | tr a-z A-Z"),
+            vec![HereDoc { delimiter: "END".to_string(), strip_tabs: true, quoted: true }]
+        );
+    }
+
+    fn list(line: &str) -> Vec<(ListOperator, String)> {
+        Parser::parse_list(line).unwrap().pipelines
+    }
+
+    #[test]
+    fn test_parse_list() {
+        use ListOperator::*;
+        assert_eq!(list("true && echo yes"), vec![(Seq, "true".to_string()), (And, "echo yes".to_string())]);
+        assert_eq!(list("false || echo no"), vec![(Seq, "false".to_string()), (Or, "echo no".to_string())]);
+        assert_eq!(list("echo a; echo b;"), vec![(Seq, "echo a".to_string()), (Seq, "echo b".to_string())]);
+        assert_eq!(
+            list("ls 2>&1 | grep x && echo \"a && b\" || echo 'c; d' $(echo e; echo f)"),
+            vec![
+                (Seq, "ls 2>&1 | grep x".to_string()),
+                (And, "echo \"a && b\"".to_string()),
+                (Or, "echo 'c; d' $(echo e; echo f)".to_string()),
+            ]
+        );
+        assert_eq!(list("echo a\\; b"), vec![(Seq, "echo a\\; b".to_string())]);
+
+        assert!(Parser::parse_list("&& echo a").is_err());
+        assert!(Parser::parse_list("echo a ||").is_err());
+        assert!(Parser::parse_list("echo a && ; echo b").is_err());
+    }
+
+    #[test]
+    fn test_parse_list_here_documents() {
+        use ListOperator::*;
+        assert_eq!(
+            list("cat <<A && echo x; cat <<B\none\nA\ntwo\nB"),
+            vec![
+                (Seq, "cat <<A\none\nA".to_string()),
+                (And, "echo x".to_string()),
+                (Seq, "cat <<B\ntwo\nB".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glob_expands_to_every_match() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["b.txt", "c.txt", "a.txt", "notes.md"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let dir = dir.path().display();
+
+        let commands = Parser::parse(&format!("ls {}/*.txt", dir)).unwrap();
+        assert_eq!(
+            commands[0].args,
+            vec![format!("{}/a.txt", dir), format!("{}/b.txt", dir), format!("{}/c.txt", dir)]
+        );
+
+        // No matches and quoted patterns stay literal
+        let commands = Parser::parse(&format!("ls {0}/*.rs '{0}/*.txt'", dir)).unwrap();
+        assert_eq!(commands[0].args, vec![format!("{}/*.rs", dir), format!("{}/*.txt", dir)]);
+    }
 }
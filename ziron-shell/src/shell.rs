@@ -10,7 +10,7 @@ use rustyline::config::{CompletionType, Configurer};
 use rustyline::Editor;
 use ziron_core::config::Config;
 use ziron_core::error::{Error, Result};
-use ziron_core::module::ModuleContext;
+use ziron_core::module::{ModuleContext, ModuleRegistry};
 use ziron_core::prompt::PromptRenderer;
 
 /// Main Ziron shell
@@ -18,7 +18,13 @@ pub struct ZironShell {
     #[allow(dead_code)]
     config: Config,
     renderer: PromptRenderer,
+    registry: ModuleRegistry,
+    socket_path: Option<std::path::PathBuf>, // Daemon socket used when shell.use_daemon is set
     editor: Editor<ZironCompleter, DefaultHistory>,
+    history_entries: std::sync::Arc<std::sync::Mutex<Vec<String>>>, // Shared with the Ctrl-R handler
+    history_path: Option<std::path::PathBuf>,
+    history_ignore: Vec<regex::Regex>, // Commands never recorded in the history
+    interactive: bool, // Running the readline loop (not `-c`), so `exit` saves the history
     executor: Executor,
     completer: ZironCompleter,
     aliases: std::collections::HashMap<String, String>,
@@ -27,6 +33,9 @@ pub struct ZironShell {
     job_manager: JobManager,
     script_args: Vec<String>, // Script arguments ($1, $2, etc.)
     last_exit_code: i32, // Last command exit code ($?)
+    exit_warned: bool, // Unfinished jobs were reported by the previous exit
+    session_start_ms: u64, // When this shell started, for the session module
+    last_duration_ms: Option<u64>, // How long the last command line ran, for the timer module
 }
 
 impl ZironShell {
@@ -34,23 +43,60 @@ impl ZironShell {
     pub fn new(config: Config, renderer: PromptRenderer) -> Result<Self> {
         let mut completer = ZironCompleter::new();
         completer.set_partial_completion(config.completion.partial_completion);
+        completer.load_config_completions(&config.completion.commands, config.performance.command_timeout());
         let mut editor = Editor::new()
             .map_err(|e| Error::Config(format!("Failed to initialize line editor: {}", e)))?;
         
         // Configure multi-column completion display with menu navigation
         // Circular type enables menu navigation with arrow keys
         editor.set_completion_type(CompletionType::Circular);
-        let _ = editor.set_max_history_size(10000); // Ignore errors for history size
+        let _ = editor.set_max_history_size(config.shell.history_size); // Ignore errors for history size
+        let _ = editor.set_history_ignore_dups(config.shell.history_ignore_dups);
         editor.set_completion_prompt_limit(100); // Limit completion items shown
         
         editor.set_helper(Some(completer.clone()));
 
-        let executor = Executor::new();
+        let history_entries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        crate::history::bind_history_search(&mut editor, config.shell.history_search, history_entries.clone());
 
-        Ok(Self {
+        // Session start for the session module, sent with every prompt context
+        let session_start_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+
+        let mut executor = Executor::new();
+        executor.set_suggest_commands(config.shell.suggest_commands);
+        for name in executor.set_disabled_builtins(&config.shell.disabled_builtins) {
+            eprintln!("Ignoring disabled_builtins entry '{}': it can't be disabled", name);
+        }
+
+        let mut registry = ModuleRegistry::with_builtins();
+        ziron_modules::register_builtin_modules(&mut registry);
+
+        let history_path = config.history_path();
+        let history_ignore = config
+            .shell
+            .history_ignore_patterns
+            .iter()
+            .filter_map(|pattern| match regex::Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    eprintln!("Invalid history_ignore_patterns entry '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        let mut shell = Self {
             config,
             renderer,
+            registry,
+            socket_path: Config::socket_path().ok(),
             editor,
+            history_entries,
+            history_path,
+            history_ignore,
+            interactive: false,
             executor,
             completer,
             aliases: std::collections::HashMap::new(),
@@ -59,36 +105,74 @@ impl ZironShell {
             job_manager: JobManager::new(),
             script_args: Vec::new(),
             last_exit_code: 0,
-        })
+            exit_warned: false,
+            session_start_ms,
+            last_duration_ms: None,
+        };
+
+        shell.load_profile();
+
+        Ok(shell)
+    }
+
+    /// Source the user's profile script (exports, aliases, functions), if present
+    fn load_profile(&mut self) {
+        if let Some(profile) = self.config.profile_path() {
+            if let Err(e) = self.source_file(&profile) {
+                eprintln!("Error loading profile {}: {}", profile.display(), e);
+            }
+        }
+    }
+
+    /// Execute each line of a file in the current shell
+    fn source_file(&mut self, path: &std::path::Path) -> Result<()> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Failed to read script: {}", e)))?;
+        // Execute each line
+        for line in script_lines(&content) {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                if let Err(e) = self.execute_line(line) {
+                    eprintln!("Error executing line: {}", e);
+                }
+            }
+        }
+        Ok(())
     }
 
 
-    /// Run the shell main loop
-    pub fn run(&mut self) -> Result<()> {
-        // Load history (try to load, ignore errors)
-        let history_path = std::path::PathBuf::from(
-            std::env::var("HOME").unwrap_or_else(|_| ".".to_string())
-        ).join(".ziron_history");
-        let _ = self.editor.load_history(&history_path);
+    /// Run the shell main loop, returning the last command's exit status
+    pub fn run(&mut self) -> Result<i32> {
+        self.interactive = true;
+        self.load_history();
 
         loop {
-            // Render prompt
-            let prompt_str = match self.render_prompt() {
-                Ok(prompt) => prompt,
-                Err(e) => {
-                    eprintln!("Error rendering prompt: {}", e);
-                    "ziron> ".to_string()
-                }
-            };
+            // Title/cwd reports go straight to the terminal so rustyline doesn't count them as columns
+            let (reports, prompt_str) = self.next_prompt();
+            if !reports.is_empty() {
+                use std::io::Write;
+                let mut stdout = std::io::stdout();
+                let _ = stdout.write_all(reports.as_bytes());
+                let _ = stdout.flush();
+            }
 
             // Use readline with the prompt - rustyline will handle display
             match self.editor.readline(&prompt_str) {
-                Ok(line) => {
-                    // Add to history
-                    let _ = self.editor.add_history_entry(line.as_str());
+                Ok(mut line) => {
+                    // Read here-document bodies up to their delimiters
+                    while Parser::needs_heredoc_lines(&line) {
+                        match self.editor.readline("> ") {
+                            Ok(next) => {
+                                line.push('\n');
+                                line.push_str(&next);
+                            }
+                            Err(_) => break,
+                        }
+                    }
+                    self.add_history(&line);
 
                     // Parse and execute
-                    if let Err(e) = self.execute_line(&line) {
+                    if let Err(e) = self.execute_line_with_hooks(&line) {
                         eprintln!("Error: {}", e);
                     }
                 }
@@ -109,108 +193,160 @@ impl ZironShell {
             }
         }
 
-        // Save history
-        let history_path = std::path::PathBuf::from(
-            std::env::var("HOME").unwrap_or_else(|_| ".".to_string())
-        ).join(".ziron_history");
-        let _ = self.editor.save_history(&history_path);
+        self.save_history();
 
-        Ok(())
+        Ok(self.last_exit_code)
     }
 
-    /// Render the prompt
-    fn render_prompt(&self) -> Result<String> {
-        use ziron_core::module::ModuleData;
-        
-        let context = ModuleContext::from_env()?;
-        let mut module_data = Vec::new();
-
-        // Get modules from theme segments (what should be displayed)
-        let modules_to_fetch: Vec<String> = if !self.renderer.theme().segments.is_empty() {
-            self.renderer.theme().segments.iter()
-                .map(|s| s.module.clone())
-                .collect()
-        } else {
-            self.config.modules.clone()
-        };
+    /// Execute a single command line without the interactive loop, returning its exit status
+    pub fn run_once(&mut self, line: &str) -> i32 {
+        if let Err(e) = self.execute_line(line) {
+            eprintln!("ziron-shell: {}", e);
+            if self.last_exit_code == 0 {
+                self.last_exit_code = 1;
+            }
+        }
+        self.last_exit_code
+    }
+
+    /// Load the history file (missing or unreadable files are ignored)
+    fn load_history(&mut self) {
+        if let Some(history_path) = &self.history_path {
+            let _ = self.editor.load_history(history_path);
+        }
+        if let Ok(mut entries) = self.history_entries.lock() {
+            *entries = self.editor.history().iter().cloned().collect();
+        }
+    }
 
-        // Fetch data from all modules
-        for module_name in &modules_to_fetch {
-            if let Some(data) = Self::fetch_module_data(module_name, &context)? {
-                module_data.push(data);
+    /// Save the history file, creating its directory if needed
+    fn save_history(&mut self) {
+        if let Some(history_path) = &self.history_path {
+            if let Some(parent) = history_path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            if let Err(e) = self.editor.save_history(history_path) {
+                eprintln!("Failed to save history to {}: {}", history_path.display(), e);
             }
         }
+    }
 
-        // Render using theme
-        let prompt = self.renderer.render(&context, &module_data)?;
-        Ok(prompt)
+    /// Add a line to the history and to the entries Ctrl-R searches
+    fn add_history(&mut self, line: &str) {
+        if self.history_ignore.iter().any(|pattern| pattern.is_match(line)) {
+            return;
+        }
+        if let Ok(true) = self.editor.add_history_entry(line) {
+            if let Ok(mut entries) = self.history_entries.lock() {
+                entries.push(line.to_string());
+            }
+        }
     }
 
-    /// Fetch module data (same logic as daemon)
-    fn fetch_module_data(module_name: &str, context: &ModuleContext) -> Result<Option<ziron_core::module::ModuleData>> {
-        use ziron_core::module::ModuleData;
-        
-        let result = match module_name {
-            "symbol" => {
-                Ok(ModuleData {
-                    module: "symbol".to_string(),
-                    data: serde_json::json!({"text": " ⚡"}),
-                    cached: false,
-                })
-            }
-            "sysinfo" => {
-                Ok(ModuleData {
-                    module: "sysinfo".to_string(),
-                    data: serde_json::json!({
-                        "text": format!("{}@{}", context.user, context.hostname)
-                    }),
-                    cached: false,
-                })
-            }
-            "cwd" => {
-                let cwd = context.current_dir.display().to_string();
-                let home = std::env::var("HOME").unwrap_or_else(|_| format!("/home/{}", context.user));
-                let cwd_short = if cwd.starts_with(&home) {
-                    cwd.replace(&home, "~")
-                } else {
-                    cwd
-                };
-                Ok(ModuleData {
-                    module: "cwd".to_string(),
-                    data: serde_json::json!({"text": cwd_short}),
-                    cached: false,
-                })
-            }
-            "git" => ziron_module_git::GitModule::fetch_data(context),
-            "exitcode" => exitcode::ExitCodeModule::fetch_data(context),
-            "timer" => timer::TimerModule::fetch_data(context),
-            "time" => time::TimeModule::fetch_data(context),
-            "venv" => venv::VenvModule::fetch_data(context),
-            "node" => node::NodeModule::fetch_data(context),
-            "rust" => rust::RustModule::fetch_data(context),
-            "conda" => conda::CondaModule::fetch_data(context),
-            "svn" => ziron_module_svn::SvnModule::fetch_data(context),
-            "mercurial" => ziron_module_mercurial::MercurialModule::fetch_data(context),
-            "docker" => ziron_module_docker::DockerModule::fetch_data(context),
-            "kubernetes" => ziron_module_kubernetes::KubernetesModule::fetch_data(context),
-            "aws" => ziron_module_aws::AwsModule::fetch_data(context),
-            "gcp" => ziron_module_gcp::GcpModule::fetch_data(context),
-            "azure" => ziron_module_azure::AzureModule::fetch_data(context),
-            "terraform" => ziron_module_terraform::TerraformModule::fetch_data(context),
-            "go" => ziron_module_go::GoModule::fetch_data(context),
-            _ => {
-                // Unknown module, return None
-                return Ok(None);
+    /// Render the next prompt as its terminal reports (title, cwd) and the text readline shows
+    fn next_prompt(&self) -> (String, String) {
+        let prompt = match self.render_prompt() {
+            Ok(prompt) => prompt,
+            Err(e) => {
+                eprintln!("Error rendering prompt: {}", e);
+                "ziron> ".to_string()
             }
         };
-        
-        match result {
-            Ok(data) => Ok(Some(data)),
-            Err(e) => {
-                tracing::warn!("Error fetching data for module {}: {}", module_name, e);
-                Ok(None)
+        let (reports, prompt) = ziron_core::prompt::split_terminal_reports(&prompt);
+        (reports.to_string(), prompt.to_string())
+    }
+
+    /// Render the prompt, through the daemon when enabled and locally otherwise
+    fn render_prompt(&self) -> Result<String> {
+        let context = self.prompt_context()?;
+
+        if self.config.shell.use_daemon {
+            match self.render_prompt_via_daemon(&context) {
+                Ok(prompt) => return Ok(prompt),
+                Err(e) => tracing::debug!("Daemon unavailable, rendering prompt locally: {}", e),
+            }
+        }
+
+        ziron_core::render_prompt_with(&self.registry, &self.config, self.renderer.theme(), &context)
+    }
+
+    /// Module context for the next prompt, with the state this shell tracks itself
+    fn prompt_context(&self) -> Result<ModuleContext> {
+        let mut context = ModuleContext::from_env()?;
+        context.exit_code = Some(self.last_exit_code);
+        context.cmd_duration_ms = self.last_duration_ms;
+        context.session_start_ms = Some(self.session_start_ms);
+        Ok(context)
+    }
+
+    /// Request a rendered prompt from the daemon over its Unix socket
+    fn render_prompt_via_daemon(&self, context: &ModuleContext) -> Result<String> {
+        use ziron_core::ipc::{Request, Response};
+
+        match self.daemon_request(Request::GetPrompt { context: context.clone() })? {
+            Response::Prompt(prompt) => Ok(prompt),
+            Response::Error(e) => Err(Error::Config(e)),
+            _ => Err(Error::Config("Unexpected response from daemon".to_string())),
+        }
+    }
+
+    /// Tell the daemon about a change of directory, so it can pre-warm the new directory's modules
+    fn report_directory_change(&self) {
+        if !self.config.shell.use_daemon {
+            return;
+        }
+        let result = self
+            .prompt_context()
+            .and_then(|context| self.daemon_request(ziron_core::ipc::Request::DirectoryChanged { context }));
+        if let Err(e) = result {
+            tracing::debug!("Could not report directory change to daemon: {}", e);
+        }
+    }
+
+    /// Send one request to the daemon over its Unix socket and read the response
+    fn daemon_request(&self, request: ziron_core::ipc::Request) -> Result<ziron_core::ipc::Response> {
+        use std::os::unix::net::UnixStream;
+
+        let socket_path = self
+            .socket_path
+            .as_ref()
+            .ok_or_else(|| Error::Config("Daemon socket path unknown".to_string()))?;
+        let mut stream = UnixStream::connect(socket_path)?;
+        let timeout = Some(std::time::Duration::from_millis(500));
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+
+        ziron_core::ipc::request(&mut stream, request)
+    }
+
+    /// Execute a command line surrounded by the configured preexec/precmd hooks
+    fn execute_line_with_hooks(&mut self, line: &str) -> Result<()> {
+        let preexec = self.config.shell.preexec.clone();
+        self.run_hooks("preexec", &preexec);
+
+        let dir = std::env::current_dir().ok();
+        let started = std::time::Instant::now();
+        let result = self.execute_line(line);
+        self.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+        if std::env::current_dir().ok() != dir {
+            self.report_directory_change();
+        }
+
+        let precmd = self.config.shell.precmd.clone();
+        self.run_hooks("precmd", &precmd);
+        result
+    }
+
+    /// Run hook commands; a failing hook is reported but never aborts the shell
+    fn run_hooks(&mut self, kind: &str, hooks: &[String]) {
+        // Hooks don't change the status of the command they surround
+        let exit_code = self.last_exit_code;
+        for hook in hooks {
+            if let Err(e) = self.execute_line(hook) {
+                eprintln!("Error in {} hook '{}': {}", kind, hook, e);
             }
         }
+        self.last_exit_code = exit_code;
     }
 
     /// Execute a command line
@@ -221,6 +357,35 @@ impl ZironShell {
             return Ok(());
         }
 
+        // C-style arithmetic for loop; its header and body have `;` of their own
+        if let Some(for_loop) = Parser::parse_arith_for(&self.expand_aliases(line))? {
+            return self.execute_arith_for(&for_loop);
+        }
+
+        // Pipelines joined by `&&`, `||` and `;`, each skipped or run on the last exit status
+        let list = Parser::parse_list(line)?;
+        let last = list.pipelines.len().saturating_sub(1);
+        for (i, (operator, pipeline)) in list.pipelines.iter().enumerate() {
+            if !operator.should_run(self.last_exit_code) {
+                continue;
+            }
+            match self.execute_pipeline_line(pipeline) {
+                // A failed pipeline doesn't stop the list, so `cmd || fallback` works
+                Err(e) if i < last => {
+                    eprintln!("ziron-shell: {}", e);
+                    if self.last_exit_code == 0 {
+                        self.last_exit_code = 1;
+                    }
+                }
+                result => result?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse and execute a single pipeline
+    fn execute_pipeline_line(&mut self, line: &str) -> Result<()> {
         // Check for alias expansion
         let expanded_line = self.expand_aliases(line);
 
@@ -233,25 +398,60 @@ impl ZironShell {
         }
 
         // Create expansion context with script arguments and last exit code
+        let mut limits = crate::parser::ExpansionLimits::default();
+        if let Some(timeout_ms) = self.config.shell.substitution_timeout_ms {
+            limits.substitution_timeout = std::time::Duration::from_millis(timeout_ms);
+        }
+        if let Some(max_output) = self.config.shell.substitution_max_output {
+            limits.max_substitution_output = max_output;
+        }
         let expansion_ctx = crate::parser::ExpansionContext {
             script_args: self.script_args.clone(),
             last_exit_code: Some(self.last_exit_code),
+            ifs: std::env::var("IFS").unwrap_or_else(|_| crate::parser::DEFAULT_IFS.to_string()),
+            limits,
+            ..Default::default()
         };
         
         // Parse command
-        let commands = Parser::parse_with_context(&expanded_line, &expansion_ctx)?;
+        let pipeline = Parser::parse_pipeline_with_context(&expanded_line, &expansion_ctx)?;
+        if pipeline.background {
+            self.exit_warned = false;
+            return self.execute_background(&pipeline);
+        }
+        if pipeline.commands.len() > 1 {
+            self.exit_warned = false;
+            let result = self.executor.execute_pipeline(&pipeline.commands);
+            return self.record_status(result);
+        }
 
         // Execute commands
-        for command in commands {
+        for command in pipeline.commands {
+            // The job warning only holds off an exit that immediately follows it
+            if command.name != "exit" {
+                self.exit_warned = false;
+            }
+
+            // Plain assignment (NAME=value); its status is that of any command substitution
+            if command.args.is_empty() && command.stdin.is_none() && Parser::is_assignment(&command.name) {
+                if let Some((name, value)) = command.name.split_once('=') {
+                    std::env::set_var(name, value);
+                }
+                self.last_exit_code = expansion_ctx.substitution_status.get().unwrap_or(0);
+                continue;
+            }
+
             // Check if command is a builtin that needs shell state
             match command.name.as_str() {
-                "alias" | "unalias" | "pushd" | "popd" | "dirs" | "source" | "jobs" | "fg" | "bg" | "kill" | "wait" => {
+                "exit" | "alias" | "unalias" | "function" | "pushd" | "popd" | "dirs" | "source" | "jobs" | "fg" | "bg" | "kill" | "wait" | "set" | "type"
+                    if self.executor.is_builtin(&command.name) =>
+                {
                     self.execute_builtin_with_state(&command)?;
                 }
                 "cd" => {
                     // Track directory changes for pushd/popd
                     let current_dir = std::env::current_dir().ok();
-                    self.executor.execute(&command)?;
+                    self.execute_with_status(&command)?;
                     if let Some(dir) = current_dir {
                         // Don't add if it's the same directory
                         if let Ok(new_dir) = std::env::current_dir() {
@@ -262,19 +462,7 @@ impl ZironShell {
                     }
                 }
                 _ => {
-                    // Check if command should run in background
-                    let is_background = command.args.last()
-                        .map(|arg| arg == "&")
-                        .unwrap_or(false);
-                    
-                    if is_background {
-                        // Remove & from args
-                        let mut bg_command = command.clone();
-                        bg_command.args.pop();
-                        self.execute_background(&bg_command)?;
-                    } else {
-                        self.executor.execute(&command)?;
-                    }
+                    self.execute_with_status(&command)?;
                 }
             }
         }
@@ -282,6 +470,47 @@ impl ZironShell {
         Ok(())
     }
 
+    /// Execute a command through the executor, recording its exit status
+    fn execute_with_status(&mut self, command: &crate::command::Command) -> Result<()> {
+        let result = self.executor.execute(command);
+        self.record_status(result)
+    }
+
+    /// Store the exit status of a command that ran, or the status for why it couldn't
+    fn record_status(&mut self, result: Result<i32>) -> Result<()> {
+        match result {
+            Ok(code) => {
+                self.last_exit_code = code;
+                Ok(())
+            }
+            Err(e) => {
+                self.last_exit_code = crate::executor::exit_status(&e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Execute a C-style `for (( init; cond; step ))` loop
+    fn execute_arith_for(&mut self, for_loop: &crate::parser::ArithFor) -> Result<()> {
+        if !for_loop.init.is_empty() {
+            Parser::evaluate_arithmetic_statement(&for_loop.init)?;
+        }
+
+        // An empty condition loops forever, as in bash
+        while for_loop.condition.is_empty()
+            || Parser::evaluate_arithmetic_statement(&for_loop.condition)? != 0
+        {
+            for statement in &for_loop.body {
+                self.execute_line(statement)?;
+            }
+            if !for_loop.step.is_empty() {
+                Parser::evaluate_arithmetic_statement(&for_loop.step)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Expand aliases in command line
     fn expand_aliases(&self, line: &str) -> String {
         let words: Vec<&str> = line.split_whitespace().collect();
@@ -301,31 +530,131 @@ impl ZironShell {
         }
     }
 
+    /// An alias's definition as `alias` prints it (`alias ll='ls -la'`)
+    fn alias_definition(&self, name: &str) -> Option<String> {
+        self.aliases.get(name).map(|value| format!("alias {}='{}'", name, value))
+    }
+
+    /// What `name` resolves to, in the order the shell looks it up
+    ///
+    /// Only the first match is returned unless `all` is set, in which case every
+    /// matching executable on `PATH` is listed too.
+    fn describe_command(&self, name: &str, all: bool) -> Vec<String> {
+        let mut descriptions = Vec::new();
+        if let Some(value) = self.aliases.get(name) {
+            descriptions.push(format!("{} is an alias for {}", name, value));
+        }
+        if self.functions.contains_key(name) {
+            descriptions.push(format!("{} is a function", name));
+        }
+        if self.executor.is_builtin(name) {
+            descriptions.push(format!("{} is a shell builtin", name));
+        }
+        if all || descriptions.is_empty() {
+            if let Ok(path) = std::env::var("PATH") {
+                for dir in path.split(':').filter(|dir| !dir.is_empty()) {
+                    let full_path = std::path::Path::new(dir).join(name);
+                    if full_path.is_file() {
+                        descriptions.push(format!("{} is {}", name, full_path.display()));
+                    }
+                }
+            }
+        }
+
+        if !all {
+            descriptions.truncate(1);
+        }
+        descriptions
+    }
+
     /// Execute built-in commands that need shell state
     fn execute_builtin_with_state(&mut self, command: &crate::command::Command) -> Result<()> {
         match command.name.as_str() {
+            "exit" => {
+                // `exit` without a status exits with the last command's
+                let code = crate::executor::exit_code_arg(command, self.last_exit_code)?;
+                if self.confirm_exit() {
+                    if self.interactive {
+                        self.save_history();
+                    }
+                    std::process::exit(code);
+                }
+                Ok(())
+            }
             "alias" => {
                 if command.args.is_empty() {
                     // List all aliases
-                    for (name, value) in &self.aliases {
-                        println!("alias {}='{}'", name, value);
+                    for name in self.aliases.keys() {
+                        println!("{}", self.alias_definition(name).unwrap_or_default());
                     }
-                } else {
-                    // Set alias
-                    for arg in &command.args {
-                        if let Some((name, value)) = arg.split_once('=') {
-                            self.aliases.insert(name.to_string(), value.to_string());
-                            self.completer.add_alias(name.to_string());
-                            // Update editor's helper
-                            if let Some(helper) = self.editor.helper_mut() {
-                                helper.add_alias(name.to_string());
-                            }
+                    return Ok(());
+                }
+
+                let mut missing = Vec::new();
+                for arg in &command.args {
+                    if let Some((name, value)) = arg.split_once('=') {
+                        // Set alias
+                        self.aliases.insert(name.to_string(), value.to_string());
+                        self.completer.add_alias(name.to_string());
+                        // Update editor's helper
+                        if let Some(helper) = self.editor.helper_mut() {
+                            helper.add_alias(name.to_string());
                         }
+                    } else {
+                        // Query a single alias
+                        match self.alias_definition(arg) {
+                            Some(definition) => println!("{}", definition),
+                            None => missing.push(arg.as_str()),
+                        }
+                    }
+                }
+                match missing.as_slice() {
+                    [] => Ok(()),
+                    names => Err(Error::Config(format!("alias: {}: not found", names.join(", ")))),
+                }
+            }
+            "type" => {
+                let all = command.args.first().is_some_and(|arg| arg == "-a");
+                let names = if all { &command.args[1..] } else { &command.args[..] };
+                for name in names {
+                    let descriptions = self.describe_command(name, all);
+                    if descriptions.is_empty() {
+                        println!("{}: not found", name);
+                    }
+                    for description in descriptions {
+                        println!("{}", description);
+                    }
+                }
+                Ok(())
+            }
+            "set" => {
+                let mut args = command.args.iter();
+                while let Some(arg) = args.next() {
+                    let enable = arg.starts_with('-');
+                    match arg.as_str() {
+                        "-C" | "+C" => self.executor.set_noclobber(enable),
+                        "-o" | "+o" => match args.next().map(|s| s.as_str()) {
+                            Some("noclobber") => self.executor.set_noclobber(enable),
+                            Some(option) => return Err(Error::Config(format!("set: {}: invalid option name", option))),
+                            None => {
+                                let state = if self.executor.noclobber() { "on" } else { "off" };
+                                println!("noclobber\t{}", state);
+                            }
+                        },
+                        _ => return Err(Error::Config(format!("set: {}: invalid option", arg))),
                     }
                 }
                 Ok(())
             }
             "unalias" => {
+                if command.args.first().is_some_and(|arg| arg == "-a") {
+                    self.aliases.clear();
+                    self.completer.clear_aliases();
+                    if let Some(helper) = self.editor.helper_mut() {
+                        helper.clear_aliases();
+                    }
+                    return Ok(());
+                }
                 for arg in &command.args {
                     self.aliases.remove(arg);
                     self.completer.remove_alias(arg);
@@ -390,17 +719,7 @@ impl ZironShell {
             }
             "source" => {
                 if let Some(script_path) = command.args.first() {
-                    let content = std::fs::read_to_string(script_path)
-                        .map_err(|e| Error::Config(format!("Failed to read script: {}", e)))?;
-                    // Execute each line
-                    for line in content.lines() {
-                        let line = line.trim();
-                        if !line.is_empty() && !line.starts_with('#') {
-                            if let Err(e) = self.execute_line(line) {
-                                eprintln!("Error executing line: {}", e);
-                            }
-                        }
-                    }
+                    self.source_file(std::path::Path::new(script_path))?;
                 }
                 Ok(())
             }
@@ -481,7 +800,7 @@ impl ZironShell {
                         }
                     } else {
                         // Process kill - handled by executor
-                        return self.executor.execute(command);
+                        return self.execute_with_status(command);
                     }
                 }
                 Ok(())
@@ -512,6 +831,31 @@ impl ZironShell {
         }
     }
 
+    /// Check for unfinished jobs before exiting; the first attempt only warns
+    ///
+    /// Like bash, only interactive shells warn.
+    fn confirm_exit(&mut self) -> bool {
+        use crate::jobs::JobStatus;
+
+        if self.exit_warned || !self.interactive {
+            return true;
+        }
+
+        #[cfg(unix)]
+        self.job_manager.reap_finished();
+        let jobs = self.job_manager.list_jobs();
+        if jobs.iter().any(|job| job.status == JobStatus::Stopped) {
+            eprintln!("There are stopped jobs.");
+        } else if jobs.iter().any(|job| job.status == JobStatus::Running) {
+            eprintln!("There are running jobs.");
+        } else {
+            return true;
+        }
+
+        self.exit_warned = true;
+        false
+    }
+
     fn builtin_dirs(&self) {
         let current = std::env::current_dir().unwrap_or_default();
         print!("{}", current.display());
@@ -529,7 +873,7 @@ impl ZironShell {
         let mut script_exit_code = 0;
         
         // Execute each line
-        for line in content.lines() {
+        for line in script_lines(&content) {
             let line = line.trim();
             if line.is_empty() || line.starts_with('#') {
                 continue;
@@ -551,26 +895,56 @@ impl ZironShell {
         Ok(())
     }
 
-    /// Execute a command in the background
-    fn execute_background(&mut self, command: &crate::command::Command) -> Result<()> {
+    /// Execute a pipeline in the background, piping each command into the next
+    fn execute_background(&mut self, pipeline: &crate::command::Pipeline) -> Result<()> {
         use std::process::{Command as ProcessCommand, Stdio};
         use crate::jobs::JobStatus;
-        
-        let mut process = ProcessCommand::new(&command.name);
-        process.args(&command.args);
-        process.stdin(Stdio::null());
-        process.stdout(Stdio::null());
-        process.stderr(Stdio::null());
-        
-        // Spawn process
-        let mut child = process.spawn()
-            .map_err(|e| Error::Config(format!("Failed to spawn process: {}", e)))?;
-        
+
+        if pipeline.commands.is_empty() {
+            return Ok(());
+        }
+
+        let mut children: Vec<std::process::Child> = Vec::new();
+        let mut previous_stdout = None;
+        for (i, command) in pipeline.commands.iter().enumerate() {
+            let mut process = ProcessCommand::new(&command.name);
+            process.args(&command.args);
+            process.stdin(match previous_stdout.take() {
+                Some(stdout) => Stdio::from(stdout),
+                None => Stdio::null(),
+            });
+            let last = i + 1 == pipeline.commands.len();
+            process.stdout(if last { Stdio::null() } else { Stdio::piped() });
+            process.stderr(Stdio::null());
+
+            // Spawn process
+            let mut child = match process.spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    // Don't leave the stages already started running unreaped
+                    for mut child in children {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    return Err(Error::Config(format!("Failed to spawn process: {}", e)));
+                }
+            };
+            previous_stdout = child.stdout.take();
+            children.push(child);
+        }
+
+        // The job is tracked by its last process, like `$!`
+        let mut child = children.pop().expect("pipeline has at least one command");
         let pid = child.id();
-        let command_str = format!("{} {}", command.name, command.args.join(" "));
-        
+        let command_str = pipeline
+            .commands
+            .iter()
+            .map(|command| format!("{} {}", command.name, command.args.join(" ")).trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+
         // Add job to job manager
-        let job_id = self.job_manager.add_job(command_str.clone(), pid);
+        let job_id = self.job_manager.add_job(command_str, pid);
         println!("[{}] {}", job_id, pid);
         
         // Check if process completed immediately
@@ -589,8 +963,378 @@ impl ZironShell {
                 self.job_manager.update_job_status(pid, JobStatus::Stopped);
             }
         }
-        
+
+        self.last_exit_code = 0;
         Ok(())
     }
 }
 
+/// Split a script into command lines, keeping here-document bodies with their command
+fn script_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match lines.last_mut() {
+            Some(last) if Parser::needs_heredoc_lines(last) => {
+                last.push('\n');
+                last.push_str(line);
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ziron_core::theme::Theme;
+
+    fn renderer() -> PromptRenderer {
+        let theme: Theme = toml::from_str("[theme]\nname = \"test\"").unwrap();
+        PromptRenderer::new(theme)
+    }
+
+    /// A shell whose profile, history and daemon socket are in a temp dir instead of the user's
+    fn test_shell(mut config: Config, renderer: PromptRenderer) -> (tempfile::TempDir, ZironShell) {
+        let dir = tempfile::TempDir::new().unwrap();
+        if config.shell.profile.is_none() {
+            let profile = dir.path().join("profile");
+            std::fs::write(&profile, "").unwrap();
+            config.shell.profile = Some(profile);
+        }
+        config.shell.history_file.get_or_insert_with(|| dir.path().join("history"));
+        let mut shell = ZironShell::new(config, renderer).unwrap();
+        shell.socket_path = Some(dir.path().join("ziron.sock"));
+        (dir, shell)
+    }
+
+    #[test]
+    fn test_profile_exports_visible_after_construction() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let profile = temp_dir.path().join("profile");
+        std::fs::write(
+            &profile,
+            "# starter profile\nexport ZIRON_TEST_PROFILE_VAR=\"from profile\"\nalias ll='ls -la'\n",
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.shell.profile = Some(profile);
+        let (_dir, shell) = test_shell(config, renderer());
+
+        assert_eq!(std::env::var("ZIRON_TEST_PROFILE_VAR").unwrap(), "from profile");
+        assert_eq!(shell.aliases.get("ll").map(String::as_str), Some("ls -la"));
+    }
+
+    #[test]
+    fn test_history_entries_shared_with_search() {
+        let mut config = Config::default();
+        config.shell.history_search = ziron_core::config::HistorySearch::Fuzzy;
+        let (_dir, mut shell) = test_shell(config, renderer());
+
+        shell.add_history("git status");
+        shell.add_history("cargo build");
+        shell.add_history("cargo build");
+
+        let entries = shell.history_entries.lock().unwrap().clone();
+        assert_eq!(entries, vec!["git status", "cargo build"]);
+        assert_eq!(rustyline::history::History::len(shell.editor.history()), 2);
+        assert_eq!(
+            crate::history::find_match(&entries, "gs", 0),
+            Some("git status".to_string())
+        );
+    }
+
+    #[test]
+    fn test_custom_history_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let history_file = temp_dir.path().join("nested").join("history");
+        let mut config = Config::default();
+        config.shell.history_file = Some(history_file.clone());
+
+        let (_dir, mut shell) = test_shell(config.clone(), renderer());
+        shell.add_history("echo saved");
+        shell.save_history();
+        assert!(std::fs::read_to_string(&history_file).unwrap().contains("echo saved"));
+
+        let (_dir, mut shell) = test_shell(config, renderer());
+        shell.load_history();
+        assert_eq!(*shell.history_entries.lock().unwrap(), vec!["echo saved"]);
+    }
+
+    #[test]
+    fn test_history_ignores_dups_and_patterns() {
+        let mut config = Config::default();
+        config.shell.history_ignore_patterns = vec!["PASSWORD=".to_string(), "^secret ".to_string()];
+        let (_dir, mut shell) = test_shell(config, renderer());
+
+        shell.add_history("ls");
+        shell.add_history("ls");
+        shell.add_history("export PASSWORD=hunter2");
+        shell.add_history("secret show");
+        shell.add_history("echo secret");
+        shell.add_history("ls");
+
+        assert_eq!(*shell.history_entries.lock().unwrap(), vec!["ls", "echo secret", "ls"]);
+    }
+
+    #[test]
+    fn test_prompt_context_session_start() {
+        let (_dir, shell) = test_shell(Config::default(), renderer());
+        let context = shell.prompt_context().unwrap();
+        assert_eq!(context.session_start_ms, Some(shell.session_start_ms));
+        assert!(shell.session_start_ms > 0);
+    }
+
+    #[test]
+    fn test_prompt_shows_last_exit_code() {
+        let theme: Theme = toml::from_str("[theme]\nname = \"test\"\n\n[[segments]]\nmodule = \"exitcode\"").unwrap();
+        let mut config = Config::default();
+        config.shell.use_daemon = false;
+        let (_dir, mut shell) = test_shell(config, PromptRenderer::new(theme));
+
+        shell.execute_line_with_hooks("false").unwrap();
+        let context = shell.prompt_context().unwrap();
+        assert_eq!(context.exit_code, Some(1));
+        assert!(context.cmd_duration_ms.is_some());
+        assert!(shell.render_prompt().unwrap().contains('1'));
+
+        shell.execute_line_with_hooks("true").unwrap();
+        assert!(!shell.render_prompt().unwrap().contains('1'));
+    }
+
+    #[test]
+    fn test_prompt_terminal_reports_split_off() {
+        let theme: Theme =
+            toml::from_str("[theme]\nname = \"test\"\nreport_cwd = true\n\n[[segments]]\nmodule = \"exitcode\"").unwrap();
+        let mut config = Config::default();
+        config.shell.use_daemon = false;
+        let (_dir, mut shell) = test_shell(config, PromptRenderer::new(theme));
+        shell.execute_line("false").unwrap();
+
+        let (reports, prompt) = shell.next_prompt();
+        assert!(reports.starts_with("\x1b]7;file://"));
+        assert!(reports.ends_with('\x07'));
+        assert!(!prompt.contains("\x1b]"));
+        assert!(prompt.contains('1'));
+    }
+
+    #[test]
+    fn test_arith_for_loop_counts() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell
+            .execute_line(r#"for (( ZIRON_FOR_I=0; ZIRON_FOR_I<5; ZIRON_FOR_I++ )); do let "ZIRON_FOR_COUNT = ZIRON_FOR_COUNT + 2"; done"#)
+            .unwrap();
+
+        assert_eq!(std::env::var("ZIRON_FOR_I").unwrap(), "5");
+        assert_eq!(std::env::var("ZIRON_FOR_COUNT").unwrap(), "10");
+    }
+
+    #[test]
+    fn test_render_prompt_falls_back_without_daemon() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let theme: Theme = toml::from_str(
+            "[theme]\nname = \"test\"\n\n[[segments]]\nmodule = \"symbol\"\n",
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        assert!(config.shell.use_daemon);
+        config.modules = Vec::new();
+        let (_dir, mut shell) = test_shell(config, PromptRenderer::new(theme));
+        shell.socket_path = Some(temp_dir.path().join("missing.sock"));
+
+        let prompt = shell.render_prompt().unwrap();
+        assert!(prompt.contains("⚡"), "unexpected prompt: {:?}", prompt);
+    }
+
+    #[test]
+    fn test_directory_change_reported_to_daemon() {
+        use std::io::{Read, Write};
+        use ziron_core::ipc::{Message, MessagePayload, Request, Response};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("ziron.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+        let daemon = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut len_buf = [0u8; 4];
+            stream.read_exact(&mut len_buf).unwrap();
+            let mut buffer = vec![0u8; u32::from_le_bytes(len_buf) as usize];
+            stream.read_exact(&mut buffer).unwrap();
+            stream.write_all(&Message::new_response(0, Response::Ok).to_frame().unwrap()).unwrap();
+            Message::deserialize(&buffer).unwrap().payload
+        });
+
+        let (_dir, mut shell) = test_shell(Config::default(), PromptRenderer::new(Theme::default()));
+        shell.socket_path = Some(socket_path);
+        shell.last_exit_code = 3;
+        shell.report_directory_change();
+
+        match daemon.join().unwrap() {
+            MessagePayload::Request(Request::DirectoryChanged { context }) => {
+                assert_eq!(context.current_dir, std::env::current_dir().unwrap());
+                assert_eq!(context.exit_code, Some(3));
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    /// Command-name completions the line editor offers for `prefix`
+    fn command_completions(shell: &ZironShell, prefix: &str) -> Vec<String> {
+        use rustyline::completion::Completer;
+        let history = DefaultHistory::new();
+        let ctx = rustyline::Context::new(&history);
+        let (_, pairs) = shell.editor.helper().unwrap().complete(prefix, prefix.len(), &ctx).unwrap();
+        pairs.into_iter().map(|pair| pair.replacement).collect()
+    }
+
+    #[test]
+    fn test_alias_query_and_unalias_all() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell.execute_line("alias zironll='ls -la' zirongs='git status'").unwrap();
+
+        assert_eq!(shell.alias_definition("zironll").as_deref(), Some("alias zironll='ls -la'"));
+        shell.execute_line("alias zironll").unwrap();
+        let err = shell.execute_line("alias zironll zironnope").unwrap_err();
+        assert!(err.to_string().contains("alias: zironnope: not found"), "{}", err);
+        assert_eq!(command_completions(&shell, "zironll"), vec!["zironll"]);
+
+        shell.execute_line("unalias -a").unwrap();
+        assert!(shell.aliases.is_empty());
+        assert!(shell.alias_definition("zirongs").is_none());
+        assert!(command_completions(&shell, "ziron").iter().all(|name| !name.starts_with("zironll") && !name.starts_with("zirongs")));
+    }
+
+    #[test]
+    fn test_assignment_keeps_braces() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell.execute_line("ZIRON_BRACE_ASSIGN={a,b}").unwrap();
+        assert_eq!(std::env::var("ZIRON_BRACE_ASSIGN").unwrap(), "{a,b}");
+    }
+
+    #[test]
+    fn test_assignment_from_substitution_sets_status() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+
+        shell.execute_line("ZIRON_SUBST_FAIL=$(echo out; exit 3)").unwrap();
+        assert_eq!(std::env::var("ZIRON_SUBST_FAIL").unwrap(), "out");
+        assert_eq!(shell.last_exit_code, 3);
+
+        shell.execute_line("ZIRON_SUBST_OK=$(true)").unwrap();
+        assert_eq!(std::env::var("ZIRON_SUBST_OK").unwrap(), "");
+        assert_eq!(shell.last_exit_code, 0);
+    }
+
+    #[test]
+    fn test_exit_status_expands_in_dollar_question() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+
+        shell.execute_line("false").unwrap();
+        assert_eq!(shell.last_exit_code, 1);
+        shell.execute_line("ZIRON_LAST_STATUS=$?").unwrap();
+        assert_eq!(std::env::var("ZIRON_LAST_STATUS").unwrap(), "1");
+
+        shell.execute_line("sh -c 'exit 4'").unwrap();
+        shell.execute_line("ZIRON_LAST_STATUS=status:$?").unwrap();
+        assert_eq!(std::env::var("ZIRON_LAST_STATUS").unwrap(), "status:4");
+    }
+
+    #[test]
+    fn test_precmd_hook_runs_after_command() {
+        let mut config = Config::default();
+        config.shell.preexec = vec!["false".to_string()];
+        config.shell.precmd = vec!["ZIRON_PRECMD_SEEN=$ZIRON_HOOKED_CMD".to_string()];
+        let (_dir, mut shell) = test_shell(config, renderer());
+
+        // The failing preexec hook doesn't stop the command or the precmd hook
+        shell.execute_line_with_hooks("ZIRON_HOOKED_CMD=ran").unwrap();
+        assert_eq!(std::env::var("ZIRON_PRECMD_SEEN").unwrap(), "ran");
+    }
+
+    #[test]
+    fn test_background_pipelines_become_jobs() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell.execute_line("sleep 1 &").unwrap();
+        shell.execute_line("echo a | cat &").unwrap();
+        shell.execute_line("true&").unwrap();
+
+        let commands: Vec<String> = shell.job_manager.list_jobs().into_iter().map(|job| job.command).collect();
+        assert_eq!(commands, vec!["sleep 1", "echo a | cat", "true"]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_failed_background_stage_reaps_earlier_ones() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        assert!(shell.execute_line("sleep 29.0417 | ziron-no-such-command &").is_err());
+
+        // The first stage was killed rather than left running
+        let marker = b"sleep\x0029.0417\x00";
+        let leftover = std::fs::read_dir("/proc").unwrap().flatten().any(|entry| {
+            std::fs::read(entry.path().join("cmdline")).is_ok_and(|cmdline| cmdline == marker)
+        });
+        assert!(!leftover);
+        assert!(shell.job_manager.list_jobs().is_empty());
+    }
+
+    #[test]
+    fn test_type_follows_resolution_order() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell.execute_line("alias ll=ls").unwrap();
+        shell.execute_line("function greet echo hi").unwrap();
+        shell.execute_line("alias echo=printf").unwrap();
+
+        assert_eq!(shell.describe_command("ll", false), vec!["ll is an alias for ls"]);
+        assert_eq!(shell.describe_command("greet", false), vec!["greet is a function"]);
+        assert_eq!(shell.describe_command("echo", false), vec!["echo is an alias for printf"]);
+
+        let echo = shell.describe_command("echo", true);
+        assert_eq!(echo[..2], ["echo is an alias for printf", "echo is a shell builtin"]);
+
+        let sh = shell.describe_command("sh", true);
+        assert!(!sh.is_empty());
+        assert!(sh.iter().all(|line| line.starts_with("sh is /")), "{:?}", sh);
+        assert!(shell.describe_command("ziron-no-such-command", true).is_empty());
+    }
+
+    #[test]
+    fn test_exit_blocked_once_by_running_job() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell.interactive = true;
+        assert!(shell.confirm_exit());
+
+        shell.execute_line("sleep 100 &").unwrap();
+        assert!(!shell.confirm_exit());
+        assert!(shell.confirm_exit());
+
+        // Running another command re-arms the warning
+        shell.execute_line("true").unwrap();
+        assert!(!shell.confirm_exit());
+
+        let pid = shell.job_manager.list_jobs()[0].pid;
+        std::process::Command::new("kill").arg(pid.to_string()).status().unwrap();
+    }
+
+    #[test]
+    fn test_finished_job_does_not_block_exit() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell.interactive = true;
+        shell.execute_line("sleep 0.05 &").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+
+        assert!(shell.confirm_exit());
+        assert!(matches!(shell.job_manager.list_jobs()[0].status, crate::jobs::JobStatus::Done(Some(0))));
+    }
+
+    #[test]
+    fn test_exit_not_blocked_without_interaction() {
+        let (_dir, mut shell) = test_shell(Config::default(), renderer());
+        shell.execute_line("sleep 100 &").unwrap();
+        assert!(shell.confirm_exit());
+
+        let pid = shell.job_manager.list_jobs()[0].pid;
+        std::process::Command::new("kill").arg(pid.to_string()).status().unwrap();
+    }
+}
+
@@ -0,0 +1,121 @@
+//! Running a single command line with `ziron-shell -c`
+
+use std::path::Path;
+use std::process::Command;
+
+/// `ziron-shell` with its config, state and home directories inside `dir` and no history overrides
+fn shell(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ziron-shell"));
+    // Themes are looked up relative to the working directory
+    command
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_STATE_HOME", dir.join("state"))
+        .env_remove("HISTFILE")
+        .env_remove("HISTSIZE");
+    command
+}
+
+#[test]
+fn test_once_exits_with_command_status() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let output = shell(dir.path()).args(["-c", "echo $GREETING"]).env("GREETING", "hi").output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hi\n");
+
+    let status = shell(dir.path()).args(["--once", "false"]).status().unwrap();
+    assert_eq!(status.code(), Some(1));
+}
+
+#[test]
+fn test_once_needs_no_theme() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(dir.path().join("config/ziron")).unwrap();
+    std::fs::write(dir.path().join("config/ziron/config.toml"), "theme = \"ziron-no-such-theme\"\n").unwrap();
+
+    let output = shell(dir.path()).current_dir(dir.path()).args(["-c", "echo ok"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
+
+#[test]
+fn test_exit_status_propagates() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let status = |line: &str| shell(dir.path()).args(["-c", line]).status().unwrap().code();
+
+    assert_eq!(status("false"), Some(1));
+    assert_eq!(status("sh -c 'exit 7'"), Some(7));
+    assert_eq!(status("ziron-no-such-command"), Some(127));
+    assert_eq!(status("exit 42"), Some(42));
+    assert_eq!(status("exit nope"), Some(1));
+    // Only interactive shells hold `exit` back for background jobs
+    assert_eq!(status("sleep 0.1 &; exit 7"), Some(7));
+}
+
+#[test]
+fn test_pipeline() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let output = shell(dir.path()).args(["-c", "echo hello | tr a-z A-Z"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "HELLO\n");
+
+    let output = shell(dir.path()).args(["-c", "sh -c 'echo b; echo a' | sort | head -n 1"]).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a\n");
+
+    let status = shell(dir.path()).args(["-c", "echo x | sh -c 'exit 5'"]).status().unwrap();
+    assert_eq!(status.code(), Some(5));
+}
+
+#[test]
+fn test_pipeline_stderr_to_stdout() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let output = shell(dir.path()).args(["-c", "ls /nonexistent-ziron 2>&1 | wc -l"]).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "1");
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = shell(dir.path()).args(["-c", "sh -c 'echo oops >&2' 2>&1 | tr a-z A-Z"]).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "OOPS\n");
+}
+
+#[test]
+fn test_command_lists() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let run = |line: &str| shell(dir.path()).args(["-c", line]).output().unwrap();
+
+    assert_eq!(String::from_utf8_lossy(&run("true && echo yes").stdout), "yes\n");
+    assert_eq!(String::from_utf8_lossy(&run("false && echo yes").stdout), "");
+    assert_eq!(String::from_utf8_lossy(&run("false || echo no").stdout), "no\n");
+    assert_eq!(String::from_utf8_lossy(&run("true || echo no").stdout), "");
+    assert_eq!(String::from_utf8_lossy(&run("echo a; echo b").stdout), "a\nb\n");
+
+    // Each pipeline is expanded when it runs, and skipped ones keep the status
+    assert_eq!(String::from_utf8_lossy(&run("sh -c 'exit 3'; echo $?").stdout), "3\n");
+    assert_eq!(String::from_utf8_lossy(&run("false && echo yes || echo $?").stdout), "1\n");
+    assert_eq!(String::from_utf8_lossy(&run("no-such-command-ziron || echo fallback").stdout), "fallback\n");
+    assert_eq!(run("true; false").status.code(), Some(1));
+}
+
+#[test]
+fn test_here_document() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let output = shell(dir.path())
+        .env("ZIRON_TEST_NAME", "ziron")
+        .args(["-c", "cat <<EOF\nhello $ZIRON_TEST_NAME\nEOF"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello ziron\n");
+
+    let output = shell(dir.path()).args(["-c", "cat <<'EOF' | tr a-z A-Z\n$HOME\nEOF"]).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "$HOME\n");
+
+    let script = dir.path().join("script.zsh");
+    std::fs::write(&script, "cat <<-END\n\tfirst\n\tEND\necho second\n").unwrap();
+    let output = shell(dir.path()).args(["-c", &format!("source {}", script.display())]).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\nsecond\n");
+}